@@ -1,5 +1,8 @@
 #![cfg_attr(feature = "bench", feature(test))]
 
+#[cfg(all(feature = "bench", test))]
+mod common;
+
 #[cfg(all(feature = "bench", test))]
 mod benches {
     extern crate lib_evolvim;
@@ -7,13 +10,10 @@ mod benches {
 
     use self::lib_evolvim::{Board, Brain};
     use self::test::Bencher;
+    use super::common::get_test_board;
 
     const FRAME_TIME_STEP: f64 = 0.001;
 
-    fn get_test_board() -> Board {
-        Board::<Brain>::load_from("assets/test.bin").unwrap()
-    }
-
     #[bench]
     fn bench_board_new_default(b: &mut Bencher) {
         b.iter(|| Board::<Brain>::default());
@@ -1,22 +1,22 @@
 #![cfg_attr(feature = "bench", feature(test))]
 
+#[cfg(all(feature = "bench", test))]
+mod common;
+
 #[cfg(all(feature = "bench", test))]
 mod benches {
     extern crate lib_evolvim;
     extern crate test;
 
-    use self::lib_evolvim::{Board, BoardSize, Brain, Environment, HLSoftBody, SoftBody};
+    use self::lib_evolvim::{BoardSize, Brain, BrainBatch, Environment, HLSoftBody, SoftBody};
     use self::lib_evolvim::{GenerateRandom, NeuralNet, RecombinationInfinite};
     use self::test::Bencher;
+    use super::common::get_test_board;
 
     // const TEST_INPUT: BrainInput = [1., 2., 3., 4., 5., 6., 7., 8., 9.];
     const TEST_BOARD_SIZE: BoardSize = (100, 100);
     const TEST_TIME: f64 = 0.0;
 
-    fn get_test_board() -> Board {
-        Board::<Brain>::load_from("assets/test.bin").unwrap()
-    }
-
     #[bench]
     fn bench_brain_new_random(b: &mut Bencher) {
         b.iter(|| Brain::new_random());
@@ -27,7 +27,7 @@ mod benches {
         let mut board = get_test_board();
         let creature: &mut SoftBody = &mut board.creatures[0].borrow_mut();
         let brain = &mut creature.brain;
-        let env = Environment::new(&board.terrain, &creature.base);
+        let env = Environment::new(&board.terrain, &creature.base, TEST_TIME, &board.climate);
 
         b.iter(|| {
             brain.run_with(&env);
@@ -39,7 +39,7 @@ mod benches {
         let mut board = get_test_board();
         let creature: &mut SoftBody = &mut board.creatures[0].borrow_mut();
         let brain = &mut creature.brain;
-        let env = Environment::new(&board.terrain, &creature.base);
+        let env = Environment::new(&board.terrain, &creature.base, TEST_TIME, &board.climate);
 
         b.iter(|| brain.load_input(&env));
     }
@@ -49,13 +49,53 @@ mod benches {
         let mut board = get_test_board();
         let creature: &mut SoftBody = &mut board.creatures[0].borrow_mut();
         let brain = &mut creature.brain;
-        let env = Environment::new(&board.terrain, &creature.base);
+        let env = Environment::new(&board.terrain, &creature.base, TEST_TIME, &board.climate);
 
         brain.load_input(&env);
 
         b.iter(|| brain.run());
     }
 
+    #[bench]
+    fn bench_brain_feed_forward_population(b: &mut Bencher) {
+        let board = get_test_board();
+
+        b.iter(|| {
+            for creature in &board.creatures {
+                creature.borrow_mut().brain.run();
+            }
+        });
+    }
+
+    /// The batched version of `bench_brain_feed_forward_population`, including copying the weights every time.
+    #[bench]
+    fn bench_brain_batch_population(b: &mut Bencher) {
+        let board = get_test_board();
+        let mut creatures: Vec<_> = board.creatures.iter().map(|c| c.borrow_mut()).collect();
+
+        b.iter(|| {
+            let brains: Vec<&Brain> = creatures.iter().map(|c| &c.brain).collect();
+            let mut batch = BrainBatch::new(&brains).unwrap();
+            batch.load_inputs(&brains);
+            batch.run();
+
+            let mut brains: Vec<&mut Brain> = creatures.iter_mut().map(|c| &mut c.brain).collect();
+            batch.store_values(&mut brains);
+        });
+    }
+
+    /// Only the calculations of `bench_brain_batch_population`, as if the weights never had to be copied.
+    #[bench]
+    fn bench_brain_batch_run(b: &mut Bencher) {
+        let board = get_test_board();
+        let creatures: Vec<_> = board.creatures.iter().map(|c| c.borrow()).collect();
+        let brains: Vec<&Brain> = creatures.iter().map(|c| &c.brain).collect();
+        let mut batch = BrainBatch::new(&brains).unwrap();
+        batch.load_inputs(&brains);
+
+        b.iter(|| batch.run());
+    }
+
     #[bench]
     fn bench_brain_evolve_1_parent(b: &mut Bencher) {
         let parents = vec![HLSoftBody::from(SoftBody::new_random(
@@ -63,7 +103,7 @@ mod benches {
             TEST_TIME,
         ))];
 
-        b.iter(|| Brain::recombination_infinite_parents(&parents));
+        b.iter(|| Brain::recombination_infinite_parents(&parents, &Default::default()));
     }
 
     #[bench]
@@ -73,6 +113,6 @@ mod benches {
             HLSoftBody::from(SoftBody::new_random(TEST_BOARD_SIZE, TEST_TIME)),
         ];
 
-        b.iter(|| Brain::recombination_infinite_parents(&parents));
+        b.iter(|| Brain::recombination_infinite_parents(&parents, &Default::default()));
     }
 }
@@ -0,0 +1,30 @@
+//! The board the benchmarks run on.
+//!
+//! It is generated instead of loaded from a file, a saved board stops loading whenever the save format changes.
+
+extern crate lib_evolvim;
+
+use self::lib_evolvim::constants::*;
+use self::lib_evolvim::{Board, Brain};
+
+/// About as many creatures as a default world has after evolving for a long time.
+const TEST_POPULATION: usize = 300;
+/// The years simulated before benchmarking, so food has been eaten and creatures are spread out.
+const TEST_YEARS: usize = 5;
+
+/// Returns a default world with `TEST_POPULATION` random creatures that has run for `TEST_YEARS` years.
+pub fn get_test_board() -> Board<Brain> {
+    let mut board = Board::new_random(
+        DEFAULT_BOARD_SIZE,
+        DEFAULT_NOISE_STEP_SIZE,
+        TEST_POPULATION,
+        DEFAULT_MIN_TEMP,
+        DEFAULT_MAX_TEMP,
+    );
+
+    for _i in 0..TEST_YEARS * 1000 {
+        board.update(0.001);
+    }
+
+    board
+}
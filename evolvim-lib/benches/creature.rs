@@ -19,7 +19,7 @@ mod benches {
             TEST_TIME,
         ))];
 
-        b.iter(|| Creature::new_baby(parents.clone(), TEST_ENERGY, TEST_TIME));
+        b.iter(|| Creature::new_baby(parents.clone(), TEST_ENERGY, TEST_TIME, &Default::default()));
     }
 
     #[bench]
@@ -29,6 +29,6 @@ mod benches {
             HLSoftBody::<Brain>::from(SoftBody::new_random(TEST_BOARD_SIZE, TEST_TIME)),
         ];
 
-        b.iter(|| Creature::new_baby(parents.clone(), TEST_ENERGY, TEST_TIME));
+        b.iter(|| Creature::new_baby(parents.clone(), TEST_ENERGY, TEST_TIME, &Default::default()));
     }
 }
@@ -0,0 +1,47 @@
+#![cfg_attr(feature = "bench", feature(test))]
+
+#[cfg(all(feature = "bench", test))]
+mod benches {
+    extern crate lib_evolvim;
+    extern crate test;
+
+    use self::lib_evolvim::neat::{Genome, InnovationRegistry, NeuralNet};
+    use self::test::Bencher;
+
+    /// Roughly the size of a genome after a few hundred years of evolution.
+    const MUTATIONS: usize = 100;
+
+    fn get_evolved_genome() -> Genome {
+        let innovations = InnovationRegistry::default();
+        let mut genome = Genome::new_fully_linked();
+
+        for _i in 0..MUTATIONS {
+            genome.mutate(&innovations);
+        }
+
+        genome
+    }
+
+    #[bench]
+    fn bench_neat_generate_phenotype(b: &mut Bencher) {
+        let genome = get_evolved_genome();
+
+        b.iter(|| NeuralNet::from(&genome));
+    }
+
+    #[bench]
+    fn bench_neat_run_phenotype_initial(b: &mut Bencher) {
+        let genome = Genome::new_fully_linked();
+        let mut net = NeuralNet::from(&genome);
+
+        b.iter(|| net.run_calculations());
+    }
+
+    #[bench]
+    fn bench_neat_run_phenotype_evolved(b: &mut Bencher) {
+        let genome = get_evolved_genome();
+        let mut net = NeuralNet::from(&genome);
+
+        b.iter(|| net.run_calculations());
+    }
+}
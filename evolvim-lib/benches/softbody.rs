@@ -1,19 +1,18 @@
 #![cfg_attr(feature = "bench", feature(test))]
 
+#[cfg(all(feature = "bench", test))]
+mod common;
+
 #[cfg(all(feature = "bench", test))]
 mod benches {
     extern crate lib_evolvim;
     extern crate test;
 
-    use self::lib_evolvim::{Board, Brain};
     use self::test::Bencher;
+    use super::common::get_test_board;
 
     const TIME_STEP: f64 = 0.001;
 
-    fn get_test_board() -> Board {
-        Board::<Brain>::load_from("assets/test.bin").unwrap()
-    }
-
     #[bench]
     fn bench_softbody_collide(b: &mut Bencher) {
         let board = get_test_board();
@@ -33,7 +32,8 @@ mod benches {
             let time = board.get_time();
 
             for c_rc in &board.creatures {
-                c_rc.borrow_mut().metabolize(TIME_STEP, time);
+                c_rc.borrow_mut()
+                    .metabolize(TIME_STEP, time, board.brain_cost);
             }
         });
     }
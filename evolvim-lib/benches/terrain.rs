@@ -6,9 +6,17 @@ mod benches {
     extern crate test;
 
     use self::lib_evolvim::constants::*;
-    use self::lib_evolvim::{Climate, Terrain};
+    use self::lib_evolvim::{BoardSize, Climate, SeaLevel, Terrain};
     use self::test::Bencher;
 
+    const BIG_BOARD_SIZE: BoardSize = (1000, 1000);
+    /// Roughly the amount of tiles creatures eat from or look at every frame.
+    const TILES_USED_PER_FRAME: usize = 1000;
+
+    fn get_big_terrain() -> Terrain {
+        Terrain::generate_perlin(BIG_BOARD_SIZE, DEFAULT_NOISE_STEP_SIZE)
+    }
+
     #[bench]
     fn bench_terrain_perlin_generation(b: &mut Bencher) {
         b.iter(|| Terrain::generate_perlin(DEFAULT_BOARD_SIZE, DEFAULT_NOISE_STEP_SIZE));
@@ -25,4 +33,65 @@ mod benches {
             terrain.update_all(time, &climate);
         });
     }
+
+    #[bench]
+    fn bench_terrain_update_all_big(b: &mut Bencher) {
+        let mut time = 0.0;
+        let mut terrain = get_big_terrain();
+        let climate = Climate::new(DEFAULT_MIN_TEMP, DEFAULT_MAX_TEMP);
+
+        b.iter(|| {
+            time += 0.001;
+            terrain.update_all(time, &climate);
+        });
+    }
+
+    /// Updating only the part of the board that fits on the screen, like the GUI does.
+    #[bench]
+    fn bench_terrain_update_visible_big(b: &mut Bencher) {
+        let mut time = 0.0;
+        let mut terrain = get_big_terrain();
+        let climate = Climate::new(DEFAULT_MIN_TEMP, DEFAULT_MAX_TEMP);
+
+        b.iter(|| {
+            time += 0.001;
+            terrain.update_all_at(time, &climate, 450..550, 450..550);
+        });
+    }
+
+    /// The work the terrain has to do every frame now that tiles are updated lazily.
+    #[bench]
+    fn bench_terrain_lazy_frame_big(b: &mut Bencher) {
+        let mut time = 0.0;
+        let mut terrain = get_big_terrain();
+        let climate = Climate::new(DEFAULT_MIN_TEMP, DEFAULT_MAX_TEMP);
+
+        b.iter(|| {
+            time += 0.001;
+            terrain.follow_sea_level(time, &climate);
+
+            for i in 0..TILES_USED_PER_FRAME {
+                let pos = (i * 997 % BIG_BOARD_SIZE.0, i * 7919 % BIG_BOARD_SIZE.1);
+                terrain.remove_food_fraction_at(pos, 0.01, time, &climate);
+            }
+        });
+    }
+
+    /// Following the sea as it rises and sinks with the seasons.
+    #[bench]
+    fn bench_terrain_follow_sea_level_big(b: &mut Bencher) {
+        let mut time = 0.0;
+        let mut terrain = get_big_terrain();
+        let mut climate = Climate::new(DEFAULT_MIN_TEMP, DEFAULT_MAX_TEMP);
+        climate.set_sea_level_settings(SeaLevel {
+            temperature_factor: 0.1,
+            ..SeaLevel::default()
+        });
+
+        b.iter(|| {
+            time += 0.01;
+            climate.update(time);
+            terrain.follow_sea_level(time, &climate);
+        });
+    }
 }
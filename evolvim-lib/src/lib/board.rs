@@ -13,6 +13,8 @@ extern crate rayon;
 use crate::brain::{Brain, GenerateRandom, NeuralNet, RecombinationInfinite};
 use crate::climate::Climate;
 use crate::constants::*;
+use crate::disaster::DisasterScheduler;
+use crate::event_log::EventLog;
 use crate::sbip::SoftBodiesInPositions;
 use crate::softbody::{HLSoftBody, SoftBody};
 use crate::terrain::Terrain;
@@ -90,10 +92,21 @@ pub struct Board<B: NeuralNet = Brain> {
 
     // Fields relevant for time or history
     year: f64,
+    pub event_log: EventLog,
 
     // Fields relevant for temperature
     pub climate: Climate,
 
+    // Fields relevant for disasters
+    pub disasters: DisasterScheduler,
+
+    // Fields relevant for brains
+    pub brain_context: B::Context,
+    /// The energy a creature loses every year for every unit of `NeuralNet::metabolic_cost`.
+    pub brain_cost: f64,
+    /// Whether brains change their weights during their life, see `NeuralNet::learn`.
+    pub lifetime_learning: bool,
+
     // Miscelanious
     pub selected_creature: SelectedCreature<B>,
 }
@@ -118,7 +131,7 @@ impl<B: NeuralNet + GenerateRandom> Default for Board<B> {
 
 impl<B: NeuralNet> Board<B> {
     pub fn new(board_width: usize, board_height: usize, terrain: Terrain, creature_minimum: usize, soft_bodies_in_positions: SoftBodiesInPositions<B>,
-    creatures: Vec<HLSoftBody<B>>, creature_id_up_to: usize, year: f64, climate: Climate, selected_creature: SelectedCreature<B>) -> Board<B>{
+    creatures: Vec<HLSoftBody<B>>, creature_id_up_to: usize, year: f64, event_log: EventLog, climate: Climate, disasters: DisasterScheduler, brain_context: B::Context, brain_cost: f64, lifetime_learning: bool, selected_creature: SelectedCreature<B>) -> Board<B>{
         Board {
             board_width,
             board_height,
@@ -130,9 +143,16 @@ impl<B: NeuralNet> Board<B> {
             creature_id_up_to,
 
             year,
+            event_log,
 
             climate,
 
+            disasters,
+
+            brain_context,
+            brain_cost,
+            lifetime_learning,
+
             selected_creature,
         }
     }
@@ -146,6 +166,26 @@ impl<B: NeuralNet + GenerateRandom> Board<B> {
         creature_minimum: usize,
         min_temp: f64,
         max_temp: f64,
+    ) -> Self {
+        Board::new_random_with_context(
+            board_size,
+            noise_step_size,
+            creature_minimum,
+            min_temp,
+            max_temp,
+            B::Context::default(),
+        )
+    }
+
+    /// Randomly generates a new `Board` whose brains use the given context from the start,
+    /// e.g. to give the first creatures a different `BrainShape`.
+    pub fn new_random_with_context(
+        board_size: BoardSize,
+        noise_step_size: f64,
+        creature_minimum: usize,
+        min_temp: f64,
+        max_temp: f64,
+        brain_context: B::Context,
     ) -> Self {
         let creatures = Vec::with_capacity(creature_minimum);
 
@@ -164,9 +204,16 @@ impl<B: NeuralNet + GenerateRandom> Board<B> {
             creature_id_up_to: 0,
 
             year: 0.0,
+            event_log: EventLog::default(),
 
             climate,
 
+            disasters: DisasterScheduler::default(),
+
+            brain_context,
+            brain_cost: DEFAULT_BRAIN_COST,
+            lifetime_learning: false,
+
             selected_creature: SelectedCreature::default(),
         };
 
@@ -183,7 +230,11 @@ impl<B: NeuralNet + GenerateRandom> Board<B> {
     fn maintain_creature_minimum(&mut self) {
         while self.creatures.len() < self.creature_minimum {
             let board_size = self.get_board_size();
-            let creature = HLSoftBody::from(SoftBody::new_random(board_size, self.year));
+            let creature = HLSoftBody::from(SoftBody::new_random_with_context(
+                board_size,
+                self.year,
+                &self.brain_context,
+            ));
 
             // Initialize in `SoftBodiesInPositions` as well.
             creature.set_sbip(&mut self.soft_bodies_in_positions, board_size);
@@ -199,17 +250,9 @@ impl<B: NeuralNet + GenerateRandom> Board<B> {
 impl<B: NeuralNet + RecombinationInfinite + GenerateRandom> Board<B> {
     pub fn update(&mut self, time_step: f64) {
         self.year += time_step;
+        self.update_disasters(time_step);
         self.climate.update(self.year);
-
-        let temp_change_into_frame =
-            self.climate.get_temperature() - self.climate.get_growth_rate(self.year - time_step);
-        let temp_change_out_of_frame =
-            self.climate.get_growth_rate(self.year + time_step) - self.climate.get_temperature();
-
-        if temp_change_into_frame * temp_change_out_of_frame < 0.0 {
-            // Temperature change flipped direction
-            self.terrain.update_all(self.year, &self.climate);
-        }
+        self.terrain.follow_sea_level(self.year, &self.climate);
 
         self.update_creatures(time_step);
 
@@ -222,6 +265,9 @@ impl<B: NeuralNet + RecombinationInfinite + GenerateRandom> Board<B> {
         // Experimental: this was moved from above to always keep the creature minimum.
         self.maintain_creature_minimum();
 
+        // Let the brains keep up with births and deaths, e.g. for NEAT species.
+        B::update_context(&mut self.brain_context, &self.creatures, self.year);
+
         // Move the creatures around on the board
         self.move_creatures(time_step);
     }
@@ -236,9 +282,10 @@ impl<B: NeuralNet + RecombinationInfinite> Board<B> {
             let time = self.get_time();
             let board_size = self.get_board_size();
             let sbip = &mut self.soft_bodies_in_positions;
+            let brain_context = &self.brain_context;
 
             for c in &mut self.creatures {
-                let maybe_baby = c.try_reproduce(time, sbip, board_size);
+                let maybe_baby = c.try_reproduce(time, sbip, board_size, brain_context);
                 if let Some(baby) = maybe_baby {
                     babies.push(baby);
                 }
@@ -277,25 +324,39 @@ impl<B: NeuralNet> Board<B> {
     }
 
     #[cfg(not(multithreading))]
-    fn update_brains(&mut self) {
+    fn update_brains(&mut self, time_step: f64) {
+        let learn = self.lifetime_learning;
         self.creatures
             .iter()
             .map(|c| c.borrow_mut())
             .for_each(|mut c| {
                 let creature: &mut SoftBody<B> = &mut c;
-                let env = crate::brain::Environment::new(&self.terrain, &creature.base);
+                let env = crate::brain::Environment::new(
+                    &self.terrain,
+                    &creature.base,
+                    self.year,
+                    &self.climate,
+                );
                 creature.brain.run_with(&env);
+                if learn {
+                    creature.brain.learn(time_step);
+                }
             });
     }
 
     #[cfg(multithreading)]
-    fn update_brains(&mut self) {
+    fn update_brains(&mut self, time_step: f64) {
+        let learn = self.lifetime_learning;
         self.creatures
             .map(|c| c.borrow_mut())
             .par_iter()
             .for_each(|c| {
-                let env = crate::brain::Environment::new(&self.terrain, &c.base);
+                let env =
+                    crate::brain::Environment::new(&self.terrain, &c.base, self.year, &self.climate);
                 c.brain.run_with(&env);
+                if learn {
+                    c.brain.learn(time_step);
+                }
             });
     }
 
@@ -304,6 +365,7 @@ impl<B: NeuralNet> Board<B> {
 
         let time = self.year;
         let board_size = self.get_board_size();
+        let brain_cost = self.brain_cost;
 
         for c_rc in &self.creatures {
             // These functions call `borrow_mut()`
@@ -313,10 +375,10 @@ impl<B: NeuralNet> Board<B> {
 
             c.record_energy();
 
-            c.metabolize(time_step, time);
+            c.metabolize(time_step, time, brain_cost);
         }
 
-        self.update_brains();
+        self.update_brains(time_step);
 
         let use_output = true;
         if use_output {
@@ -435,6 +497,36 @@ impl<B: NeuralNet> Board<B> {
         return self.creatures.len();
     }
 
+    /// Returns every species with its amount of living members, biggest first.
+    ///
+    /// This is empty when the brains don't have species.
+    pub fn get_species_counts(&self) -> Vec<(usize, usize)> {
+        let mut counts = std::collections::HashMap::new();
+        for c in &self.creatures {
+            if let Some(species) = c.borrow().brain.get_species() {
+                *counts.entry(species).or_insert(0) += 1;
+            }
+        }
+
+        let mut counts: Vec<(usize, usize)> = counts.into_iter().collect();
+        counts.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+        counts
+    }
+
+    /// Returns every kind of brain with its amount of living creatures, most common first.
+    pub fn get_architecture_counts(&self) -> Vec<(&'static str, usize)> {
+        let mut counts = std::collections::HashMap::new();
+        for c in &self.creatures {
+            *counts.entry(c.borrow().brain.get_architecture()).or_insert(0) += 1;
+        }
+
+        let mut counts: Vec<(&'static str, usize)> = counts.into_iter().collect();
+        counts.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+        counts
+    }
+
     /// Returns a `String` representing the current season.
     ///
     /// Can be either "Winter", "Spring", "Summer" or "Autumn".
@@ -0,0 +1,100 @@
+//! Contains the activation functions a neuron can use.
+
+#![warn(missing_docs)]
+
+extern crate rand;
+
+use self::rand::Rng;
+use std::fmt;
+
+/// The function a neuron applies to the weighted sum of its inputs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Activation {
+    /// `1 / (1 + e^-x)`, between 0 and 1.
+    Sigmoid,
+    /// The hyperbolic tangent, between -1 and 1.
+    Tanh,
+    /// `x` for positive values and 0 otherwise.
+    ReLU,
+    /// `e^(-x^2)`, 1 around 0 and dropping off to both sides.
+    Gaussian,
+    /// `sin(x)`, useful for repeating patterns.
+    Sine,
+    /// 1 for positive values and 0 otherwise.
+    Step,
+    /// Leaves the value unchanged.
+    Identity,
+}
+
+impl Default for Activation {
+    fn default() -> Self {
+        Activation::Sigmoid
+    }
+}
+
+impl Activation {
+    /// Every activation function, used to pick a random one.
+    pub const ALL: [Activation; 7] = [
+        Activation::Sigmoid,
+        Activation::Tanh,
+        Activation::ReLU,
+        Activation::Gaussian,
+        Activation::Sine,
+        Activation::Step,
+        Activation::Identity,
+    ];
+
+    /// Returns a random activation function.
+    pub fn new_random() -> Self {
+        let i = rand::thread_rng().gen_range(0, Activation::ALL.len());
+
+        Activation::ALL[i]
+    }
+
+    /// Returns a random activation function different from this one.
+    pub fn new_random_other(self) -> Self {
+        let others: Vec<Activation> = Activation::ALL
+            .iter()
+            .cloned()
+            .filter(|a| *a != self)
+            .collect();
+        let i = rand::thread_rng().gen_range(0, others.len());
+
+        others[i]
+    }
+
+    /// Applies this function to `x`.
+    pub fn apply(self, x: f64) -> f64 {
+        match self {
+            Activation::Sigmoid => 1.0 / (1.0 + (-x).exp()),
+            Activation::Tanh => x.tanh(),
+            Activation::ReLU => x.max(0.0),
+            Activation::Gaussian => (-x * x).exp(),
+            Activation::Sine => x.sin(),
+            Activation::Step => {
+                if x > 0.0 {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            Activation::Identity => x,
+        }
+    }
+}
+
+impl fmt::Display for Activation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Activation::Sigmoid => "sigmoid",
+            Activation::Tanh => "tanh",
+            Activation::ReLU => "ReLU",
+            Activation::Gaussian => "gaussian",
+            Activation::Sine => "sine",
+            Activation::Step => "step",
+            Activation::Identity => "identity",
+        };
+
+        write!(f, "{}", name)
+    }
+}
@@ -0,0 +1,202 @@
+//! Contains the ways a `Brain` can combine the weights of its parents and mutate them.
+//!
+//! Which ones are used is decided by the `RecombinationConfig` of the world, so their effect on evolvability can be
+//! compared. The defaults are the operators of the original Processing code.
+//! A `RecurrentBrain` mutates its weights with the same operators, see `RecurrentConfig`.
+
+#![warn(missing_docs)]
+
+extern crate nalgebra;
+extern crate rand;
+
+use self::nalgebra::DMatrix;
+use self::rand::distributions::StandardNormal;
+use self::rand::Rng;
+use super::BrainShape;
+use std::f64::consts::PI;
+
+/// How the weights of the parents are combined into the weights of a child.
+///
+/// Every weight matrix has one column per neuron, its first element is the weight of the bias node.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Crossover {
+    /// The operator of the Processing code: the weights are laid out on a circle and every parent gets an arc of it,
+    /// which is rotated randomly for every child.
+    Axons,
+    /// Every weight comes from a random parent.
+    Uniform,
+    /// Every neuron gets all its incoming weights from a single random parent, so it keeps working the same way.
+    Neuron,
+    /// The weights are put in one long row and cut at this many random points,
+    /// the parents take turns giving the parts in between. One point gives single-point crossover.
+    Points(usize),
+    /// Every weight is a weighted average of the parents, with random weights that are the same for every weight.
+    Blend,
+}
+
+impl Default for Crossover {
+    fn default() -> Self {
+        Crossover::Axons
+    }
+}
+
+/// How the weights of a child are changed after crossover.
+///
+/// Both change every weight, how much depends on the mutability of the child.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum WeightMutation {
+    /// The mutation of the Processing code: a random number between -1 and 1 to the ninth power,
+    /// so most changes are tiny and a few are big.
+    Processing,
+    /// A normally distributed change with the same variance as `Processing`.
+    Gaussian,
+}
+
+impl Default for WeightMutation {
+    fn default() -> Self {
+        WeightMutation::Processing
+    }
+}
+
+/// Decides how every `Brain` in a world is recombined, it is the context of `Brain`.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RecombinationConfig {
+    /// How the weights of the parents are combined.
+    pub crossover: Crossover,
+    /// How the weights are mutated afterwards.
+    pub mutation: WeightMutation,
+    /// The shape of every `Brain` created at random in this world, children keep the shape of their parents.
+    pub shape: BrainShape,
+}
+
+impl Crossover {
+    /// Returns the weights of a child of parents with the given weights, they all have the same shape.
+    ///
+    /// This doesn't mutate anything, `Brain::recombine_with` does that afterwards.
+    ///
+    /// # Panics
+    /// If `parents` is empty.
+    pub fn cross(self, parents: &[&[DMatrix<f64>]]) -> Vec<DMatrix<f64>> {
+        use Crossover::*;
+
+        assert!(!parents.is_empty(), "Crossover needs at least one parent.");
+        let mut rng = rand::thread_rng();
+        let amount_parents = parents.len();
+        let mut weights: Vec<DMatrix<f64>> = parents[0].to_vec();
+
+        match self {
+            Axons => {
+                let random_rotation: f64 = rng.gen();
+
+                // The brain is laid out as a grid of axons, this decides which parent gives which axon.
+                // It is as wide as the amount of layers and one higher than the biggest layer.
+                let brain_width = (weights.len() + 1) as f64;
+                let brain_height = weights
+                    .iter()
+                    .map(|t| t.nrows().max(t.ncols() + 1))
+                    .max()
+                    .unwrap() as f64;
+
+                for (x, theta) in weights.iter_mut().enumerate() {
+                    let axon_angles = get_axon_angles(
+                        theta.nrows() + theta.ncols(),
+                        x,
+                        brain_width,
+                        brain_height,
+                    );
+
+                    for y in 0..theta.nrows() {
+                        for z in 0..theta.ncols() {
+                            let axon_angle = axon_angles[y + z];
+                            let parent_id = (((axon_angle + random_rotation) % 1.0)
+                                * amount_parents as f64)
+                                .floor() as usize;
+
+                            theta[(y, z)] = parents[parent_id][x][(y, z)];
+                        }
+                    }
+                }
+            }
+            Uniform => {
+                for (x, theta) in weights.iter_mut().enumerate() {
+                    for (i, w) in theta.iter_mut().enumerate() {
+                        *w = parents[rng.gen_range(0, amount_parents)][x].as_slice()[i];
+                    }
+                }
+            }
+            Neuron => {
+                for (x, theta) in weights.iter_mut().enumerate() {
+                    for z in 0..theta.ncols() {
+                        let parent = parents[rng.gen_range(0, amount_parents)];
+                        theta.set_column(z, &parent[x].column(z));
+                    }
+                }
+            }
+            Points(points) => {
+                let total: usize = weights.iter().map(|t| t.len()).sum();
+                let mut cuts: Vec<usize> = (0..points).map(|_| rng.gen_range(0, total)).collect();
+                cuts.sort();
+
+                // Counts the weights in the order of `as_slice`, layer by layer.
+                let first = rng.gen_range(0, amount_parents);
+                let mut place = 0;
+                for (x, theta) in weights.iter_mut().enumerate() {
+                    for (i, w) in theta.iter_mut().enumerate() {
+                        let passed = cuts.iter().take_while(|c| **c <= place).count();
+                        *w = parents[(first + passed) % amount_parents][x].as_slice()[i];
+                        place += 1;
+                    }
+                }
+            }
+            Blend => {
+                let shares: Vec<f64> = (0..amount_parents).map(|_| rng.gen()).collect();
+                let total: f64 = shares.iter().sum();
+
+                for (x, theta) in weights.iter_mut().enumerate() {
+                    *theta = parents
+                        .iter()
+                        .zip(&shares)
+                        .map(|(p, share)| &p[x] * (share / total))
+                        .fold(DMatrix::zeros(theta.nrows(), theta.ncols()), |a, b| a + b);
+                }
+            }
+        }
+
+        weights
+    }
+}
+
+// const MUTATE_MULTI: f64 = 0.5.powi(9);
+const MUTATE_MULTI: f64 = 0.001953125;
+
+impl WeightMutation {
+    /// Changes every weight, `mutability` is that of the child.
+    pub(crate) fn mutate(self, weights: &mut [DMatrix<f64>], mutability: f64) {
+        let mut rng = rand::thread_rng();
+        let scale = mutability / MUTATE_MULTI;
+
+        for w in weights.iter_mut().flat_map(|theta| theta.iter_mut()) {
+            let r = match self {
+                WeightMutation::Processing => (rng.gen::<f64>() * 2.0 - 1.0).powi(9),
+                // The ninth power of a uniform number between -1 and 1 has a variance of 1/19.
+                WeightMutation::Gaussian => rng.sample::<f64, _>(StandardNormal) / (19.0f64).sqrt(),
+            };
+
+            *w += r * scale;
+        }
+    }
+}
+
+/// For the default shape `brain_width` is 3 and `brain_height` is 11, just like in the original Processing code.
+fn get_axon_angles(max: usize, x: usize, brain_width: f64, brain_height: f64) -> Vec<f64> {
+    let mut vec = Vec::with_capacity(max);
+
+    for i in 0..max {
+        vec.push(
+            PI + ((i as f64 - brain_height) / 2.0).atan2(x as f64 - brain_width / 2.0) / (2.0 * PI),
+        );
+    }
+
+    vec
+}
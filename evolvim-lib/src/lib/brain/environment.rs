@@ -47,10 +47,17 @@ impl<'a, B> EnvironmentMut<'a, B> {
 pub struct Environment<'a> {
     pub terrain: &'a Terrain,
     pub this_body: &'a Rock,
+    pub time: f64,
+    pub climate: &'a Climate,
 }
 
 impl<'a> Environment<'a> {
-    pub fn new(terrain: &'a Terrain, this_body: &'a Rock) -> Self {
-        Environment { terrain, this_body }
+    pub fn new(terrain: &'a Terrain, this_body: &'a Rock, time: f64, climate: &'a Climate) -> Self {
+        Environment {
+            terrain,
+            this_body,
+            time,
+            climate,
+        }
     }
 }
@@ -0,0 +1,38 @@
+//! Turns brains into Graphviz DOT graphs and JSON so they can be studied in other tools.
+//!
+//! In DOT graphs sensors are boxes, outputs are double circles and the width of an edge shows the size of its weight.
+//! Positive weights are green, negative weights are red and disabled links are dashed.
+
+extern crate serde_json;
+
+/// A brain that can be exported for outside inspection.
+pub trait ExportBrain {
+    /// Returns a Graphviz DOT graph of this brain, render it with `dot -Tsvg brain.dot > brain.svg`.
+    fn to_dot(&self) -> String;
+
+    /// Returns the structure and weights of this brain as JSON.
+    fn to_json(&self) -> serde_json::Value;
+}
+
+/// Returns the DOT attributes of an edge with the given weight.
+pub fn get_edge_attributes(weight: f64, enabled: bool) -> String {
+    let colour = if weight < 0.0 {
+        "firebrick"
+    } else {
+        "forestgreen"
+    };
+    let width = 0.5 + 2.0 * weight.abs().min(2.0);
+    let style = if enabled { "solid" } else { "dashed" };
+
+    format!(
+        "color={}, penwidth={:.2}, style={}, tooltip=\"{:.4}\"",
+        colour, width, style, weight
+    )
+}
+
+/// Writes the start of a DOT graph: left to right, with round nodes unless stated otherwise.
+pub fn write_dot_header(dot: &mut String, name: &str) {
+    dot.push_str(&format!("digraph {} {{\n", name));
+    dot.push_str("    rankdir=LR;\n");
+    dot.push_str("    node [shape=circle, fontsize=10];\n");
+}
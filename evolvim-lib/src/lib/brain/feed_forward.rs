@@ -6,12 +6,13 @@
 
 extern crate nalgebra;
 extern crate rand;
+extern crate serde_json;
 
-use self::allocator::Allocator;
-use self::dimension::DimName;
-use self::nalgebra::*;
+use self::nalgebra::{DMatrix, RowDVector};
 use self::rand::Rng;
-use std::f64::consts::PI;
+use self::serde_json::json;
+use super::{Activation, Plasticity, RecombinationConfig};
+use std::fmt;
 
 pub type BrainOutput<'a> = &'a [FPN];
 pub type BrainInput = [FPN; 9];
@@ -19,21 +20,95 @@ pub type BrainInput = [FPN; 9];
 type FPN = f64;
 
 /// The amount of neurons in the input layer.
-type _InputLayerSize = U10;
-/// The amount of neurons in the input layer plus the bias node.
-type InputLayerSizePlusBias = U11;
-/// The amount of neurons in the hidden layer.
-type HiddenLayerSize = U10;
-/// The amount of neurons in the hidden layer plus the bias node.
-type HiddenLayerSizePlusBias = U11;
+pub const INPUT_LAYER_SIZE: usize = 10;
 /// The amount of neurons in the output layer.
-type OutputLayerSize = U10;
+pub const OUTPUT_LAYER_SIZE: usize = 10;
+/// The sizes of the hidden layers of a `Brain` created by `GenerateRandom::new_random`, worlds can choose their own.
+pub const DEFAULT_HIDDEN_LAYERS: [usize; 1] = [10];
+/// The mutability of a `Brain` created by `GenerateRandom::new_random`, children inherit it from their parents.
+pub const DEFAULT_MUTABILITY: f64 = 0.0005;
+/// The lowest mutability a `Brain` can evolve.
+pub const MIN_MUTABILITY: f64 = DEFAULT_MUTABILITY / 100.0;
+/// The highest mutability a `Brain` can evolve.
+pub const MAX_MUTABILITY: f64 = DEFAULT_MUTABILITY * 10.0;
 
 // const AXON_ANGLES_0: Vec<f64> = get_axon_angles(110, 0);
 // const AXON_ANGLES_1: Vec<f64> = get_axon_angles(110, 1);
 
+/// The sizes of the hidden layers of a `Brain`.
+///
+/// The input and output layers always have `INPUT_LAYER_SIZE` and `OUTPUT_LAYER_SIZE` neurons,
+/// the creature's senses and muscles decide those.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BrainShape {
+    /// The amount of neurons in every hidden layer, from the input to the output side.
+    pub hidden_layers: Vec<usize>,
+}
+
+impl Default for BrainShape {
+    fn default() -> Self {
+        BrainShape {
+            hidden_layers: DEFAULT_HIDDEN_LAYERS.to_vec(),
+        }
+    }
+}
+
+impl BrainShape {
+    /// Creates a shape with the given hidden layer sizes, an empty `Vec` gives a brain without hidden layers.
+    pub fn new(hidden_layers: Vec<usize>) -> Self {
+        BrainShape { hidden_layers }
+    }
+
+    /// Returns the amount of neurons in every layer, including the input and output layers.
+    pub fn get_layer_sizes(&self) -> Vec<usize> {
+        let mut sizes = Vec::with_capacity(self.hidden_layers.len() + 2);
+        sizes.push(INPUT_LAYER_SIZE);
+        sizes.extend_from_slice(&self.hidden_layers);
+        sizes.push(OUTPUT_LAYER_SIZE);
+
+        sizes
+    }
+}
+
+impl fmt::Display for BrainShape {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let sizes: Vec<String> = self
+            .get_layer_sizes()
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+
+        write!(f, "{}", sizes.join("-"))
+    }
+}
+
+/// Returned when brains of different shapes are recombined.
+#[derive(Debug)]
+pub struct ShapeMismatch {
+    /// The shape of the first parent.
+    pub expected: BrainShape,
+    /// The first shape that didn't match it.
+    pub found: BrainShape,
+}
+
+impl fmt::Display for ShapeMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Can not recombine a brain of shape {} with one of shape {}.",
+            self.expected, self.found
+        )
+    }
+}
+
+impl std::error::Error for ShapeMismatch {}
+
 /// This struct contains all parameters and values necessary for a feed-forward neural network.
 ///
+/// The amount and size of the hidden layers can differ between brains, see `BrainShape`.
+/// Every layer but the input layer has its own `Activation`.
+/// When the board has lifetime learning turned on the weights change during the life of the creature, see `Plasticity`.
+///
 /// # Usage
 /// Give it some input with `load_input()` and perform the calculations with `feed_forward()`, then extract the output with `get_output`.
 /// Or just do all three at once with `run`!
@@ -42,56 +117,101 @@ type OutputLayerSize = U10;
 /// *Brain.pde/Brain*, although this doesn't have an `Axon` class/structure to rely on.
 #[derive(Serialize, Deserialize)]
 pub struct Brain {
-    // This dimension should be equal to InputLayerSize + 1.
-    a_1: RowVectorN<FPN, InputLayerSizePlusBias>,
-    // These dimensions should be equal to InputLayerSize + 1 by HiddenLayerSize.
-    theta_1: MatrixMN<FPN, InputLayerSizePlusBias, HiddenLayerSize>,
-    // This dimension should be equal to HiddenLayerSize + 1.
-    a_2: RowVectorN<FPN, HiddenLayerSizePlusBias>,
-    // These dimensions should be equal to HiddenLayerSize + 1 by OutputLayerSize.
-    theta_2: MatrixMN<FPN, HiddenLayerSizePlusBias, OutputLayerSize>,
-    // This dimension should be equal to OUTPUT_LAYER_SIZE.
-    a_3: RowVectorN<FPN, OutputLayerSize>,
+    shape: BrainShape,
+    // The values of every layer, without bias nodes.
+    layers: Vec<RowDVector<FPN>>,
+    // One matrix for every layer but the input layer.
+    // Its dimensions are the size of the previous layer + 1 (for the bias node) by the size of the next layer.
+    // The bias node is the first row.
+    weights: Vec<DMatrix<FPN>>,
+    // One activation function for every layer but the input layer.
+    activations: Vec<Activation>,
+    // How much the weights of children change, this evolves as well.
+    mutability: FPN,
+    // How the weights change during the life of the creature, this evolves as well.
+    plasticity: Plasticity,
+    // The weights after learning, `None` until this brain learned something.
+    // Children inherit `weights`, not these.
+    learned_weights: Option<Vec<DMatrix<FPN>>>,
 }
 
 impl super::NeuralNet for Brain {
+    type Context = RecombinationConfig;
+
     fn load_input(&mut self, env: &super::Environment) {
+        let memory = self.get_memory();
+        let input = &mut self.layers[0];
+
         // Load the memory
-        self.a_1[0] = self.get_memory();
+        input[0] = memory;
 
         // The current energy of the creature
-        self.a_1[1] = env.this_body.get_energy();
+        input[1] = env.this_body.get_energy();
 
         // The current mouth hue
-        self.a_1[2] = env.this_body.get_mouth_hue();
+        input[2] = env.this_body.get_mouth_hue();
 
         // Look directly underneath the creature
         let pos = env.this_body.get_position();
-        let tile = env.terrain.get_tile_at(pos.into());
+        let tile = env
+            .terrain
+            .get_updated_tile_at(pos.into(), env.time, env.climate);
         let colors = tile.get_hsba_color();
-        self.a_1[3] = colors[0] as FPN;
-        self.a_1[4] = colors[1] as FPN;
-        self.a_1[5] = colors[2] as FPN;
+        input[3] = colors[0] as FPN;
+        input[4] = colors[1] as FPN;
+        input[5] = colors[2] as FPN;
     }
 
     /// Performs feed foward propagation on the neural network.
-    // TODO: see if I can speed this up a little with clever memory management.
     fn run(&mut self) {
-        let mut z_2 = self.a_1 * self.theta_1;
-        // Perform sigmoid function
-        Brain::sigmoid(&mut z_2);
-        // Add bias.
-        self.a_2 = z_2.insert_column(0, 1.0);
+        let weights = self.learned_weights.as_ref().unwrap_or(&self.weights);
+        feed_forward(&mut self.layers, weights, &self.activations);
+    }
+
+    /// Every weight learns from the neurons it links, the bias node always has a value of 1.
+    fn learn(&mut self, time_step: f64) {
+        if !self.plasticity.is_active() {
+            return;
+        }
+        if self.learned_weights.is_none() {
+            self.learned_weights = Some(self.weights.clone());
+        }
+
+        let plasticity = self.plasticity;
+        let weights = self.learned_weights.as_mut().unwrap();
+        for (x, theta) in weights.iter_mut().enumerate() {
+            let input = self.layers[x].as_slice();
+            let output = self.layers[x + 1].as_slice();
+            let rows = theta.nrows();
+
+            // Every column holds the weights to one neuron, with the bias node first.
+            for (column, post) in theta.as_mut_slice().chunks_mut(rows).zip(output) {
+                column[0] = plasticity.update_weight(column[0], 1.0, *post, time_step);
+                for (w, pre) in column[1..].iter_mut().zip(input) {
+                    *w = plasticity.update_weight(*w, *pre, *post, time_step);
+                }
+            }
+        }
+    }
 
-        let z_3 = self.a_2 * self.theta_2;
-        // // Perform sigmoid function, wasn't done in original Processing code.
-        // Brain::sigmoid(&mut z_3);
+    fn get_architecture(&self) -> &'static str {
+        "feed-forward"
+    }
+
+    fn get_mutability(&self) -> Option<f64> {
+        Some(self.mutability)
+    }
+
+    fn get_plasticity(&self) -> Option<Plasticity> {
+        Some(self.plasticity)
+    }
 
-        // Don't need to add bias here.
-        self.a_3 = z_3;
+    /// Every weight, including those of the bias nodes, costs one unit.
+    fn metabolic_cost(&self) -> f64 {
+        self.weights.iter().map(|w| w.len()).sum::<usize>() as f64
     }
 
-    fn use_output(&self, env: &mut super::EnvironmentMut<Self>, time_step: f64) {
+    fn use_output<B>(&self, env: &mut super::EnvironmentMut<B>, time_step: f64) {
         let acceleration = self.wants_acceleration();
         env.this_body.accelerate(acceleration, time_step);
 
@@ -100,10 +220,15 @@ impl super::NeuralNet for Brain {
 
         // TODO: clean this mess.
         let tile_pos = env.this_body.get_random_covered_tile(env.board_size);
-        let tile = env.terrain.get_tile_at_mut(tile_pos);
         let eat_amount = self.wants_to_eat();
-        env.this_body
-            .eat(eat_amount, time_step, env.time, env.climate, tile);
+        env.this_body.eat(
+            eat_amount,
+            time_step,
+            env.time,
+            env.climate,
+            env.terrain,
+            tile_pos,
+        );
 
         let mouth_hue = self.wants_mouth_hue();
         env.this_body.set_mouth_hue(mouth_hue);
@@ -111,118 +236,186 @@ impl super::NeuralNet for Brain {
 }
 
 impl super::GenerateRandom for Brain {
-    /// Returns a brain with completely random weights.
+    /// Returns a brain of the default shape with completely random weights.
     fn new_random() -> Self {
-        let theta_1 = <MatrixMN<FPN, InputLayerSizePlusBias, HiddenLayerSize>>::new_random()
-            - <MatrixMN<FPN, InputLayerSizePlusBias, HiddenLayerSize>>::from_element(0.5);
-        let theta_2 = <MatrixMN<FPN, HiddenLayerSizePlusBias, OutputLayerSize>>::new_random()
-            - <MatrixMN<FPN, HiddenLayerSizePlusBias, OutputLayerSize>>::from_element(0.5);
+        Brain::new_random_with_shape(BrainShape::default())
+    }
 
-        Brain {
-            // Empty input
-            a_1: <RowVectorN<FPN, InputLayerSizePlusBias>>::zeros(),
-            // Initialize random weights between [-0.5, 0.5].
-            theta_1,
-            // Empty hidden layer
-            a_2: <RowVectorN<FPN, HiddenLayerSizePlusBias>>::zeros(),
-            // Initialize random weights between [-0.5, 0.5].
-            theta_2,
-            // Empty output
-            a_3: <RowVectorN<FPN, OutputLayerSize>>::zeros(),
-        }
+    /// Returns a brain of the shape of the world with completely random weights.
+    fn new_random_with_context(context: &RecombinationConfig) -> Self {
+        Brain::new_random_with_shape(context.shape.clone())
     }
 }
 
 impl Brain {
-    /// # Processing equivalent
-    /// *Brain.pde/outputs*, although here only a reference to the output values is returned instead of a copy.
-    pub fn get_output(&self) -> BrainOutput {
-        return self.a_3.as_slice();
-    }
+    /// Returns a brain of the given shape with completely random weights.
+    ///
+    /// The hidden layers use a sigmoid function and the output layer doesn't use one, just like in the original Processing code.
+    pub fn new_random_with_shape(shape: BrainShape) -> Self {
+        let mut activations = vec![Activation::Sigmoid; shape.hidden_layers.len()];
+        activations.push(Activation::Identity);
 
-    /// Returns a reference to the hidden layer values.
-    pub fn get_hidden_layer(&self) -> &[FPN] {
-        self.a_2.as_slice()
+        Brain::new_random_with_activations(shape, activations)
     }
 
-    /// Returns a reference to the input layer values.
-    pub fn get_input_layer(&self) -> &[FPN] {
-        self.a_1.as_slice()
-    }
+    /// Returns a brain of the given shape with completely random weights and the given activation functions.
+    ///
+    /// # Panics
+    /// If there isn't exactly one activation function for every hidden layer and the output layer.
+    pub fn new_random_with_activations(shape: BrainShape, activations: Vec<Activation>) -> Self {
+        let sizes = shape.get_layer_sizes();
+        assert_eq!(
+            activations.len(),
+            sizes.len() - 1,
+            "A brain of shape {} needs {} activation functions.",
+            shape,
+            sizes.len() - 1
+        );
+
+        let weights = sizes
+            .windows(2)
+            .map(|w| {
+                // Initialize random weights between [-0.5, 0.5].
+                DMatrix::new_random(w[0] + 1, w[1]) - DMatrix::from_element(w[0] + 1, w[1], 0.5)
+            })
+            .collect();
 
-    /// Performs the sigmoid function for every element in the matrix.
-    fn sigmoid<R: DimName, C: DimName>(matrix: &mut MatrixMN<FPN, R, C>)
-    where
-        DefaultAllocator: Allocator<FPN, R, C>,
-    {
-        for v in matrix.iter_mut() {
-            *v = 1.0 / (1.0 + (-*v).exp());
+        Brain {
+            // Empty layers
+            layers: Brain::empty_layers(&sizes),
+            weights,
+            activations,
+            mutability: DEFAULT_MUTABILITY,
+            plasticity: Plasticity::default(),
+            learned_weights: None,
+            shape,
         }
     }
-}
 
-impl super::RecombinationInfinite for Brain {
-    /// # Processing equivalent
-    /// *Brain.pde/evolve*, although the structure of the brain is different and there are no calls to `Axon`s here.
-    /// Everything is done in this function.
-    ///
-    /// TODO: improve performance via vectorization.
-    /// TODO: understand formulae and improve them or come up with my own
-    fn recombination_infinite_parents(parents: &Vec<crate::softbody::HLSoftBody<Brain>>) -> Self {
-        let a_1 = <RowVectorN<FPN, InputLayerSizePlusBias>>::zeros();
-        let a_2 = <RowVectorN<FPN, HiddenLayerSizePlusBias>>::zeros();
-        let a_3 = <RowVectorN<FPN, OutputLayerSize>>::zeros();
-
-        let mut theta_1 = <MatrixMN<FPN, InputLayerSizePlusBias, HiddenLayerSize>>::zeros();
-        let mut theta_2 = <MatrixMN<FPN, HiddenLayerSizePlusBias, OutputLayerSize>>::zeros();
+    fn empty_layers(sizes: &[usize]) -> Vec<RowDVector<FPN>> {
+        sizes.iter().map(|s| RowDVector::zeros(*s)).collect()
+    }
 
-        let mut rng = rand::thread_rng();
-        let random_rotation: f64 = rng.gen();
-        let amount_parents = parents.len() as f64;
+    /// Returns the sizes of the hidden layers of this brain.
+    pub fn get_shape(&self) -> &BrainShape {
+        &self.shape
+    }
 
-        const MUTABILITY: f64 = 0.0005;
-        // const MUTATE_MULTI: f64 = 0.5.powi(9);
-        const MUTATE_MULTI: f64 = 0.001953125;
+    /// Returns the activation function of every layer but the input layer.
+    pub fn get_activations(&self) -> &[Activation] {
+        &self.activations
+    }
 
-        let axon_angles = get_axon_angles(110, 0);
-        for y in 0..theta_1.nrows() {
-            for z in 0..theta_1.ncols() {
-                // BRAIN_HEIGHT = 11; x = 0; BRAIN_WIDTH = 3;
-                let axon_angle = axon_angles[y + z];
+    /// Changes the activation function of `layer`, where 0 is the first hidden layer.
+    pub fn set_activation(&mut self, layer: usize, activation: Activation) {
+        self.activations[layer] = activation;
+    }
 
-                let parent_id =
-                    (((axon_angle + random_rotation) % 1.0) * amount_parents).floor() as usize;
+    /// Changes the rule this brain learns with, children inherit it.
+    pub fn set_plasticity(&mut self, plasticity: Plasticity) {
+        self.plasticity = plasticity;
+    }
 
-                let r = (rng.gen::<f64>() * 2.0 - 1.0).powi(9);
+    /// # Processing equivalent
+    /// *Brain.pde/outputs*, although here only a reference to the output values is returned instead of a copy.
+    pub fn get_output(&self) -> BrainOutput {
+        return self.layers.last().unwrap().as_slice();
+    }
 
-                theta_1[(y, z)] = parents[parent_id].borrow().brain.theta_1[(y, z)]
-                    + r * MUTABILITY / MUTATE_MULTI;
-            }
+    /// Returns a reference to the values of the first hidden layer, `None` if this brain has no hidden layers.
+    pub fn get_hidden_layer(&self) -> Option<&[FPN]> {
+        if self.layers.len() > 2 {
+            Some(self.layers[1].as_slice())
+        } else {
+            None
         }
+    }
 
-        let axon_angles = get_axon_angles(110, 1);
-        for y in 0..theta_2.nrows() {
-            for z in 0..theta_2.ncols() {
-                // BRAIN_HEIGHT = 11; x = 1; BRAIN_WIDTH = 3;
-                let axon_angle = axon_angles[y + z];
+    /// Returns a reference to the values of every layer, from input to output.
+    pub fn get_layers(&self) -> Vec<&[FPN]> {
+        self.layers.iter().map(|l| l.as_slice()).collect()
+    }
 
-                let parent_id =
-                    (((axon_angle + random_rotation) % 1.0) * amount_parents).floor() as usize;
+    /// Returns a reference to the input layer values.
+    pub fn get_input_layer(&self) -> &[FPN] {
+        self.layers[0].as_slice()
+    }
 
-                let r = (rng.gen::<f64>() * 2.0 - 1.0).powi(9);
+    /// Creates a child of `parents` with a bit of mutation, using the operators of the Processing code.
+    ///
+    /// See `recombine_with` for the details.
+    ///
+    /// # Processing equivalent
+    /// *Brain.pde/evolve*, although the structure of the brain is different and there are no calls to `Axon`s here.
+    pub fn recombine(parents: &[&Brain]) -> Result<Brain, ShapeMismatch> {
+        Brain::recombine_with(parents, &RecombinationConfig::default())
+    }
 
-                theta_2[(y, z)] = parents[parent_id].borrow().brain.theta_2[(y, z)]
-                    + r * MUTABILITY / MUTATE_MULTI;
-            }
+    /// Creates a child of `parents` with a bit of mutation, `config` decides how the weights are crossed and mutated.
+    /// Every layer gets the activation function of a random parent.
+    /// The plasticity is the average of the parents with a bit of mutation, what they learned is not passed on.
+    /// How much the weights mutate depends on the mutability the child inherits, see `inherit_mutability`.
+    ///
+    /// Returns an error if not all parents have the same shape.
+    pub fn recombine_with(
+        parents: &[&Brain],
+        config: &RecombinationConfig,
+    ) -> Result<Brain, ShapeMismatch> {
+        let shape = &parents[0].shape;
+        if let Some(other) = parents.iter().find(|p| p.shape != *shape) {
+            return Err(ShapeMismatch {
+                expected: shape.clone(),
+                found: other.shape.clone(),
+            });
         }
 
-        Brain {
-            a_1,
-            theta_1,
-            a_2,
-            theta_2,
-            a_3,
-        }
+        let sizes = shape.get_layer_sizes();
+        let mut rng = rand::thread_rng();
+
+        let parent_mutabilities: Vec<FPN> = parents.iter().map(|p| p.mutability).collect();
+        let mutability =
+            super::inherit_mutability(&parent_mutabilities, MIN_MUTABILITY, MAX_MUTABILITY);
+
+        let parent_weights: Vec<&[DMatrix<FPN>]> = parents.iter().map(|p| &p.weights[..]).collect();
+        let mut weights = config.crossover.cross(&parent_weights);
+        config.mutation.mutate(&mut weights, mutability);
+
+        let activations = (0..sizes.len() - 1)
+            .map(|x| parents[rng.gen_range(0, parents.len())].activations[x])
+            .collect();
+
+        let parent_plasticities: Vec<Plasticity> = parents.iter().map(|p| p.plasticity).collect();
+        let mut plasticity = Plasticity::average(&parent_plasticities);
+        plasticity.mutate();
+
+        Ok(Brain {
+            layers: Brain::empty_layers(&sizes),
+            weights,
+            activations,
+            mutability,
+            plasticity,
+            learned_weights: None,
+            shape: shape.clone(),
+        })
+    }
+}
+
+impl super::RecombinationInfinite for Brain {
+    /// Recombines the first parent with every other parent of the same shape, see `Brain::recombine_with`.
+    ///
+    /// Parents with a different shape than the first one are left out.
+    fn recombination_infinite_brains(
+        parents: &[(&Brain, f64)],
+        context: &RecombinationConfig,
+    ) -> Self {
+        let shape = parents[0].0.get_shape();
+        let brains: Vec<&Brain> = parents
+            .iter()
+            .map(|(b, _)| *b)
+            .filter(|b| b.get_shape() == shape)
+            .collect();
+
+        Brain::recombine_with(&brains, context).expect("All parents have the same shape.")
     }
 }
 
@@ -279,16 +472,271 @@ impl Brain {
     }
 }
 
-fn get_axon_angles(max: usize, x: usize) -> Vec<f64> {
-    let mut vec = Vec::with_capacity(max);
-    const BRAIN_WIDTH: f64 = 3.0;
-    const BRAIN_HEIGHT: f64 = 11.0;
+/// Calculates the values of every layer but the first from the one before it.
+///
+/// `weights` and `activations` have one element for every layer but the first, the first row of every matrix holds
+/// the weights of the bias node. The values are written straight into `layers`, so this doesn't allocate.
+pub(crate) fn feed_forward(
+    layers: &mut [RowDVector<FPN>],
+    weights: &[DMatrix<FPN>],
+    activations: &[Activation],
+) {
+    for (i, theta) in weights.iter().enumerate() {
+        let (previous, next) = layers.split_at_mut(i + 1);
+        let input = previous[i].as_slice();
+        let activation = activations[i];
+
+        // `theta` is stored column-major, so every column (one per neuron) is a contiguous slice.
+        // Its first element is the weight of the bias node.
+        for (v, column) in next[0]
+            .iter_mut()
+            .zip(theta.as_slice().chunks(theta.nrows()))
+        {
+            let z = column[0]
+                + column[1..]
+                    .iter()
+                    .zip(input)
+                    .map(|(w, x)| w * x)
+                    .sum::<FPN>();
+
+            *v = activation.apply(z);
+        }
+    }
+}
 
-    for i in 0..max {
-        vec.push(
-            PI + ((i as f64 - BRAIN_HEIGHT) / 2.0).atan2(x as f64 - BRAIN_WIDTH / 2.0) / (2.0 * PI),
-        );
+/// Runs many brains of the same shape at once, the batched version of `NeuralNet::run`.
+///
+/// For every weight and every neuron the values of all brains are stored next to each other,
+/// so every multiply-add goes over the whole batch in a row and can be vectorised.
+/// The weights are copied by `new`, so a batch has to be built again when a brain learns or the population changes.
+pub struct BrainBatch {
+    /// The amount of brains in the batch.
+    amount: usize,
+    // For every layer but the input layer: the weights in the order of `DMatrix::as_slice`, every one `amount` times.
+    weights: Vec<Vec<FPN>>,
+    // For every layer but the input layer: the activation function of every brain.
+    activations: Vec<Vec<Activation>>,
+    // For every layer: the value of every neuron, every one `amount` times.
+    layers: Vec<Vec<FPN>>,
+}
+
+impl BrainBatch {
+    /// Copies the weights of `brains`, the weights they learned if they did.
+    ///
+    /// Returns an error if not all brains have the same shape.
+    ///
+    /// # Panics
+    /// If `brains` is empty.
+    pub fn new(brains: &[&Brain]) -> Result<Self, ShapeMismatch> {
+        assert!(!brains.is_empty(), "A batch needs at least one brain.");
+
+        let shape = &brains[0].shape;
+        if let Some(other) = brains.iter().find(|b| b.shape != *shape) {
+            return Err(ShapeMismatch {
+                expected: shape.clone(),
+                found: other.shape.clone(),
+            });
+        }
+
+        let amount = brains.len();
+        let all_weights: Vec<&[DMatrix<FPN>]> = brains
+            .iter()
+            .map(|b| &b.learned_weights.as_ref().unwrap_or(&b.weights)[..])
+            .collect();
+
+        let weights = (0..all_weights[0].len())
+            .map(|x| {
+                let mut layer = vec![0.0; all_weights[0][x].len() * amount];
+                for (b, weights) in all_weights.iter().enumerate() {
+                    for (i, w) in weights[x].iter().enumerate() {
+                        layer[i * amount + b] = *w;
+                    }
+                }
+
+                layer
+            })
+            .collect();
+        let activations = (0..brains[0].activations.len())
+            .map(|x| brains.iter().map(|b| b.activations[x]).collect())
+            .collect();
+        let layers = shape
+            .get_layer_sizes()
+            .iter()
+            .map(|size| vec![0.0; size * amount])
+            .collect();
+
+        Ok(BrainBatch {
+            amount,
+            weights,
+            activations,
+            layers,
+        })
+    }
+
+    /// Returns the amount of brains in this batch.
+    pub fn len(&self) -> usize {
+        self.amount
     }
 
-    vec
+    /// Returns `true` if this batch has no brains, which never happens.
+    pub fn is_empty(&self) -> bool {
+        self.amount == 0
+    }
+
+    /// Copies the input layer of every brain, they have to be in the same order as in `new`.
+    pub fn load_inputs(&mut self, brains: &[&Brain]) {
+        let amount = self.amount;
+
+        for (b, brain) in brains.iter().enumerate() {
+            for (i, v) in brain.layers[0].iter().enumerate() {
+                self.layers[0][i * amount + b] = *v;
+            }
+        }
+    }
+
+    /// Calculates the values of every layer but the first for every brain, see `feed_forward`.
+    pub fn run(&mut self) {
+        let amount = self.amount;
+
+        for (x, theta) in self.weights.iter().enumerate() {
+            let (previous, next) = self.layers.split_at_mut(x + 1);
+            let input = &previous[x];
+            let output = &mut next[0];
+            // Every neuron has the weight of the bias node and one for every input.
+            let rows = input.len() / amount + 1;
+
+            for (z, neuron) in output.chunks_mut(amount).enumerate() {
+                let column = &theta[z * rows * amount..(z + 1) * rows * amount];
+
+                neuron.copy_from_slice(&column[..amount]);
+                for (weights, values) in column[amount..].chunks(amount).zip(input.chunks(amount)) {
+                    for ((v, w), i) in neuron.iter_mut().zip(weights).zip(values) {
+                        *v += w * i;
+                    }
+                }
+
+                for (v, activation) in neuron.iter_mut().zip(&self.activations[x]) {
+                    *v = activation.apply(*v);
+                }
+            }
+        }
+    }
+
+    /// Copies the values of every layer into `brains`, as if they had run themselves.
+    ///
+    /// They have to be in the same order as in `new`.
+    pub fn store_values(&self, brains: &mut [&mut Brain]) {
+        let amount = self.amount;
+
+        for (b, brain) in brains.iter_mut().enumerate() {
+            for (layer, values) in brain.layers.iter_mut().zip(&self.layers) {
+                for (i, v) in layer.iter_mut().enumerate() {
+                    *v = values[i * amount + b];
+                }
+            }
+        }
+    }
+}
+
+/// The names of the inputs set by `load_input`, the other inputs are always 0.
+pub(super) const INPUT_LABELS: [&str; 6] = [
+    "Memory",
+    "Energy",
+    "Mouth hue",
+    "Tile hue",
+    "Tile saturation",
+    "Tile brightness",
+];
+
+impl super::ExportBrain for Brain {
+    /// Every layer is a column with its bias node on top, outputs are labeled with their intention.
+    fn to_dot(&self) -> String {
+        let mut dot = String::new();
+        super::export::write_dot_header(&mut dot, "brain");
+
+        let sizes = self.shape.get_layer_sizes();
+        let intentions = self.intentions();
+        for (x, size) in sizes.iter().enumerate() {
+            let last = x == sizes.len() - 1;
+            if !last {
+                dot.push_str(&format!(
+                    "    l{}_bias [label=\"bias\", shape=box, style=dotted];\n",
+                    x
+                ));
+            }
+
+            for i in 0..*size {
+                let (label, shape) = if x == 0 {
+                    let label = INPUT_LABELS
+                        .get(i)
+                        .map_or(format!("Input {}", i), |l| l.to_string());
+                    (label, "box")
+                } else if last {
+                    let label = intentions
+                        .get(i)
+                        .cloned()
+                        .unwrap_or(format!("Output {}", i));
+                    (label, "doublecircle")
+                } else {
+                    (String::new(), "circle")
+                };
+
+                dot.push_str(&format!(
+                    "    l{}_{} [label=\"{}\", shape={}];\n",
+                    x, i, label, shape
+                ));
+            }
+        }
+
+        for (x, theta) in self.weights.iter().enumerate() {
+            for y in 0..theta.nrows() {
+                let from = if y == 0 {
+                    format!("l{}_bias", x)
+                } else {
+                    format!("l{}_{}", x, y - 1)
+                };
+
+                for z in 0..theta.ncols() {
+                    dot.push_str(&format!(
+                        "    {} -> l{}_{} [{}];\n",
+                        from,
+                        x + 1,
+                        z,
+                        super::export::get_edge_attributes(theta[(y, z)], true)
+                    ));
+                }
+            }
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Every layer but the input layer has its activation function and its weights,
+    /// one row per neuron of the previous layer with the bias node first.
+    fn to_json(&self) -> serde_json::Value {
+        let layers: Vec<_> = self
+            .weights
+            .iter()
+            .zip(&self.activations)
+            .map(|(theta, activation)| {
+                let rows: Vec<Vec<FPN>> = (0..theta.nrows())
+                    .map(|y| (0..theta.ncols()).map(|z| theta[(y, z)]).collect())
+                    .collect();
+
+                json!({
+                    "activation": activation,
+                    "weights": rows,
+                })
+            })
+            .collect();
+
+        json!({
+            "type": "feed-forward",
+            "hidden_layers": self.shape.hidden_layers,
+            "mutability": self.mutability,
+            "plasticity": self.plasticity,
+            "layers": layers,
+        })
+    }
 }
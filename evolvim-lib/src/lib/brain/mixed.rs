@@ -0,0 +1,291 @@
+//! Contains `MixedBrain`, which lets creatures with different kinds of brains live in the same world.
+//!
+//! Every creature keeps the kind of brain it was born with and only has children with creatures of the same kind,
+//! so the kinds compete for the same food and space without ever mixing. This makes it possible to compare them
+//! under exactly the same circumstances.
+
+#![warn(missing_docs)]
+
+extern crate rand;
+extern crate serde_json;
+
+use self::rand::Rng;
+use super::{
+    Brain, Environment, EnvironmentMut, ExportBrain, GenerateRandom, Intentions, NeuralNet,
+    Plasticity, RecombinationConfig, RecombinationInfinite, RecurrentBrain, RecurrentConfig,
+};
+use crate::neat::{HyperNeatBrain, NeatBrain, NeatContext};
+use crate::softbody::HLSoftBody;
+
+/// One brain of any of the kinds this crate provides.
+#[derive(Serialize, Deserialize)]
+pub enum MixedBrain {
+    /// A `Brain`.
+    FeedForward(Brain),
+    /// A `RecurrentBrain`.
+    Recurrent(RecurrentBrain),
+    /// A `NeatBrain`.
+    Neat(NeatBrain),
+    /// A `HyperNeatBrain`.
+    HyperNeat(HyperNeatBrain),
+}
+
+/// The contexts of the kinds of brain that need one.
+///
+/// NEAT genomes and CPPNs each have their own innovations and species, so they never get mixed up.
+#[derive(Default, Serialize, Deserialize)]
+pub struct MixedContext {
+    /// The context of every `Brain`.
+    pub feed_forward: RecombinationConfig,
+    /// The context of every `RecurrentBrain`.
+    pub recurrent: RecurrentConfig,
+    /// The context of every `NeatBrain`.
+    pub neat: NeatContext,
+    /// The context of every `HyperNeatBrain`.
+    pub hyperneat: NeatContext,
+}
+
+impl NeuralNet for MixedBrain {
+    type Context = MixedContext;
+
+    fn load_input(&mut self, env: &Environment) {
+        use MixedBrain::*;
+
+        match self {
+            FeedForward(b) => b.load_input(env),
+            Recurrent(b) => b.load_input(env),
+            Neat(b) => b.load_input(env),
+            HyperNeat(b) => b.load_input(env),
+        }
+    }
+
+    fn run(&mut self) {
+        use MixedBrain::*;
+
+        match self {
+            FeedForward(b) => b.run(),
+            Recurrent(b) => b.run(),
+            Neat(b) => b.run(),
+            HyperNeat(b) => b.run(),
+        }
+    }
+
+    fn learn(&mut self, time_step: f64) {
+        use MixedBrain::*;
+
+        match self {
+            FeedForward(b) => b.learn(time_step),
+            Recurrent(b) => b.learn(time_step),
+            Neat(b) => b.learn(time_step),
+            HyperNeat(b) => b.learn(time_step),
+        }
+    }
+
+    fn use_output<B>(&self, env: &mut EnvironmentMut<B>, time_step: f64) {
+        use MixedBrain::*;
+
+        match self {
+            FeedForward(b) => b.use_output(env, time_step),
+            Recurrent(b) => b.use_output(env, time_step),
+            Neat(b) => b.use_output(env, time_step),
+            HyperNeat(b) => b.use_output(env, time_step),
+        }
+    }
+
+    /// Returns the kind of the brain inside.
+    fn get_architecture(&self) -> &'static str {
+        use MixedBrain::*;
+
+        match self {
+            FeedForward(b) => b.get_architecture(),
+            Recurrent(b) => b.get_architecture(),
+            Neat(b) => b.get_architecture(),
+            HyperNeat(b) => b.get_architecture(),
+        }
+    }
+
+    fn metabolic_cost(&self) -> f64 {
+        use MixedBrain::*;
+
+        match self {
+            FeedForward(b) => b.metabolic_cost(),
+            Recurrent(b) => b.metabolic_cost(),
+            Neat(b) => b.metabolic_cost(),
+            HyperNeat(b) => b.metabolic_cost(),
+        }
+    }
+
+    /// Note that every kind of brain measures its mutability differently.
+    fn get_mutability(&self) -> Option<f64> {
+        use MixedBrain::*;
+
+        match self {
+            FeedForward(b) => b.get_mutability(),
+            Recurrent(b) => b.get_mutability(),
+            Neat(b) => b.get_mutability(),
+            HyperNeat(b) => b.get_mutability(),
+        }
+    }
+
+    fn get_plasticity(&self) -> Option<Plasticity> {
+        use MixedBrain::*;
+
+        match self {
+            FeedForward(b) => b.get_plasticity(),
+            Recurrent(b) => b.get_plasticity(),
+            Neat(b) => b.get_plasticity(),
+            HyperNeat(b) => b.get_plasticity(),
+        }
+    }
+
+    /// Always `None`: NEAT and HyperNEAT brains get their species from different registries, which hand out the
+    /// same ids. Their species can be found in the `MixedContext`.
+    fn get_species(&self) -> Option<usize> {
+        None
+    }
+
+    fn update_context(context: &mut MixedContext, creatures: &[HLSoftBody<Self>], time: f64) {
+        let mut creatures: Vec<_> = creatures.iter().map(|c| c.borrow_mut()).collect();
+
+        let neat = creatures.iter_mut().filter_map(|c| match &mut c.brain {
+            MixedBrain::Neat(b) => Some(b),
+            _ => None,
+        });
+        context.neat.species.update_brains(neat, time);
+
+        let hyperneat = creatures.iter_mut().filter_map(|c| match &mut c.brain {
+            MixedBrain::HyperNeat(b) => Some(b),
+            _ => None,
+        });
+        context.hyperneat.species.update_brains(hyperneat, time);
+    }
+}
+
+impl Intentions for MixedBrain {
+    fn wants_birth(&self) -> f64 {
+        use MixedBrain::*;
+
+        match self {
+            FeedForward(b) => b.wants_birth(),
+            Recurrent(b) => b.wants_birth(),
+            Neat(b) => b.wants_birth(),
+            HyperNeat(b) => b.wants_birth(),
+        }
+    }
+
+    fn wants_help_birth(&self) -> f64 {
+        use MixedBrain::*;
+
+        match self {
+            FeedForward(b) => b.wants_help_birth(),
+            Recurrent(b) => b.wants_help_birth(),
+            Neat(b) => b.wants_help_birth(),
+            HyperNeat(b) => b.wants_help_birth(),
+        }
+    }
+}
+
+impl GenerateRandom for MixedBrain {
+    /// Returns a random brain of a random kind, every kind is equally likely.
+    fn new_random() -> Self {
+        MixedBrain::new_random_with_context(&MixedContext::default())
+    }
+
+    /// Feed-forward brains get the shape of the world.
+    fn new_random_with_context(context: &MixedContext) -> Self {
+        use MixedBrain::*;
+
+        match rand::thread_rng().gen_range(0, 4) {
+            0 => FeedForward(Brain::new_random_with_context(&context.feed_forward)),
+            1 => Recurrent(RecurrentBrain::new_random()),
+            2 => Neat(NeatBrain::new_random()),
+            _ => HyperNeat(HyperNeatBrain::new_random()),
+        }
+    }
+}
+
+/// Returns the parents `get` returns a brain for, with their energy.
+fn get_parents<'a, T>(
+    parents: &[(&'a MixedBrain, f64)],
+    get: fn(&'a MixedBrain) -> Option<&'a T>,
+) -> Vec<(&'a T, f64)> {
+    parents
+        .iter()
+        .filter_map(|(b, energy)| get(b).map(|b| (b, *energy)))
+        .collect()
+}
+
+impl RecombinationInfinite for MixedBrain {
+    /// The child gets the kind of brain of the first parent, parents with another kind are left out.
+    fn recombination_infinite_brains(parents: &[(&Self, f64)], context: &MixedContext) -> Self {
+        use MixedBrain::*;
+
+        match parents[0].0 {
+            FeedForward(_) => {
+                let parents = get_parents(parents, |b| match b {
+                    FeedForward(b) => Some(b),
+                    _ => None,
+                });
+                FeedForward(Brain::recombination_infinite_brains(
+                    &parents,
+                    &context.feed_forward,
+                ))
+            }
+            Recurrent(_) => {
+                let parents = get_parents(parents, |b| match b {
+                    Recurrent(b) => Some(b),
+                    _ => None,
+                });
+                Recurrent(RecurrentBrain::recombination_infinite_brains(
+                    &parents,
+                    &context.recurrent,
+                ))
+            }
+            Neat(_) => {
+                let parents = get_parents(parents, |b| match b {
+                    Neat(b) => Some(b),
+                    _ => None,
+                });
+                Neat(NeatBrain::recombination_infinite_brains(
+                    &parents,
+                    &context.neat,
+                ))
+            }
+            HyperNeat(_) => {
+                let parents = get_parents(parents, |b| match b {
+                    HyperNeat(b) => Some(b),
+                    _ => None,
+                });
+                HyperNeat(HyperNeatBrain::recombination_infinite_brains(
+                    &parents,
+                    &context.hyperneat,
+                ))
+            }
+        }
+    }
+}
+
+impl ExportBrain for MixedBrain {
+    fn to_dot(&self) -> String {
+        use MixedBrain::*;
+
+        match self {
+            FeedForward(b) => b.to_dot(),
+            Recurrent(b) => b.to_dot(),
+            Neat(b) => b.to_dot(),
+            HyperNeat(b) => b.to_dot(),
+        }
+    }
+
+    /// Returns the JSON of the brain inside, its `type` tells which kind it is.
+    fn to_json(&self) -> serde_json::Value {
+        use MixedBrain::*;
+
+        match self {
+            FeedForward(b) => b.to_json(),
+            Recurrent(b) => b.to_json(),
+            Neat(b) => b.to_json(),
+            HyperNeat(b) => b.to_json(),
+        }
+    }
+}
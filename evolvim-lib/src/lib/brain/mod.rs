@@ -1,10 +1,25 @@
+pub mod activation;
+pub use activation::Activation;
+pub mod crossover;
+pub use crossover::{Crossover, RecombinationConfig, WeightMutation};
+pub mod export;
+pub use export::ExportBrain;
 pub mod feed_forward;
-pub use feed_forward::Brain;
+pub use feed_forward::{Brain, BrainBatch, BrainShape};
+pub mod mixed;
+pub use mixed::{MixedBrain, MixedContext};
+pub mod plasticity;
+pub use plasticity::Plasticity;
+pub mod recurrent;
+pub use recurrent::{RecurrentBrain, RecurrentConfig};
 
 mod environment;
 pub use environment::{Environment, EnvironmentMut};
 
 pub trait NeuralNet: Intentions {
+    /// State shared by every brain of this type in a world, owned and saved by the `Board`.
+    type Context: Default + serde::Serialize + serde::de::DeserializeOwned;
+
     fn load_input(&mut self, env: &Environment);
 
     fn run(&mut self);
@@ -14,9 +29,75 @@ pub trait NeuralNet: Intentions {
         self.run();
     }
 
-    fn use_output(&self, env: &mut EnvironmentMut<Self>, time_step: f64)
+    /// Changes the weights of this brain with its `Plasticity` for `time_step` years, based on the values of the last `run`.
+    ///
+    /// Only called when `Board::lifetime_learning` is on, brain types without plasticity don't learn.
+    fn learn(&mut self, _time_step: f64) {}
+
+    /// Lets the body act on the output of the brain.
+    ///
+    /// `env` can belong to creatures with another brain type, like a `MixedBrain` that contains this brain.
+    fn use_output<B>(&self, env: &mut EnvironmentMut<B>, time_step: f64)
     where
         Self: std::marker::Sized;
+
+    /// Returns the kind of brain this is, creatures only have children with creatures of the same kind.
+    fn get_architecture(&self) -> &'static str;
+
+    /// Returns how big this brain is, creatures pay `Board::brain_cost` energy per year for every unit.
+    fn metabolic_cost(&self) -> f64 {
+        0.0
+    }
+
+    /// Returns how strongly this brain mutates its children, `None` for brain types that don't evolve it.
+    fn get_mutability(&self) -> Option<f64> {
+        None
+    }
+
+    /// Returns the rule this brain learns with, `None` for brain types that don't learn.
+    fn get_plasticity(&self) -> Option<Plasticity> {
+        None
+    }
+
+    /// Returns the species this brain belongs to, `None` for brain types without species.
+    fn get_species(&self) -> Option<usize> {
+        None
+    }
+
+    /// Called once every `Board::update` after births and deaths so the `Context` can keep up with the population.
+    fn update_context(
+        _context: &mut Self::Context,
+        _creatures: &[crate::softbody::HLSoftBody<Self>],
+        _time: f64,
+    ) where
+        Self: std::marker::Sized,
+    {
+    }
+}
+
+/// How much the mutability of a child can differ from that of its parents.
+const MUTABILITY_CHANGE: f64 = 0.2;
+
+/// Returns the mutability of a child: the average mutability of its parents multiplied by a random factor around 1,
+/// kept between `min` and `max`.
+///
+/// Brains that mutate as much as suits their surroundings have more children, so evolution tunes its own exploration rate.
+pub fn inherit_mutability(parents: &[f64], min: f64, max: f64) -> f64 {
+    use rand::distributions::StandardNormal;
+    use rand::Rng;
+
+    let average = parents.iter().sum::<f64>() / parents.len() as f64;
+    let change: f64 = rand::thread_rng().sample(StandardNormal);
+
+    (average * (MUTABILITY_CHANGE * change).exp())
+        .max(min)
+        .min(max)
+}
+
+/// Returns a hue between 0 and 1 for drawing a species, consecutive species get clearly different hues.
+pub fn get_species_hue(species: usize) -> f64 {
+    // The golden ratio spreads the hues out evenly.
+    (species as f64 * 0.618_033_988_749_895) % 1.0
 }
 
 pub trait Intentions {
@@ -26,6 +107,14 @@ pub trait Intentions {
 
 pub trait GenerateRandom {
     fn new_random() -> Self;
+
+    /// Returns a random brain for a world with the given context, most kinds of brain don't use it.
+    fn new_random_with_context(_context: &<Self as NeuralNet>::Context) -> Self
+    where
+        Self: NeuralNet + std::marker::Sized,
+    {
+        Self::new_random()
+    }
 }
 
 pub trait RecombinationTwoParents {
@@ -34,10 +123,25 @@ pub trait RecombinationTwoParents {
         Self: NeuralNet + std::marker::Sized;
 }
 
-pub trait RecombinationInfinite {
-    fn recombination_infinite_parents(parents: &Vec<crate::softbody::HLSoftBody<Self>>) -> Self
+pub trait RecombinationInfinite: NeuralNet {
+    /// Returns a child of the given brains, each with the energy of its creature as a measure of how fit it is.
+    fn recombination_infinite_brains(parents: &[(&Self, f64)], context: &Self::Context) -> Self
     where
-        Self: NeuralNet + std::marker::Sized;
+        Self: std::marker::Sized;
+
+    fn recombination_infinite_parents(
+        parents: &Vec<crate::softbody::HLSoftBody<Self>>,
+        context: &Self::Context,
+    ) -> Self
+    where
+        Self: std::marker::Sized,
+    {
+        let parents: Vec<_> = parents.iter().map(|p| p.borrow()).collect();
+        let brains: Vec<(&Self, f64)> =
+            parents.iter().map(|p| (&p.brain, p.get_energy())).collect();
+
+        Self::recombination_infinite_brains(&brains, context)
+    }
 }
 
 pub trait ProvideInformation {
@@ -0,0 +1,92 @@
+//! Contains `Plasticity`, the rule brains use to change their weights during the life of a creature.
+//!
+//! This is the ABCD rule: a weight changes by `learning_rate * (a * pre * post + b * pre + c * post + d)` every year,
+//! where `pre` is the value of the neuron the link comes from and `post` the value of the neuron it goes to.
+//! The coefficients evolve, but what a brain learns is not passed on to its children.
+
+#![warn(missing_docs)]
+
+extern crate rand;
+
+use self::rand::distributions::StandardNormal;
+use self::rand::Rng;
+
+/// The highest learning rate that can evolve, this is the change per year.
+pub const MAX_LEARNING_RATE: f64 = 100.0;
+/// The highest absolute value of the coefficients `a`, `b`, `c` and `d`.
+pub const MAX_COEFFICIENT: f64 = 1.0;
+/// Learning never makes a weight bigger than this or smaller than minus this.
+pub const MAX_LEARNED_WEIGHT: f64 = 5.0;
+
+/// How much the learning rate of a child can differ from that of its parents.
+const LEARNING_RATE_CHANGE: f64 = 5.0;
+/// How much the other coefficients of a child can differ from those of its parents.
+const COEFFICIENT_CHANGE: f64 = 0.05;
+
+/// The coefficients of the ABCD rule.
+///
+/// The default rule has a learning rate of 0, so it never changes a weight. Evolution can turn learning on.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Plasticity {
+    /// How fast weights change, between 0 and `MAX_LEARNING_RATE`.
+    pub learning_rate: f64,
+    /// The weight of the correlation between both neurons, this is the Hebbian part of the rule.
+    pub a: f64,
+    /// The weight of the neuron the link comes from.
+    pub b: f64,
+    /// The weight of the neuron the link goes to.
+    pub c: f64,
+    /// A constant change.
+    pub d: f64,
+}
+
+impl Plasticity {
+    /// Returns `weight` after learning for `time_step` years, kept between `-MAX_LEARNED_WEIGHT` and `MAX_LEARNED_WEIGHT`.
+    pub fn update_weight(&self, weight: f64, pre: f64, post: f64, time_step: f64) -> f64 {
+        let change = self.a * pre * post + self.b * pre + self.c * post + self.d;
+
+        (weight + self.learning_rate * change * time_step)
+            .max(-MAX_LEARNED_WEIGHT)
+            .min(MAX_LEARNED_WEIGHT)
+    }
+
+    /// Returns whether this rule can change a weight, brains don't learn at all when it can't.
+    pub fn is_active(&self) -> bool {
+        self.learning_rate > 0.0
+            && (self.a != 0.0 || self.b != 0.0 || self.c != 0.0 || self.d != 0.0)
+    }
+
+    /// Returns the average rule of `parents`.
+    ///
+    /// # Panics
+    /// If `parents` is empty.
+    pub fn average(parents: &[Plasticity]) -> Plasticity {
+        assert!(!parents.is_empty(), "An average needs at least one rule.");
+
+        let amount = parents.len() as f64;
+        let sum = |get: fn(&Plasticity) -> f64| parents.iter().map(get).sum::<f64>() / amount;
+
+        Plasticity {
+            learning_rate: sum(|p| p.learning_rate),
+            a: sum(|p| p.a),
+            b: sum(|p| p.b),
+            c: sum(|p| p.c),
+            d: sum(|p| p.d),
+        }
+    }
+
+    /// Changes every coefficient a little.
+    pub fn mutate(&mut self) {
+        let mut rng = rand::thread_rng();
+        let mut change = |change: f64| change * rng.sample::<f64, _>(StandardNormal);
+
+        self.learning_rate = (self.learning_rate + change(LEARNING_RATE_CHANGE))
+            .max(0.0)
+            .min(MAX_LEARNING_RATE);
+        for coefficient in [&mut self.a, &mut self.b, &mut self.c, &mut self.d].iter_mut() {
+            **coefficient = (**coefficient + change(COEFFICIENT_CHANGE))
+                .max(-MAX_COEFFICIENT)
+                .min(MAX_COEFFICIENT);
+        }
+    }
+}
@@ -0,0 +1,411 @@
+//! Contains a recurrent neural network that remembers things between ticks.
+//!
+//! The hidden layer is a gated recurrent unit (GRU): every tick it decides how much of its previous state to keep
+//! and how much to replace with something new. This state is saved together with the creature.
+
+#![warn(missing_docs)]
+
+extern crate nalgebra;
+extern crate serde_json;
+
+use self::nalgebra::{DMatrix, RowDVector};
+use self::serde_json::json;
+use super::crossover::{Crossover, WeightMutation};
+use super::feed_forward::{
+    DEFAULT_MUTABILITY, INPUT_LABELS, INPUT_LAYER_SIZE, MAX_MUTABILITY, MIN_MUTABILITY,
+    OUTPUT_LAYER_SIZE,
+};
+
+type FPN = f64;
+
+/// The amount of neurons in the hidden state of a `RecurrentBrain` created by `GenerateRandom::new_random`.
+pub const DEFAULT_HIDDEN_SIZE: usize = 10;
+
+/// Decides how every `RecurrentBrain` in a world is recombined, it is the context of `RecurrentBrain`.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RecurrentConfig {
+    /// How the weights are mutated after they are taken from the parents.
+    pub mutation: WeightMutation,
+}
+
+/// A neural network with a gated hidden state that is carried across ticks.
+///
+/// It has the same inputs and outputs as `Brain` so creatures behave the same way, only the thinking differs.
+///
+/// # Usage
+/// Give it some input with `load_input()` and perform the calculations with `run()`, then extract the output with `get_output`.
+#[derive(Serialize, Deserialize)]
+pub struct RecurrentBrain {
+    input: RowDVector<FPN>,
+    /// The state that is carried across ticks.
+    hidden: RowDVector<FPN>,
+    output: RowDVector<FPN>,
+
+    // All weight matrices take a bias node as their first row.
+    // The gates have dimensions 1 + INPUT_LAYER_SIZE + hidden size by hidden size.
+    /// Decides how much of the hidden state gets replaced.
+    update_gate: DMatrix<FPN>,
+    /// Decides how much of the hidden state is used to come up with the replacement.
+    reset_gate: DMatrix<FPN>,
+    /// Comes up with the replacement.
+    candidate: DMatrix<FPN>,
+    /// Turns the hidden state into output, its dimensions are 1 + hidden size by OUTPUT_LAYER_SIZE.
+    readout: DMatrix<FPN>,
+
+    /// How much the weights of children change, this evolves as well.
+    mutability: FPN,
+}
+
+impl super::NeuralNet for RecurrentBrain {
+    type Context = RecurrentConfig;
+
+    fn load_input(&mut self, env: &super::Environment) {
+        let memory = self.get_memory();
+        let input = &mut self.input;
+
+        // Load the memory, the hidden state remembers things too but this keeps the inputs equal to `Brain`
+        input[0] = memory;
+
+        // The current energy of the creature
+        input[1] = env.this_body.get_energy();
+
+        // The current mouth hue
+        input[2] = env.this_body.get_mouth_hue();
+
+        // Look directly underneath the creature
+        let pos = env.this_body.get_position();
+        let tile = env
+            .terrain
+            .get_updated_tile_at(pos.into(), env.time, env.climate);
+        let colors = tile.get_hsba_color();
+        input[3] = colors[0] as FPN;
+        input[4] = colors[1] as FPN;
+        input[5] = colors[2] as FPN;
+    }
+
+    /// Updates the hidden state and calculates the output.
+    fn run(&mut self) {
+        let hidden_size = self.hidden.len();
+        let with_bias = self.input.clone().insert_column(0, 1.0);
+
+        let x_h = concat(&with_bias, &self.hidden);
+        let mut z = &x_h * &self.update_gate;
+        sigmoid(&mut z);
+        let mut r = &x_h * &self.reset_gate;
+        sigmoid(&mut r);
+
+        let x_rh = concat(&with_bias, &r.component_mul(&self.hidden));
+        let mut candidate = &x_rh * &self.candidate;
+        tanh(&mut candidate);
+
+        for i in 0..hidden_size {
+            self.hidden[i] = (1.0 - z[i]) * self.hidden[i] + z[i] * candidate[i];
+        }
+
+        // Don't perform the sigmoid function on the output, just like `Brain`.
+        self.output = self.hidden.clone().insert_column(0, 1.0) * &self.readout;
+    }
+
+    fn get_architecture(&self) -> &'static str {
+        "recurrent"
+    }
+
+    fn get_mutability(&self) -> Option<f64> {
+        Some(self.mutability)
+    }
+
+    /// Every weight of the gates and the readout costs one unit.
+    fn metabolic_cost(&self) -> f64 {
+        (self.update_gate.len() + self.reset_gate.len() + self.candidate.len() + self.readout.len())
+            as f64
+    }
+
+    fn use_output<B>(&self, env: &mut super::EnvironmentMut<B>, time_step: f64) {
+        let acceleration = self.wants_acceleration();
+        env.this_body.accelerate(acceleration, time_step);
+
+        let turning = self.wants_turning();
+        env.this_body.turn(turning, time_step);
+
+        let tile_pos = env.this_body.get_random_covered_tile(env.board_size);
+        let eat_amount = self.wants_to_eat();
+        env.this_body.eat(
+            eat_amount,
+            time_step,
+            env.time,
+            env.climate,
+            env.terrain,
+            tile_pos,
+        );
+
+        let mouth_hue = self.wants_mouth_hue();
+        env.this_body.set_mouth_hue(mouth_hue);
+    }
+}
+
+impl super::GenerateRandom for RecurrentBrain {
+    /// Returns a brain with `DEFAULT_HIDDEN_SIZE` hidden neurons and completely random weights.
+    fn new_random() -> Self {
+        RecurrentBrain::new_random_with_size(DEFAULT_HIDDEN_SIZE)
+    }
+}
+
+impl RecurrentBrain {
+    /// Returns a brain with `hidden_size` hidden neurons and completely random weights.
+    pub fn new_random_with_size(hidden_size: usize) -> Self {
+        let gate_rows = 1 + INPUT_LAYER_SIZE + hidden_size;
+
+        // Initialize random weights between [-0.5, 0.5].
+        let random =
+            |rows, cols| DMatrix::new_random(rows, cols) - DMatrix::from_element(rows, cols, 0.5);
+
+        RecurrentBrain {
+            input: RowDVector::zeros(INPUT_LAYER_SIZE),
+            hidden: RowDVector::zeros(hidden_size),
+            output: RowDVector::zeros(OUTPUT_LAYER_SIZE),
+
+            update_gate: random(gate_rows, hidden_size),
+            reset_gate: random(gate_rows, hidden_size),
+            candidate: random(gate_rows, hidden_size),
+            readout: random(1 + hidden_size, OUTPUT_LAYER_SIZE),
+
+            mutability: DEFAULT_MUTABILITY,
+        }
+    }
+
+    /// Returns the amount of neurons in the hidden state.
+    pub fn get_hidden_size(&self) -> usize {
+        self.hidden.len()
+    }
+
+    /// Returns a reference to the input values.
+    pub fn get_input_layer(&self) -> &[FPN] {
+        self.input.as_slice()
+    }
+
+    /// Returns a reference to the hidden state.
+    pub fn get_hidden_state(&self) -> &[FPN] {
+        self.hidden.as_slice()
+    }
+
+    /// Returns a reference to the output values.
+    pub fn get_output(&self) -> &[FPN] {
+        self.output.as_slice()
+    }
+
+    /// Returns the names of the outputs, equal to `Brain::intentions`.
+    pub fn intentions(&self) -> Vec<String> {
+        vec![
+            "Memory",
+            "Acceleration",
+            "Turning",
+            "Eating",
+            "Birth",
+            "Mouth hue",
+            "Help birth",
+        ]
+        .into_iter()
+        .map(String::from)
+        .collect()
+    }
+
+    /// Returns a copy of every weight matrix, in the order of the fields.
+    fn get_weights(&self) -> Vec<DMatrix<FPN>> {
+        vec![
+            self.update_gate.clone(),
+            self.reset_gate.clone(),
+            self.candidate.clone(),
+            self.readout.clone(),
+        ]
+    }
+}
+
+impl super::RecombinationInfinite for RecurrentBrain {
+    /// Every weight is taken from a random parent and mutated by the inherited mutability with the `WeightMutation`
+    /// of the world, the hidden state starts empty.
+    ///
+    /// Parents with a different hidden size than the first one are left out.
+    fn recombination_infinite_brains(
+        parents: &[(&RecurrentBrain, f64)],
+        config: &RecurrentConfig,
+    ) -> Self {
+        let hidden_size = parents[0].0.get_hidden_size();
+        let parents: Vec<&RecurrentBrain> = parents
+            .iter()
+            .map(|(b, _)| *b)
+            .filter(|b| b.get_hidden_size() == hidden_size)
+            .collect();
+
+        let mut child = RecurrentBrain::new_random_with_size(hidden_size);
+        let parent_mutabilities: Vec<FPN> = parents.iter().map(|p| p.mutability).collect();
+        child.mutability =
+            super::inherit_mutability(&parent_mutabilities, MIN_MUTABILITY, MAX_MUTABILITY);
+
+        let parent_weights: Vec<Vec<DMatrix<FPN>>> =
+            parents.iter().map(|p| p.get_weights()).collect();
+        let parent_weights: Vec<&[DMatrix<FPN>]> =
+            parent_weights.iter().map(|w| w.as_slice()).collect();
+        let mut weights = Crossover::Uniform.cross(&parent_weights);
+        config.mutation.mutate(&mut weights, child.mutability);
+
+        let mut child_matrices = [
+            &mut child.update_gate,
+            &mut child.reset_gate,
+            &mut child.candidate,
+            &mut child.readout,
+        ];
+        for (matrix, weights) in child_matrices.iter_mut().zip(weights) {
+            **matrix = weights;
+        }
+
+        child
+    }
+}
+
+impl super::Intentions for RecurrentBrain {
+    fn wants_birth(&self) -> f64 {
+        self.get_output()[4]
+    }
+
+    fn wants_help_birth(&self) -> f64 {
+        self.get_output()[6]
+    }
+}
+
+#[allow(missing_docs)]
+// All functions to retrieve intentions
+impl RecurrentBrain {
+    pub fn get_memory(&self) -> f64 {
+        self.get_output()[0]
+    }
+
+    pub fn wants_acceleration(&self) -> f64 {
+        self.get_output()[1]
+    }
+
+    pub fn wants_turning(&self) -> f64 {
+        self.get_output()[2]
+    }
+
+    pub fn wants_to_eat(&self) -> f64 {
+        self.get_output()[3]
+    }
+
+    pub fn wants_mouth_hue(&self) -> f64 {
+        self.get_output()[5]
+    }
+}
+
+impl super::ExportBrain for RecurrentBrain {
+    /// Shows how the candidate state is made from the inputs and the hidden state, and the readout.
+    /// The gates are left out to keep the graph readable, they are in the JSON export.
+    fn to_dot(&self) -> String {
+        use super::export::get_edge_attributes;
+
+        let mut dot = String::new();
+        super::export::write_dot_header(&mut dot, "brain");
+
+        dot.push_str("    bias [label=\"bias\", shape=box, style=dotted];\n");
+        for i in 0..INPUT_LAYER_SIZE {
+            let label = INPUT_LABELS
+                .get(i)
+                .map_or(format!("Input {}", i), |l| l.to_string());
+            dot.push_str(&format!("    i{} [label=\"{}\", shape=box];\n", i, label));
+        }
+        for h in 0..self.get_hidden_size() {
+            dot.push_str(&format!("    h{} [label=\"\"];\n", h));
+        }
+        let intentions = self.intentions();
+        for o in 0..OUTPUT_LAYER_SIZE {
+            let label = intentions
+                .get(o)
+                .cloned()
+                .unwrap_or(format!("Output {}", o));
+            dot.push_str(&format!(
+                "    o{} [label=\"{}\", shape=doublecircle];\n",
+                o, label
+            ));
+        }
+
+        for row in 0..self.candidate.nrows() {
+            // The hidden state comes from the previous tick, don't let those edges decide the layout.
+            let (from, extra) = if row == 0 {
+                ("bias".to_string(), "")
+            } else if row <= INPUT_LAYER_SIZE {
+                (format!("i{}", row - 1), "")
+            } else {
+                (
+                    format!("h{}", row - 1 - INPUT_LAYER_SIZE),
+                    ", constraint=false",
+                )
+            };
+
+            for h in 0..self.candidate.ncols() {
+                dot.push_str(&format!(
+                    "    {} -> h{} [{}{}];\n",
+                    from,
+                    h,
+                    get_edge_attributes(self.candidate[(row, h)], true),
+                    extra
+                ));
+            }
+        }
+        for row in 0..self.readout.nrows() {
+            let from = if row == 0 {
+                "bias".to_string()
+            } else {
+                format!("h{}", row - 1)
+            };
+
+            for o in 0..self.readout.ncols() {
+                dot.push_str(&format!(
+                    "    {} -> o{} [{}];\n",
+                    from,
+                    o,
+                    get_edge_attributes(self.readout[(row, o)], true)
+                ));
+            }
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Every weight matrix as a list of rows, the first row belongs to the bias node.
+    fn to_json(&self) -> serde_json::Value {
+        let rows = |matrix: &DMatrix<FPN>| -> Vec<Vec<FPN>> {
+            (0..matrix.nrows())
+                .map(|y| (0..matrix.ncols()).map(|x| matrix[(y, x)]).collect())
+                .collect()
+        };
+
+        json!({
+            "type": "recurrent",
+            "hidden_size": self.get_hidden_size(),
+            "mutability": self.mutability,
+            "hidden_state": self.hidden.as_slice(),
+            "update_gate": rows(&self.update_gate),
+            "reset_gate": rows(&self.reset_gate),
+            "candidate": rows(&self.candidate),
+            "readout": rows(&self.readout),
+        })
+    }
+}
+
+/// Puts `b` after `a`.
+fn concat(a: &RowDVector<FPN>, b: &RowDVector<FPN>) -> RowDVector<FPN> {
+    RowDVector::from_iterator(a.len() + b.len(), a.iter().chain(b.iter()).cloned())
+}
+
+fn sigmoid(vector: &mut RowDVector<FPN>) {
+    for v in vector.iter_mut() {
+        *v = 1.0 / (1.0 + (-*v).exp());
+    }
+}
+
+fn tanh(vector: &mut RowDVector<FPN>) {
+    for v in vector.iter_mut() {
+        *v = v.tanh();
+    }
+}
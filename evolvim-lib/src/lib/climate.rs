@@ -1,3 +1,4 @@
+use crate::constants::*;
 use std::f64::consts::PI;
 
 #[derive(Serialize, Deserialize)]
@@ -5,6 +6,13 @@ pub struct Climate {
     temperature: f64,
     min_temperature: f64,
     max_temperature: f64,
+    /// The part of the year after which the temperature crosses 0 for the first time, `None` if it never does.
+    zero_temperature_phase: Option<f64>,
+
+    sea_level: f64,
+    sea_level_settings: SeaLevel,
+    /// Temporary change to the sea level, e.g. by a flood.
+    sea_level_offset: f64,
 }
 
 impl Climate {
@@ -23,17 +31,61 @@ impl Climate {
             + (temp_range / PI / 4.0) * ((PI * 2.0 * last_updated).sin() - (PI * 2.0 * time).sin());
     }
 
+    /// Returns the first moment after `time` at which the temperature crosses 0, infinity if it never does.
+    ///
+    /// Food only grows while the temperature is positive and only decays while it is negative.
+    pub fn get_next_zero_temperature(&self, time: f64) -> f64 {
+        let first = match self.zero_temperature_phase {
+            Some(phase) => phase,
+            None => return std::f64::INFINITY,
+        };
+
+        // It crosses 0 twice a year, symmetrically around the coldest moment.
+        let year = time.floor();
+        [year + first, year + 1.0 - first, year + 1.0 + first]
+            .iter()
+            .cloned()
+            .find(|t| *t > time)
+            .unwrap()
+    }
+
     pub fn update(&mut self, time: f64) {
         self.temperature = self.get_growth_rate(time);
+        self.sea_level =
+            self.sea_level_settings.get_level(time, self.temperature) + self.sea_level_offset;
     }
 
     pub fn new(min: f64, max: f64) -> Self {
+        // The temperature is `m - half_range * cos(2 PI t)`, see `get_growth_rate`.
+        let half_range = (max - min) * 0.5;
+        let m = min + half_range;
+        let zero_temperature_phase = if half_range > 0.0 && m.abs() < half_range {
+            Some((m / half_range).acos() / (2.0 * PI))
+        } else {
+            None
+        };
+
         Climate {
             temperature: 0.0,
             min_temperature: min,
             max_temperature: max,
+            zero_temperature_phase,
+
+            sea_level: DEFAULT_SEA_LEVEL,
+            sea_level_settings: SeaLevel::default(),
+            sea_level_offset: 0.0,
         }
     }
+
+    /// Changes the way the sea level behaves, takes effect on the next call to `update`.
+    pub fn set_sea_level_settings(&mut self, settings: SeaLevel) {
+        self.sea_level_settings = settings;
+    }
+
+    /// Raises (or lowers) the sea level on top of `SeaLevel`, takes effect on the next call to `update`.
+    pub fn set_sea_level_offset(&mut self, offset: f64) {
+        self.sea_level_offset = offset;
+    }
 }
 
 // All functions simply returning properties.
@@ -41,4 +93,71 @@ impl Climate {
     pub fn get_temperature(&self) -> f64 {
         return self.temperature;
     }
+
+    /// Returns the sea level, every `Tile` with a lower elevation is water.
+    pub fn get_sea_level(&self) -> f64 {
+        return self.sea_level;
+    }
+
+    pub fn get_sea_level_settings(&self) -> &SeaLevel {
+        &self.sea_level_settings
+    }
+}
+
+/// Determines the height of the sea over time.
+///
+/// The sea level is the sum of a base level, a part following the temperature, the tides
+/// and a user-defined schedule.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SeaLevel {
+    pub base: f64,
+    /// How much the sea rises for every unit of temperature (see `Climate::get_temperature`).
+    pub temperature_factor: f64,
+    pub tide_amplitude: f64,
+    pub tides_per_year: f64,
+    /// Extra height of the sea at the given years, linearly interpolated in between.
+    ///
+    /// Should be sorted by year.
+    pub schedule: Vec<(f64, f64)>,
+}
+
+impl Default for SeaLevel {
+    fn default() -> Self {
+        SeaLevel {
+            base: DEFAULT_SEA_LEVEL,
+            temperature_factor: DEFAULT_SEA_LEVEL_TEMPERATURE_FACTOR,
+            tide_amplitude: 0.0,
+            tides_per_year: 0.0,
+            schedule: Vec::new(),
+        }
+    }
+}
+
+impl SeaLevel {
+    pub fn get_level(&self, time: f64, temperature: f64) -> f64 {
+        self.base
+            + self.temperature_factor * temperature
+            + self.tide_amplitude * (2.0 * PI * self.tides_per_year * time).sin()
+            + self.get_scheduled_offset(time)
+    }
+
+    fn get_scheduled_offset(&self, time: f64) -> f64 {
+        if self.schedule.is_empty() {
+            return 0.0;
+        }
+
+        match self.schedule.iter().position(|(year, _)| *year > time) {
+            // Before the first scheduled moment
+            Some(0) => self.schedule[0].1,
+            // Somewhere in between
+            Some(i) => {
+                let (year_a, level_a) = self.schedule[i - 1];
+                let (year_b, level_b) = self.schedule[i];
+
+                level_a + (level_b - level_a) * (time - year_a) / (year_b - year_a)
+            }
+            // After the last scheduled moment
+            None => self.schedule.last().unwrap().1,
+        }
+    }
 }
@@ -48,6 +48,17 @@ pub const DEFAULT_MAX_TEMP: f64 = 0.7;
 /// Used for terrain generation.
 pub const DEFAULT_NOISE_STEP_SIZE: f64 = 0.1;
 
+/// Every `Tile` with a lower elevation is water.
+pub const DEFAULT_SEA_LEVEL: f64 = 0.0;
+
+/// How much the sea rises for every unit of temperature, a positive value makes coasts flood in summer.
+///
+/// The sea doesn't follow the temperature by default, see `SeaLevel::temperature_factor`.
+pub const DEFAULT_SEA_LEVEL_TEMPERATURE_FACTOR: f64 = 0.0;
+
+/// The sea level has to change at least this much before `Terrain` floods or dries out tiles.
+pub const SEA_LEVEL_UPDATE_THRESHOLD: f64 = 0.01;
+
 // ************************* //
 // ******** DRAWING ******** //
 // ************************* //
@@ -63,6 +74,9 @@ pub const COLOR_FERTILE: [f32; 3] = [0., 0., 0.2];
 
 pub const COLOR_BLACK: [f32; 3] = [0., 1., 0.];
 
+/// [Hue, Saturation, Brightness], water with the maximum amount of algae.
+pub const COLOR_ALGAE: [f32; 3] = [0., 1., 0.35];
+
 // ******************** //
 // ******** UI ******** //
 // ******************** //
@@ -81,6 +95,8 @@ pub const ACCELERATION_BACK_ENERGY: f64 = 0.24;
 pub const TURN_ENERGY: f64 = 0.06;
 
 pub const METABOLISM_ENERGY: f64 = 0.004;
+/// The default energy a creature loses every year for every unit of `NeuralNet::metabolic_cost` of its brain.
+pub const DEFAULT_BRAIN_COST: f64 = 0.0002;
 
 pub const SWIM_ENERGY: f64 = 0.008;
 pub const EAT_ENERGY: f64 = 0.05;
@@ -91,14 +107,48 @@ pub const INJURED_ENERGY: f64 = 0.25;
 // ******* FOOD ******** //
 // ********************* //
 
+/// Growth and decay rate of `Nutrient::Plant`.
 pub const FOOD_GROWTH_RATE: f64 = 1.0;
+/// Maximum level of `Nutrient::Plant`.
 pub const MAX_GROWTH_LEVEL: f64 = 3.0;
 pub const FOOD_SENSITIVITY: f64 = 0.3;
 
+pub const SEED_GROWTH_RATE: f64 = 0.3;
+pub const SEED_DECAY_RATE: f64 = 0.1;
+pub const MAX_SEED_LEVEL: f64 = 1.0;
+
+pub const ALGAE_GROWTH_RATE: f64 = 0.8;
+pub const ALGAE_DECAY_RATE: f64 = 1.0;
+pub const MAX_ALGAE_LEVEL: f64 = 2.0;
+
 pub const EAT_WHILE_MOVING_INEFFICIENCY_MULTIPLIER: f64 = 2.0;
 pub const EAT_SPEED: f64 = 0.5;
 
+// ********************* //
+// ******* DIET ******** //
+// ********************* //
+
+/// The share of every `Nutrient` (Plant, Seed, Algae) in a perfectly balanced diet, should add up to 1.
+pub const DIET_REQUIREMENT: [f64; 3] = [0.5, 0.3, 0.2];
+/// The fraction of energy a creature still gets out of its food when it only eats a single `Nutrient`.
+pub const DIET_BASE_EFFICIENCY: f64 = 0.4;
+/// How long (in years) a creature remembers what it ate.
+pub const DIET_MEMORY: f64 = 0.05;
+
 // ********************** //
 // **** REPRODUCTION **** //
 // ********************** //
 pub const BABY_SIZE: f64 = SAFE_SIZE + 0.1;
+
+// ********************** //
+// ******** BODY ******** //
+// ********************** //
+
+/// The top speed of a body with the default `BodyGenome`, it has none just like bodies before they could evolve one.
+pub const DEFAULT_MAX_SPEED: f64 = std::f64::INFINITY;
+/// The highest finite top speed, bodies with a higher `max_speed` have no top speed at all.
+///
+/// Accelerating costs `max_speed / MAX_FINITE_SPEED` times the usual energy, but never more than that.
+pub const MAX_FINITE_SPEED: f64 = 2.0;
+/// How much the traits in the `BodyGenome` of a child differ from the average of its parents.
+pub const BODY_GENE_CHANGE: f64 = 0.05;
@@ -0,0 +1,361 @@
+//! Contains disasters that can strike a world, useful for studying how well a population recovers.
+//!
+//! Disasters either happen at a fixed year or at random with a given chance per year, see `DisasterScheduler`.
+//! A scheduler can be loaded from a JSON file, for example:
+//!
+//! ```json
+//! {
+//!     "scheduled": [
+//!         { "year": 10.0, "disaster": { "Meteor": { "center": [50.0, 50.0], "radius": 8.0 } } },
+//!         { "year": 20.0, "disaster": { "Flood": { "rise": 0.3, "duration": 1.0 } } }
+//!     ],
+//!     "random": [
+//!         { "chance_per_year": 0.1, "disaster": { "Drought": { "center": null, "radius": 15.0, "duration": 0.5 } } },
+//!         { "chance_per_year": 0.02, "disaster": { "Plague": { "mortality": 0.3 } } }
+//!     ]
+//! }
+//! ```
+//!
+//! Every disaster is recorded in the `EventLog` of the `Board`.
+
+extern crate rand;
+extern crate serde_json;
+
+use crate::board::{Board, BoardCoordinate, BoardPreciseCoordinate, BoardSize};
+use crate::brain::NeuralNet;
+use crate::event_log::EventKind;
+use crate::terrain::TerrainEdit;
+use std::fmt;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum Disaster {
+    /// Nothing grows within `radius` of `center` for `duration` years.
+    ///
+    /// A random center is picked when it is `None`.
+    Drought {
+        center: Option<(f64, f64)>,
+        radius: f64,
+        duration: f64,
+    },
+    /// Kills every creature within `radius` of `center` and removes all food there.
+    ///
+    /// A random center is picked when it is `None`.
+    Meteor {
+        center: Option<(f64, f64)>,
+        radius: f64,
+    },
+    /// Every creature dies with a chance of `mortality`.
+    Plague { mortality: f64 },
+    /// The sea rises by `rise` for `duration` years.
+    Flood { rise: f64, duration: f64 },
+}
+
+impl Disaster {
+    /// Returns how long this disaster lasts, `None` for disasters that are over instantly.
+    pub fn get_duration(&self) -> Option<f64> {
+        match self {
+            Disaster::Drought { duration, .. } | Disaster::Flood { duration, .. } => {
+                Some(*duration)
+            }
+            Disaster::Meteor { .. } | Disaster::Plague { .. } => None,
+        }
+    }
+
+    /// Picks a random center on the board for disasters without one.
+    fn place(&mut self, board_size: BoardSize) {
+        use self::rand::Rng;
+
+        match self {
+            Disaster::Drought { center, .. } | Disaster::Meteor { center, .. } => {
+                if center.is_none() {
+                    let mut rng = rand::thread_rng();
+                    let x = rng.gen::<f64>() * board_size.0 as f64;
+                    let y = rng.gen::<f64>() * board_size.1 as f64;
+
+                    *center = Some((x, y));
+                }
+            }
+            Disaster::Plague { .. } | Disaster::Flood { .. } => {}
+        }
+    }
+}
+
+impl fmt::Display for Disaster {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Disaster::Drought {
+                center: Some((x, y)),
+                radius,
+                duration,
+            } => write!(
+                f,
+                "drought at ({:.1}, {:.1}) with radius {:.1} for {:.2} years",
+                x, y, radius, duration
+            ),
+            Disaster::Drought {
+                center: None,
+                radius,
+                duration,
+            } => write!(
+                f,
+                "drought with radius {:.1} for {:.2} years",
+                radius, duration
+            ),
+            Disaster::Meteor {
+                center: Some((x, y)),
+                radius,
+            } => write!(
+                f,
+                "meteor at ({:.1}, {:.1}) with radius {:.1}",
+                x, y, radius
+            ),
+            Disaster::Meteor {
+                center: None,
+                radius,
+            } => write!(f, "meteor with radius {:.1}", radius),
+            Disaster::Plague { mortality } => {
+                write!(f, "plague with mortality {:.0}%", mortality * 100.0)
+            }
+            Disaster::Flood { rise, duration } => write!(
+                f,
+                "flood raising the sea by {:.2} for {:.2} years",
+                rise, duration
+            ),
+        }
+    }
+}
+
+/// A disaster that happens at a fixed year.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ScheduledDisaster {
+    pub year: f64,
+    pub disaster: Disaster,
+}
+
+/// A disaster that can happen at any moment.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RandomDisaster {
+    pub chance_per_year: f64,
+    pub disaster: Disaster,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct ActiveDisaster {
+    disaster: Disaster,
+    end: f64,
+    /// The growth multiplier every tile of a drought had before any drought struck it, restored when it ends.
+    previous_growth: Vec<(BoardCoordinate, f64)>,
+}
+
+/// Decides when disasters strike and keeps track of the ones that are still going on.
+#[derive(Default, Serialize, Deserialize)]
+pub struct DisasterScheduler {
+    #[serde(default)]
+    pub scheduled: Vec<ScheduledDisaster>,
+    #[serde(default)]
+    pub random: Vec<RandomDisaster>,
+    #[serde(default)]
+    active: Vec<ActiveDisaster>,
+    /// The year of the last update, scheduled disasters after it and up to the current year start.
+    /// `None` until the first update, which starts the disasters of its time step including its beginning.
+    #[serde(default)]
+    last_update: Option<f64>,
+}
+
+impl DisasterScheduler {
+    /// Loads a `DisasterScheduler` from a JSON file, see the module documentation for an example.
+    pub fn load_from<P: AsRef<std::path::Path>>(
+        path: P,
+    ) -> Result<DisasterScheduler, Box<dyn std::error::Error>> {
+        let file = std::fs::File::open(path)?;
+
+        Ok(serde_json::from_reader(file)?)
+    }
+
+    /// Returns every disaster that is currently going on.
+    pub fn get_active(&self) -> impl Iterator<Item = &Disaster> {
+        self.active.iter().map(|a| &a.disaster)
+    }
+
+    /// Returns all disasters starting since the last update, which lasted `time_step` and ended at `time`.
+    ///
+    /// The bounds are the exact years of the updates, so a disaster on the border of two time steps starts exactly once.
+    fn get_starting(&mut self, time: f64, time_step: f64) -> Vec<Disaster> {
+        use self::rand::Rng;
+
+        let mut rng = rand::thread_rng();
+        let last_update = self.last_update.replace(time);
+        let scheduled = self
+            .scheduled
+            .iter()
+            .filter(|s| match last_update {
+                Some(last) => s.year > last && s.year <= time,
+                None => s.year >= time - time_step && s.year <= time,
+            })
+            .map(|s| s.disaster.clone());
+        let random = self
+            .random
+            .iter()
+            .filter(|r| rng.gen::<f64>() < r.chance_per_year * time_step)
+            .map(|r| r.disaster.clone());
+
+        scheduled.chain(random).collect()
+    }
+
+    /// Returns how much all active floods together raise the sea.
+    fn get_sea_level_rise(&self) -> f64 {
+        self.get_active()
+            .map(|d| match d {
+                Disaster::Flood { rise, .. } => *rise,
+                _ => 0.0,
+            })
+            .sum()
+    }
+}
+
+impl<B: NeuralNet> Board<B> {
+    /// Starts and ends disasters, called once every `update`.
+    pub fn update_disasters(&mut self, time_step: f64) {
+        let time = self.get_time();
+
+        let (ended, active) = self.disasters.active.drain(..).partition(|a| a.end <= time);
+        self.disasters.active = active;
+        for a in ended {
+            self.end_disaster(a);
+        }
+
+        for d in self.disasters.get_starting(time, time_step) {
+            self.trigger_disaster(d);
+        }
+
+        self.climate
+            .set_sea_level_offset(self.disasters.get_sea_level_rise());
+    }
+
+    /// Lets `disaster` strike right now.
+    pub fn trigger_disaster(&mut self, mut disaster: Disaster) {
+        use self::rand::Rng;
+
+        let time = self.get_time();
+        disaster.place(self.get_board_size());
+        let mut previous_growth = Vec::new();
+
+        match &disaster {
+            Disaster::Drought {
+                center: Some((x, y)),
+                radius,
+                ..
+            } => {
+                let change = self.terrain.paint(
+                    BoardPreciseCoordinate(*x, *y),
+                    *radius,
+                    TerrainEdit::GrowthMultiplier(0.0),
+                    time,
+                    &self.climate,
+                );
+
+                // Tiles already in a drought remember what they had before that one.
+                let in_drought: std::collections::HashMap<BoardCoordinate, f64> = self
+                    .disasters
+                    .active
+                    .iter()
+                    .flat_map(|a| a.previous_growth.iter().cloned())
+                    .collect();
+                previous_growth = change
+                    .get_tiles()
+                    .map(|(pos, tile)| {
+                        let growth = in_drought
+                            .get(&pos)
+                            .cloned()
+                            .unwrap_or_else(|| tile.get_growth_multiplier());
+                        (pos, growth)
+                    })
+                    .collect();
+            }
+            Disaster::Meteor {
+                center: Some((x, y)),
+                radius,
+            } => {
+                for c in &self.creatures {
+                    let mut c = c.borrow_mut();
+
+                    if crate::softbody::distance(*x, *y, c.get_px(), c.get_py()) < *radius {
+                        let energy = c.get_energy();
+                        c.lose_energy(energy);
+                    }
+                }
+
+                self.terrain.paint(
+                    BoardPreciseCoordinate(*x, *y),
+                    *radius,
+                    TerrainEdit::ClearFood,
+                    time,
+                    &self.climate,
+                );
+            }
+            Disaster::Plague { mortality } => {
+                let mut rng = rand::thread_rng();
+
+                for c in &self.creatures {
+                    if rng.gen::<f64>() < *mortality {
+                        let mut c = c.borrow_mut();
+                        let energy = c.get_energy();
+                        c.lose_energy(energy);
+                    }
+                }
+            }
+            // Floods raise the sea for as long as they are active, see `get_sea_level_rise`.
+            // Droughts and meteors without a center don't exist anymore because of `place` above.
+            _ => {}
+        }
+
+        if let Some(duration) = disaster.get_duration() {
+            self.disasters.active.push(ActiveDisaster {
+                disaster: disaster.clone(),
+                end: time + duration,
+                previous_growth,
+            });
+        }
+
+        self.event_log
+            .record(time, EventKind::DisasterStarted(disaster));
+    }
+
+    fn end_disaster(&mut self, active: ActiveDisaster) {
+        let time = self.get_time();
+        let disaster = active.disaster;
+
+        if let Disaster::Drought { .. } = &disaster {
+            // Restores what was there before, like growth multipliers painted with a brush.
+            for (pos, growth) in active.previous_growth {
+                self.terrain.edit(
+                    pos,
+                    TerrainEdit::GrowthMultiplier(growth),
+                    time,
+                    &self.climate,
+                );
+            }
+
+            // Overlapping droughts that are still going on should keep their tiles dry.
+            for d in &self.disasters.active {
+                if let Disaster::Drought {
+                    center: Some((x, y)),
+                    radius,
+                    ..
+                } = &d.disaster
+                {
+                    self.terrain.paint(
+                        BoardPreciseCoordinate(*x, *y),
+                        *radius,
+                        TerrainEdit::GrowthMultiplier(0.0),
+                        time,
+                        &self.climate,
+                    );
+                }
+            }
+        }
+
+        self.event_log
+            .record(time, EventKind::DisasterEnded(disaster));
+    }
+}
@@ -0,0 +1,55 @@
+//! Keeps a record of everything noteworthy that happened in a world.
+//!
+//! The `EventLog` is saved together with the `Board` so the history of a world survives saving and loading.
+
+use crate::disaster::Disaster;
+use std::fmt;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum EventKind {
+    /// A disaster struck the world.
+    DisasterStarted(Disaster),
+    /// A disaster with a duration (e.g. a drought) is over.
+    DisasterEnded(Disaster),
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Event {
+    pub time: f64,
+    pub kind: EventKind,
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "year {:.2}: ", self.time)?;
+
+        match &self.kind {
+            EventKind::DisasterStarted(d) => write!(f, "{} started", d),
+            EventKind::DisasterEnded(d) => write!(f, "{} ended", d),
+        }
+    }
+}
+
+#[derive(Default, Serialize, Deserialize)]
+pub struct EventLog {
+    events: Vec<Event>,
+}
+
+impl EventLog {
+    pub fn record(&mut self, time: f64, kind: EventKind) {
+        self.events.push(Event { time, kind });
+    }
+
+    /// Returns all events, from old to new.
+    pub fn get_events(&self) -> &[Event] {
+        &self.events
+    }
+
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+}
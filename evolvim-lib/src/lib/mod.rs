@@ -15,6 +15,8 @@ pub mod board;
 pub mod brain;
 pub mod climate;
 pub mod constants;
+pub mod disaster;
+pub mod event_log;
 pub mod neat;
 pub mod sbip;
 pub mod softbody;
@@ -23,7 +25,7 @@ pub mod serde_structs;
 
 pub use self::board::*;
 pub use self::brain::*;
-pub use self::climate::Climate;
+pub use self::climate::{Climate, SeaLevel};
 pub use self::sbip::*;
 pub use self::softbody::*;
 pub use self::terrain::*;
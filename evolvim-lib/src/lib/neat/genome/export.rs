@@ -0,0 +1,58 @@
+extern crate serde_json;
+
+use super::{Genome, NodeType};
+use crate::brain::export::{get_edge_attributes, write_dot_header};
+use crate::brain::ExportBrain;
+
+impl ExportBrain for Genome {
+    /// Every gene becomes a node or an edge, disabled links are dashed.
+    /// Sensors are on the left and outputs on the right.
+    fn to_dot(&self) -> String {
+        let mut dot = String::new();
+        write_dot_header(&mut dot, "genome");
+
+        let mut sensors = Vec::new();
+        let mut outputs = Vec::new();
+        for n in &self.node_genome {
+            let (kind, shape) = match &n.node_type {
+                NodeType::Sensor(input) => {
+                    sensors.push(n.id);
+                    (format!("\\n{}", input), "box")
+                }
+                NodeType::Output(output) => {
+                    outputs.push(n.id);
+                    (format!("\\n{}", output), "doublecircle")
+                }
+                NodeType::Hidden => (String::new(), "circle"),
+            };
+
+            dot.push_str(&format!(
+                "    n{} [label=\"{}{}\\n{}\", shape={}];\n",
+                n.id, n.id, kind, n.activation, shape
+            ));
+        }
+
+        let rank =
+            |ids: &[usize]| -> String { ids.iter().map(|id| format!(" n{};", id)).collect() };
+        dot.push_str(&format!("    {{ rank=source;{} }}\n", rank(&sensors)));
+        dot.push_str(&format!("    {{ rank=sink;{} }}\n", rank(&outputs)));
+
+        for c in &self.connection_genome {
+            dot.push_str(&format!(
+                "    n{} -> n{} [{}, label=\"{}\"];\n",
+                c.from,
+                c.to,
+                get_edge_attributes(c.weight, c.enabled),
+                c.innovation_number
+            ));
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// The genome exactly as it is saved: its node genes, connection genes, mutation rate and plasticity.
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::to_value(self).expect("A genome can always be turned into JSON.")
+    }
+}
@@ -1,3 +1,5 @@
+use crate::brain::Activation;
+
 pub type Id = usize;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -11,6 +13,7 @@ pub enum NodeType {
 pub struct NodeGene {
     pub node_type: NodeType,
     pub id: Id,
+    pub activation: Activation,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -23,7 +26,41 @@ pub struct ConnectionGene {
     pub innovation_number: usize,
 }
 
-impl NodeGene {}
+impl NodeType {
+    pub fn is_sensor(&self) -> bool {
+        match self {
+            NodeType::Sensor(_) => true,
+            NodeType::Hidden | NodeType::Output(_) => false,
+        }
+    }
+
+    pub fn is_hidden(&self) -> bool {
+        match self {
+            NodeType::Hidden => true,
+            NodeType::Sensor(_) | NodeType::Output(_) => false,
+        }
+    }
+
+    /// Sensors and outputs pass their values on unchanged, hidden nodes start out with a sigmoid function.
+    pub fn get_default_activation(&self) -> Activation {
+        match self {
+            NodeType::Hidden => Activation::Sigmoid,
+            NodeType::Sensor(_) | NodeType::Output(_) => Activation::Identity,
+        }
+    }
+}
+
+impl NodeGene {
+    pub fn new(node_type: NodeType, id: Id) -> Self {
+        let activation = node_type.get_default_activation();
+
+        NodeGene {
+            node_type,
+            id,
+            activation,
+        }
+    }
+}
 
 impl ConnectionGene {
     pub fn disable_and_info(&mut self) -> (Id, Id) {
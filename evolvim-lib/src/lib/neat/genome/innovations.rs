@@ -0,0 +1,205 @@
+//! Contains `InnovationRegistry`, which hands out innovation numbers and node ids for structural mutations.
+//!
+//! When two creatures independently add the same link or split the same link they get the same numbers,
+//! this way their genomes still line up during recombination and speciation.
+
+use super::gene::Id;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// The first innovation number not used by `Genome::new_fully_linked`.
+const FIRST_INNOVATION_NUMBER: usize = super::AMOUNT_INPUT * super::AMOUNT_OUTPUT + 1;
+/// The first node id not used by `Genome::new_fully_linked`.
+const FIRST_NODE_ID: Id = super::AMOUNT_INPUT + super::AMOUNT_OUTPUT + 1;
+
+/// Returns the id `Genome::new_fully_linked_with` gives its sensor number `input`, counted from 0.
+pub(super) fn get_initial_sensor_id(input: usize) -> Id {
+    input + 1
+}
+
+/// Returns the id `Genome::new_fully_linked_with` gives its output number `output`, counted from 0.
+pub(super) fn get_initial_output_id(output: usize) -> Id {
+    super::AMOUNT_INPUT + output + 1
+}
+
+/// Returns the innovation number `Genome::new_fully_linked_with` gives the link from sensor `input` to output `output`.
+pub(super) fn get_initial_innovation(input: usize, output: usize) -> usize {
+    output * super::AMOUNT_INPUT + input + 1
+}
+
+/// Old entries are removed once every this many lookups.
+const PRUNE_INTERVAL: u64 = 10_000;
+/// Entries that haven't been looked up for this many lookups are removed when pruning.
+const MAX_ENTRY_AGE: u64 = 50_000;
+
+#[derive(Serialize, Deserialize)]
+struct Entry<T> {
+    value: T,
+    last_used: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Innovations {
+    next_innovation_number: usize,
+    next_node_id: Id,
+    /// Innovation numbers of links, by the nodes they connect.
+    connections: HashMap<(Id, Id), Entry<usize>>,
+    /// The nodes that were put in the middle of links, by the innovation number of that link.
+    splits: HashMap<usize, Entry<Id>>,
+    /// The amount of lookups so far, used to decide which entries are old.
+    lookups: u64,
+}
+
+/// Knows the links of `Genome::new_fully_linked`, so adding one of them again gives the same number.
+impl Default for Innovations {
+    fn default() -> Self {
+        let mut connections = HashMap::new();
+        for output in 0..super::AMOUNT_OUTPUT {
+            for input in 0..super::AMOUNT_INPUT {
+                connections.insert(
+                    (get_initial_sensor_id(input), get_initial_output_id(output)),
+                    Entry {
+                        value: get_initial_innovation(input, output),
+                        last_used: 0,
+                    },
+                );
+            }
+        }
+
+        Innovations {
+            next_innovation_number: FIRST_INNOVATION_NUMBER,
+            next_node_id: FIRST_NODE_ID,
+            connections,
+            splits: HashMap::new(),
+            lookups: 0,
+        }
+    }
+}
+
+impl Innovations {
+    fn tick(&mut self) -> u64 {
+        self.lookups += 1;
+
+        if self.lookups % PRUNE_INTERVAL == 0 {
+            self.prune();
+        }
+
+        self.lookups
+    }
+
+    /// Forgets entries that weren't used recently, the counters keep going so no numbers are ever handed out twice.
+    ///
+    /// The links of `Genome::new_fully_linked` are never forgotten.
+    fn prune(&mut self) {
+        let oldest = self.lookups.saturating_sub(MAX_ENTRY_AGE);
+
+        self.connections
+            .retain(|_, e| e.value < FIRST_INNOVATION_NUMBER || e.last_used >= oldest);
+        self.splits.retain(|_, e| e.last_used >= oldest);
+    }
+
+    fn new_innovation_number(&mut self) -> usize {
+        let number = self.next_innovation_number;
+        self.next_innovation_number += 1;
+
+        number
+    }
+
+    fn new_node_id(&mut self) -> Id {
+        let id = self.next_node_id;
+        self.next_node_id += 1;
+
+        id
+    }
+}
+
+/// Remembers which structural mutations happened recently, owned by the `Board`.
+///
+/// It can be shared between threads, all methods lock it internally.
+#[derive(Default)]
+pub struct InnovationRegistry {
+    inner: Mutex<Innovations>,
+}
+
+impl InnovationRegistry {
+    /// Returns the innovation number of a link from `from` to `to`, reusing the number of an identical link if there was one.
+    pub fn get_connection_innovation(&self, from: Id, to: Id) -> usize {
+        let mut inner = self.inner.lock().unwrap();
+        let now = inner.tick();
+
+        if let Some(entry) = inner.connections.get_mut(&(from, to)) {
+            entry.last_used = now;
+            return entry.value;
+        }
+
+        let value = inner.new_innovation_number();
+        inner.connections.insert(
+            (from, to),
+            Entry {
+                value,
+                last_used: now,
+            },
+        );
+
+        value
+    }
+
+    /// Returns the id of the node that goes in the middle of the link with `innovation_number`,
+    /// reusing the id if that link was split before.
+    pub fn get_split_node(&self, innovation_number: usize) -> Id {
+        let mut inner = self.inner.lock().unwrap();
+        let now = inner.tick();
+
+        if let Some(entry) = inner.splits.get_mut(&innovation_number) {
+            entry.last_used = now;
+            return entry.value;
+        }
+
+        let value = inner.new_node_id();
+        inner.splits.insert(
+            innovation_number,
+            Entry {
+                value,
+                last_used: now,
+            },
+        );
+
+        value
+    }
+
+    /// Returns a node id that has never been used before.
+    pub fn get_new_node_id(&self) -> Id {
+        self.inner.lock().unwrap().new_node_id()
+    }
+
+    /// Returns the amount of mutations that are currently remembered, the links of `Genome::new_fully_linked` don't count.
+    pub fn len(&self) -> usize {
+        let inner = self.inner.lock().unwrap();
+        let connections = inner
+            .connections
+            .values()
+            .filter(|e| e.value >= FIRST_INNOVATION_NUMBER)
+            .count();
+
+        connections + inner.splits.len()
+    }
+
+    /// Returns `true` if no mutations are remembered.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl serde::Serialize for InnovationRegistry {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.inner.lock().unwrap().serialize(serializer)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for InnovationRegistry {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(InnovationRegistry {
+            inner: Mutex::new(Innovations::deserialize(deserializer)?),
+        })
+    }
+}
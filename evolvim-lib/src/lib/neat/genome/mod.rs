@@ -1,39 +1,32 @@
 extern crate rand;
 
+mod export;
 mod gene;
+mod innovations;
 mod mutation;
 mod recombination;
-// mod innovations;
 mod speciation;
 mod utils;
 
 use self::gene::{ConnectionGene, NodeGene};
 pub use self::gene::{Id, NodeType};
+pub use self::innovations::InnovationRegistry;
+pub use self::mutation::MutationConfig;
+pub use self::speciation::DistanceCoefficients;
+use crate::brain::Plasticity;
 use rand::Rng;
 
 const AMOUNT_INPUT: usize = 6;
-const AMOUNT_OUTPUT: usize = 4;
-static mut INNOVATION_NUMBER: usize = AMOUNT_INPUT * AMOUNT_OUTPUT;
-static mut NODE_NUMBER: Id = AMOUNT_INPUT + AMOUNT_OUTPUT;
-
-fn get_innovation_number() -> usize {
-    unsafe {
-        INNOVATION_NUMBER += 1;
-        return INNOVATION_NUMBER;
-    }
-}
-
-pub fn get_next_node_id() -> Id {
-    unsafe {
-        NODE_NUMBER += 1;
-        return NODE_NUMBER;
-    }
-}
+const AMOUNT_OUTPUT: usize = 7;
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Genome {
     node_genome: Vec<NodeGene>,
     connection_genome: Vec<ConnectionGene>,
+    /// The average amount of mutations every call to `mutate`.
+    mutation_rate: f64,
+    /// How the weights of the phenotype change during its life.
+    plasticity: Plasticity,
 }
 
 impl Genome {
@@ -46,6 +39,21 @@ impl Genome {
         &self.connection_genome
     }
 
+    /// Returns the average amount of mutations every call to `mutate`, this evolves along with the genome.
+    pub fn get_mutation_rate(&self) -> f64 {
+        self.mutation_rate
+    }
+
+    /// Returns the rule the phenotype learns with, this evolves along with the genome.
+    pub fn get_plasticity(&self) -> &Plasticity {
+        &self.plasticity
+    }
+
+    /// Changes the rule the phenotype learns with.
+    pub fn set_plasticity(&mut self, plasticity: Plasticity) {
+        self.plasticity = plasticity;
+    }
+
     fn get_random_node_id(&self) -> Id {
         self.node_genome[self.get_random_node_place()].id
     }
@@ -54,8 +62,25 @@ impl Genome {
         rand::thread_rng().gen_range(0, self.node_genome.len())
     }
 
-    fn get_random_connection_place(&self) -> usize {
-        rand::thread_rng().gen_range(0, self.connection_genome.len())
+    /// Returns `None` if this genome has no links left.
+    fn get_random_connection_place(&self) -> Option<usize> {
+        if self.connection_genome.is_empty() {
+            None
+        } else {
+            Some(rand::thread_rng().gen_range(0, self.connection_genome.len()))
+        }
+    }
+
+    fn get_random_hidden_node_place(&self) -> Option<usize> {
+        let hidden: Vec<usize> = (0..self.node_genome.len())
+            .filter(|&i| self.node_genome[i].node_type.is_hidden())
+            .collect();
+
+        if hidden.is_empty() {
+            None
+        } else {
+            Some(hidden[rand::thread_rng().gen_range(0, hidden.len())])
+        }
     }
 
     fn get_random_weight() -> f64 {
@@ -67,31 +92,64 @@ impl Genome {
     }
 
     fn add_node(&mut self, node_type: NodeType, id: Id) {
-        self.node_genome.push(NodeGene { node_type, id });
+        self.node_genome.push(NodeGene::new(node_type, id));
     }
 
-    fn add_connection(&mut self, from: Id, to: Id, weight: f64) {
-        self.connection_genome.push(ConnectionGene {
-            from,
-            to,
-            weight,
+    /// Adds a link from `from` to `to` unless this genome already has one, returns whether it was added.
+    ///
+    /// A link the `InnovationRegistry` forgot gets a new innovation number,
+    /// so existing links are found by their nodes instead of their number.
+    /// The connection genome stays sorted by innovation number, recombination relies on that.
+    fn add_connection(
+        &mut self,
+        from: Id,
+        to: Id,
+        weight: f64,
+        registry: &InnovationRegistry,
+    ) -> bool {
+        if self.has_connection(from, to) {
+            return false;
+        }
+
+        let innovation_number = registry.get_connection_innovation(from, to);
+
+        let place = self
+            .connection_genome
+            .binary_search_by_key(&innovation_number, |c| c.innovation_number);
+        if let Err(place) = place {
+            self.connection_genome.insert(
+                place,
+                ConnectionGene {
+                    from,
+                    to,
+                    weight,
+
+                    enabled: true,
+                    innovation_number,
+                },
+            );
 
-            enabled: true,
-            innovation_number: get_innovation_number(),
-        });
+            true
+        } else {
+            false
+        }
     }
 
-    pub fn new_fully_linked() -> Self {
-        let mut genome = Genome {
-            node_genome: Vec::new(),
-            connection_genome: Vec::new(),
-        };
-        let mut node_counter = 1;
+    fn has_node(&self, id: Id) -> bool {
+        self.node_genome.iter().any(|n| n.id == id)
+    }
+
+    fn has_connection(&self, from: Id, to: Id) -> bool {
+        self.connection_genome
+            .iter()
+            .any(|c| c.from == from && c.to == to)
+    }
 
+    pub fn new_fully_linked() -> Self {
         use crate::neat::input::Eye;
         use crate::neat::input::InputType;
         const EYE: [Eye; 3] = Eye::get_all_three(0.0, 0.0);
-        let input_nodes: [InputType; 6] = [
+        let input_nodes: [InputType; AMOUNT_INPUT] = [
             InputType::Bias(1.0),
             InputType::MouthHue,
             InputType::Energy,
@@ -99,38 +157,61 @@ impl Genome {
             InputType::Eye(EYE[1]).clone(),
             InputType::Eye(EYE[2]).clone(),
         ];
-        for i in 0..AMOUNT_INPUT {
-            genome.add_node(NodeType::Sensor(input_nodes[i].clone()), node_counter);
-            node_counter += 1;
-        }
 
-        let mut con_counter = 1;
         use crate::neat::output::OutputType;
-        const OUTPUT_NODES: [NodeType; 4] = [
-            NodeType::Output(OutputType::Turning),
-            NodeType::Output(OutputType::Accelerating),
-            NodeType::Output(OutputType::MouthHue),
-            NodeType::Output(OutputType::Eating),
+        const OUTPUT_NODES: [OutputType; AMOUNT_OUTPUT] = [
+            OutputType::Turning,
+            OutputType::Accelerating,
+            OutputType::MouthHue,
+            OutputType::Eating,
+            OutputType::Fight,
+            OutputType::Birth,
+            OutputType::HelpBirth,
         ];
-        for i in 0..AMOUNT_OUTPUT {
-            genome.add_node(OUTPUT_NODES[i].clone(), node_counter);
-            node_counter += 1;
 
-            let to = genome.node_genome.last().unwrap().id;
-            for i in 0..AMOUNT_INPUT {
-                let from = genome.node_genome[i].id;
+        Self::new_fully_linked_with(&input_nodes, &OUTPUT_NODES)
+    }
+
+    /// Returns a genome with the given sensors and outputs and a link from every sensor to every output.
+    ///
+    /// Every genome built this way gets the same node ids and innovation numbers, so they can all be recombined.
+    ///
+    /// # Panics
+    /// If there are more than 6 sensors or 7 outputs, the `InnovationRegistry` only reserves numbers for that many.
+    pub fn new_fully_linked_with(
+        input_nodes: &[crate::neat::input::InputType],
+        output_nodes: &[crate::neat::output::OutputType],
+    ) -> Self {
+        assert!(input_nodes.len() <= AMOUNT_INPUT && output_nodes.len() <= AMOUNT_OUTPUT);
+
+        let mut genome = Genome {
+            node_genome: Vec::new(),
+            connection_genome: Vec::new(),
+            mutation_rate: mutation::DEFAULT_MUTATION_RATE,
+            plasticity: Plasticity::default(),
+        };
+        for (i, input) in input_nodes.iter().enumerate() {
+            genome.add_node(
+                NodeType::Sensor(input.clone()),
+                innovations::get_initial_sensor_id(i),
+            );
+        }
+
+        // Because all creatures start with this basic genome all of them get the same node ids and innovation numbers,
+        // the `InnovationRegistry` knows them as well.
+        for (o, output) in output_nodes.iter().enumerate() {
+            let to = innovations::get_initial_output_id(o);
+            genome.add_node(NodeType::Output(output.clone()), to);
 
-                // Because all creatures start with this basic genome give all the connections the same innovation number
-                // `counter` is used for this purpose
+            for i in 0..input_nodes.len() {
                 genome.connection_genome.push(ConnectionGene {
-                    from,
+                    from: innovations::get_initial_sensor_id(i),
                     to,
                     weight: Self::get_random_weight(),
 
                     enabled: true,
-                    innovation_number: con_counter,
+                    innovation_number: innovations::get_initial_innovation(i, o),
                 });
-                con_counter += 1;
             }
         }
 
@@ -141,7 +222,10 @@ impl Genome {
 impl Genome {
     pub fn log_nodes(&self) {
         for n in &self.node_genome {
-            println!("\tnode {} is {:?}", n.id, n.node_type);
+            println!(
+                "\tnode {} is {:?} with activation {}",
+                n.id, n.node_type, n.activation
+            );
         }
     }
 
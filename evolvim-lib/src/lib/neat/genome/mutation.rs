@@ -1,14 +1,72 @@
 use super::gene::{NodeGene, NodeType};
-use super::{get_next_node_id, Genome};
+use super::{Genome, Id, InnovationRegistry};
+use crate::neat::input::InputType;
+use rand::Rng;
 
-const CHANCE_MUTATE_NEW_LINK: f64 = 0.1;
-const CHANCE_MUTATE_LINK_TO_NODE: f64 = 0.05;
-const CHANCE_MUTATE_TWEAK_WEIGHT: f64 = 0.6;
-const CHANCE_MUTATE_RANDOM_WEIGHT: f64 = 0.2;
-const CHANCE_MUTATE_TOGGLE_ENABLED: f64 = 0.05;
+/// The average amount of mutations per call to `mutate` for a new genome, this evolves as well.
+///
+/// It doesn't depend on the size of the genome, when it did bigger genomes grew faster and faster.
+pub(super) const DEFAULT_MUTATION_RATE: f64 = 5.0;
+const MIN_MUTATION_RATE: f64 = 0.5;
+const MAX_MUTATION_RATE: f64 = 50.0;
+/// The most links an inversion reverses at once.
+const MAX_INVERSION_LENGTH: usize = 5;
+
+/// The relative chances of every kind of mutation, only their ratios matter.
+///
+/// Setting a chance to 0.0 turns that mutation off.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MutationConfig {
+    pub new_link: f64,
+    pub link_to_node: f64,
+    pub tweak_weight: f64,
+    pub random_weight: f64,
+    pub toggle_enabled: f64,
+    pub activation: f64,
+    pub deletion: f64,
+    pub node_deletion: f64,
+    pub duplication: f64,
+    pub inversion: f64,
+    pub insertion: f64,
+    pub translocation: f64,
+    pub remove_disabled: f64,
+    pub add_sensor: f64,
+    pub jitter_sensor: f64,
+    pub remove_sensor: f64,
+}
+
+impl Default for MutationConfig {
+    fn default() -> Self {
+        MutationConfig {
+            new_link: 0.1,
+            link_to_node: 0.05,
+            tweak_weight: 0.6,
+            random_weight: 0.2,
+            toggle_enabled: 0.05,
+            activation: 0.05,
+            deletion: 0.05,
+            node_deletion: 0.02,
+            duplication: 0.02,
+            inversion: 0.02,
+            insertion: 0.02,
+            translocation: 0.03,
+            remove_disabled: 0.03,
+            add_sensor: 0.02,
+            jitter_sensor: 0.05,
+            remove_sensor: 0.02,
+        }
+    }
+}
 
 impl Genome {
-    pub fn mutate(&mut self) {
+    /// Mutates this genome with the default chances.
+    pub fn mutate(&mut self, registry: &InnovationRegistry) {
+        self.mutate_with(registry, &MutationConfig::default());
+    }
+
+    /// Changes the mutation rate and plasticity a little, then performs that many random mutations on average.
+    pub fn mutate_with(&mut self, registry: &InnovationRegistry, config: &MutationConfig) {
         use rand::distributions::Distribution;
         use MutationType::*;
 
@@ -18,80 +76,358 @@ impl Genome {
             TweakWeight,
             RandomizeWeight,
             ToggleEnabled,
+            ChangeActivation,
+            Deletion,
+            NodeDeletion,
+            Duplication,
+            Inversion,
+            Insertion,
+            Translocation,
+            RemoveDisabled,
+            AddSensor,
+            JitterSensor,
+            RemoveSensor,
         }
 
         impl MutationType {
-            const fn get_choices() -> [Self; 5] {
+            const fn get_choices() -> [Self; 16] {
                 [
                     AddConnection,
                     ConnectionToNode,
                     TweakWeight,
                     RandomizeWeight,
                     ToggleEnabled,
+                    ChangeActivation,
+                    Deletion,
+                    NodeDeletion,
+                    Duplication,
+                    Inversion,
+                    Insertion,
+                    Translocation,
+                    RemoveDisabled,
+                    AddSensor,
+                    JitterSensor,
+                    RemoveSensor,
                 ]
             }
 
-            const fn get_weights() -> [f64; 5] {
+            fn get_weights(config: &MutationConfig) -> [f64; 16] {
                 [
-                    CHANCE_MUTATE_NEW_LINK,
-                    CHANCE_MUTATE_LINK_TO_NODE,
-                    CHANCE_MUTATE_TWEAK_WEIGHT,
-                    CHANCE_MUTATE_RANDOM_WEIGHT,
-                    CHANCE_MUTATE_TOGGLE_ENABLED,
+                    config.new_link,
+                    config.link_to_node,
+                    config.tweak_weight,
+                    config.random_weight,
+                    config.toggle_enabled,
+                    config.activation,
+                    config.deletion,
+                    config.node_deletion,
+                    config.duplication,
+                    config.inversion,
+                    config.insertion,
+                    config.translocation,
+                    config.remove_disabled,
+                    config.add_sensor,
+                    config.jitter_sensor,
+                    config.remove_sensor,
                 ]
             }
         }
 
-        let dist = rand::distributions::WeightedIndex::new(&MutationType::get_weights()).unwrap();
+        // All chances being zero means nothing ever mutates.
+        let dist = match rand::distributions::WeightedIndex::new(&MutationType::get_weights(config))
+        {
+            Ok(dist) => dist,
+            Err(_) => return,
+        };
         let mut rng = rand::thread_rng();
-        let times = self.connection_genome.len() / 2;
 
-        for _i in 0..times {
+        self.mutation_rate = crate::brain::inherit_mutability(
+            &[self.mutation_rate],
+            MIN_MUTATION_RATE,
+            MAX_MUTATION_RATE,
+        );
+        self.plasticity.mutate();
+        // Round up or down at random so the average stays right.
+        let mut amount = self.mutation_rate.floor() as usize;
+        if rng.gen::<f64>() < self.mutation_rate.fract() {
+            amount += 1;
+        }
+
+        for _i in 0..amount {
             match MutationType::get_choices()[dist.sample(&mut rng)] {
-                AddConnection => self.mutate_add_connection(),
-                ConnectionToNode => self.mutate_connection_to_node(),
+                AddConnection => self.mutate_add_connection(registry),
+                ConnectionToNode => self.mutate_connection_to_node(registry),
                 TweakWeight => self.mutate_tweak_weight(),
                 RandomizeWeight => self.mutate_randomize_weight(),
                 ToggleEnabled => self.mutate_toggle_gene(),
+                ChangeActivation => self.mutate_activation(),
+                Deletion => self.mutate_delete_connection(),
+                NodeDeletion => self.mutate_delete_node(),
+                Duplication => self.mutate_duplicate_node(registry),
+                Inversion => self.mutate_inversion(),
+                Insertion => self.mutate_insertion(registry),
+                Translocation => self.mutate_translocation(registry),
+                RemoveDisabled => self.mutate_remove_disabled(),
+                AddSensor => self.mutate_add_sensor(registry),
+                JitterSensor => self.mutate_jitter_sensor(),
+                RemoveSensor => self.mutate_remove_sensor(),
             }
         }
     }
 
-    pub fn mutate_add_connection(&mut self) {
+    pub fn mutate_add_connection(&mut self, registry: &InnovationRegistry) {
         let from = self.get_random_node_id();
         let to = self.get_random_node_id();
         let weight = Self::get_random_weight();
 
-        self.add_connection(from, to, weight);
+        self.add_connection(from, to, weight, registry);
     }
 
-    pub fn mutate_connection_to_node(&mut self) {
-        let connection_id = self.get_random_connection_place();
-        let next_node_id = get_next_node_id();
+    pub fn mutate_connection_to_node(&mut self, registry: &InnovationRegistry) {
+        let connection_id = match self.get_random_connection_place() {
+            Some(place) => place,
+            None => return,
+        };
+        let innovation_number = self.connection_genome[connection_id].innovation_number;
+        let mut next_node_id = registry.get_split_node(innovation_number);
+        // This genome already split this link before, that node can't be used twice.
+        if self.has_node(next_node_id) {
+            next_node_id = registry.get_new_node_id();
+        }
         let (from, to) = self.connection_genome[connection_id].disable_and_info();
 
-        self.add_connection(from, next_node_id, Self::get_random_weight());
-        self.add_connection(next_node_id, to, Self::get_random_weight());
+        self.add_connection(from, next_node_id, Self::get_random_weight(), registry);
+        self.add_connection(next_node_id, to, Self::get_random_weight(), registry);
 
-        self.node_genome.push(NodeGene {
-            node_type: NodeType::Hidden,
-            id: next_node_id,
-        });
+        self.node_genome
+            .push(NodeGene::new(NodeType::Hidden, next_node_id));
     }
 
     pub fn mutate_tweak_weight(&mut self) {
-        let connection_id = self.get_random_connection_place();
-        self.connection_genome[connection_id].weight *= Self::get_random_weight_multiplier();
+        if let Some(connection_id) = self.get_random_connection_place() {
+            self.connection_genome[connection_id].weight *= Self::get_random_weight_multiplier();
+        }
     }
 
     pub fn mutate_randomize_weight(&mut self) {
-        let connection_id = self.get_random_connection_place();
-        self.connection_genome[connection_id].weight = Self::get_random_weight();
+        if let Some(connection_id) = self.get_random_connection_place() {
+            self.connection_genome[connection_id].weight = Self::get_random_weight();
+        }
     }
 
     pub fn mutate_toggle_gene(&mut self) {
-        let connection_id = self.get_random_connection_place();
-        // toggle `enabled`
-        self.connection_genome[connection_id].toggle_enabled();
+        if let Some(connection_id) = self.get_random_connection_place() {
+            // toggle `enabled`
+            self.connection_genome[connection_id].toggle_enabled();
+        }
+    }
+
+    /// Gives a random hidden node a different activation function.
+    ///
+    /// Sensor and output nodes keep theirs, the inputs and outputs of the brain have a fixed range.
+    pub fn mutate_activation(&mut self) {
+        if let Some(place) = self.get_random_hidden_node_place() {
+            let node = &mut self.node_genome[place];
+            node.activation = node.activation.new_random_other();
+        }
+    }
+
+    /// Deletion: removes a random link, and the hidden node it leaves unconnected if there is one.
+    pub fn mutate_delete_connection(&mut self) {
+        if let Some(connection_id) = self.get_random_connection_place() {
+            self.connection_genome.remove(connection_id);
+            self.remove_orphan_nodes();
+        }
+    }
+
+    /// Deletion: removes a random hidden node together with all its links.
+    pub fn mutate_delete_node(&mut self) {
+        let place = match self.get_random_hidden_node_place() {
+            Some(place) => place,
+            None => return,
+        };
+        let id = self.node_genome.remove(place).id;
+
+        self.connection_genome
+            .retain(|c| c.from != id && c.to != id);
+        self.remove_orphan_nodes();
+    }
+
+    /// Duplication: copies a random hidden node with all its enabled links.
+    ///
+    /// The outgoing links of the original are split between it and the copy, so the network
+    /// behaves the same until one of them mutates.
+    pub fn mutate_duplicate_node(&mut self, registry: &InnovationRegistry) {
+        let place = match self.get_random_hidden_node_place() {
+            Some(place) => place,
+            None => return,
+        };
+        let original = self.node_genome[place].clone();
+        let copy_id = registry.get_new_node_id();
+
+        let replace = |id: Id| if id == original.id { copy_id } else { id };
+        let mut copied = Vec::new();
+        for c in self.connection_genome.iter_mut().filter(|c| c.enabled) {
+            if c.from == original.id {
+                c.weight /= 2.0;
+            }
+            if c.from == original.id || c.to == original.id {
+                copied.push((replace(c.from), replace(c.to), c.weight));
+            }
+        }
+
+        self.node_genome.push(NodeGene {
+            id: copy_id,
+            ..original
+        });
+        for (from, to, weight) in copied {
+            self.add_connection(from, to, weight, registry);
+        }
+    }
+
+    /// Inversion: reverses the order of the weights along a short random path through the network.
+    ///
+    /// The path starts at a random link and keeps following a random link out of the node it ends at,
+    /// the way an inversion reverses a stretch of a chromosome. Nothing happens if the first link leads nowhere.
+    pub fn mutate_inversion(&mut self) {
+        let start = match self.get_random_connection_place() {
+            Some(place) => place,
+            None => return,
+        };
+
+        let mut rng = rand::thread_rng();
+        let mut path = vec![start];
+        while path.len() < MAX_INVERSION_LENGTH {
+            let end = self.connection_genome[*path.last().unwrap()].to;
+            let next: Vec<usize> = (0..self.connection_genome.len())
+                .filter(|i| self.connection_genome[*i].from == end && !path.contains(i))
+                .collect();
+            if next.is_empty() {
+                break;
+            }
+
+            path.push(next[rng.gen_range(0, next.len())]);
+        }
+
+        let length = path.len();
+        for i in 0..length / 2 {
+            let (a, b) = (path[i], path[length - 1 - i]);
+            let weight = self.connection_genome[a].weight;
+            self.connection_genome[a].weight = self.connection_genome[b].weight;
+            self.connection_genome[b].weight = weight;
+        }
+    }
+
+    /// Insertion: adds a new hidden node with a link from and a link to a random node.
+    pub fn mutate_insertion(&mut self, registry: &InnovationRegistry) {
+        let from = self.get_random_node_id();
+        let to = self.get_random_node_id();
+        let id = registry.get_new_node_id();
+
+        self.node_genome.push(NodeGene::new(NodeType::Hidden, id));
+        self.add_connection(from, id, Self::get_random_weight(), registry);
+        self.add_connection(id, to, Self::get_random_weight(), registry);
+    }
+
+    /// Translocation: moves the end of a random link to a different node, keeping its weight and whether it is enabled.
+    pub fn mutate_translocation(&mut self, registry: &InnovationRegistry) {
+        let connection_id = match self.get_random_connection_place() {
+            Some(place) => place,
+            None => return,
+        };
+        let from = self.connection_genome[connection_id].from;
+        let to = self.get_random_node_id();
+        let weight = self.connection_genome[connection_id].weight;
+        let enabled = self.connection_genome[connection_id].enabled;
+        let innovation_number = self.connection_genome[connection_id].innovation_number;
+
+        if self.add_connection(from, to, weight, registry) {
+            // Adding a link can move the old one to a different place.
+            self.connection_genome
+                .retain(|c| c.innovation_number != innovation_number);
+            for c in self.connection_genome.iter_mut() {
+                if c.from == from && c.to == to {
+                    c.enabled = enabled;
+                }
+            }
+            self.remove_orphan_nodes();
+        }
+    }
+
+    /// Removes a random disabled link, these don't do anything but make the genome bigger.
+    pub fn mutate_remove_disabled(&mut self) {
+        let disabled: Vec<usize> = (0..self.connection_genome.len())
+            .filter(|&i| !self.connection_genome[i].enabled)
+            .collect();
+        if disabled.is_empty() {
+            return;
+        }
+
+        let place = disabled[rand::thread_rng().gen_range(0, disabled.len())];
+        self.connection_genome.remove(place);
+        self.remove_orphan_nodes();
+    }
+
+    /// Adds a random new sensor with a link to a random node that isn't a sensor.
+    pub fn mutate_add_sensor(&mut self, registry: &InnovationRegistry) {
+        let targets: Vec<Id> = self
+            .node_genome
+            .iter()
+            .filter(|n| !n.node_type.is_sensor())
+            .map(|n| n.id)
+            .collect();
+        let to = targets[rand::thread_rng().gen_range(0, targets.len())];
+        let id = registry.get_new_node_id();
+
+        self.node_genome
+            .push(NodeGene::new(NodeType::Sensor(InputType::new_random()), id));
+        self.add_connection(id, to, Self::get_random_weight(), registry);
+    }
+
+    /// Moves a random eye a little, does nothing if this genome has no eyes.
+    pub fn mutate_jitter_sensor(&mut self) {
+        let eyes: Vec<usize> = (0..self.node_genome.len())
+            .filter(|&i| match self.node_genome[i].node_type {
+                NodeType::Sensor(InputType::Eye(_)) => true,
+                _ => false,
+            })
+            .collect();
+        if eyes.is_empty() {
+            return;
+        }
+
+        let place = eyes[rand::thread_rng().gen_range(0, eyes.len())];
+        if let NodeType::Sensor(InputType::Eye(eye)) = &mut self.node_genome[place].node_type {
+            eye.jitter();
+        }
+    }
+
+    /// Removes a random sensor without enabled links, together with its disabled links.
+    pub fn mutate_remove_sensor(&mut self) {
+        let connections = &self.connection_genome;
+        let unused: Vec<usize> = (0..self.node_genome.len())
+            .filter(|&i| {
+                let n = &self.node_genome[i];
+                n.node_type.is_sensor() && !connections.iter().any(|c| c.enabled && c.from == n.id)
+            })
+            .collect();
+        if unused.is_empty() {
+            return;
+        }
+
+        let place = unused[rand::thread_rng().gen_range(0, unused.len())];
+        let id = self.node_genome.remove(place).id;
+        self.connection_genome
+            .retain(|c| c.from != id && c.to != id);
+        self.remove_orphan_nodes();
+    }
+
+    /// Removes the hidden nodes without any links, sensors and outputs are always kept.
+    pub fn remove_orphan_nodes(&mut self) {
+        let connections = &self.connection_genome;
+
+        self.node_genome.retain(|n| {
+            !n.node_type.is_hidden() || connections.iter().any(|c| c.from == n.id || c.to == n.id)
+        });
     }
 }
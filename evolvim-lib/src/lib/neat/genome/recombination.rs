@@ -1,64 +1,102 @@
-use super::gene::NodeGene;
-use super::utils::{RecombinationGeneTypes, RecombinationGenomesIterator};
+use super::gene::{ConnectionGene, NodeGene};
 use super::Genome;
 
 impl Genome {
-    /// Multipoint crossover:
+    /// Multipoint crossover between two equally fit parents:
     /// - matching genes: average the weight
     /// - disjoint genes: always include
     /// - excess genes: always include
     pub fn new_from_2(parent_a: &Genome, parent_b: &Genome) -> Genome {
-        let mut genome = Genome {
-            node_genome: Vec::new(),
-            connection_genome: Vec::new(),
-        };
+        Genome::new_from_parents(&[(parent_a, 0.0), (parent_b, 0.0)])
+    }
+
+    /// Multipoint crossover between any amount of parents, each paired with its fitness:
+    /// - genes of the fittest parent: include, the weight is the average of every parent with that gene
+    /// - disjoint and excess genes of less fit parents: leave out
+    ///
+    /// If several parents are the fittest the genes of all of them are included.
+    /// Links between the same nodes can have different innovation numbers when the `InnovationRegistry` forgot one,
+    /// only the oldest of those is included.
+    /// The mutation rate and plasticity are the averages of all parents, this doesn't mutate anything:
+    /// `mutate_with` changes them afterwards like it does for a child with a single parent.
+    /// Whether a gene is enabled and which version of a node is used is decided by the fittest parent that has it.
+    ///
+    /// # Panics
+    /// If `parents` is empty.
+    pub fn new_from_parents(parents: &[(&Genome, f64)]) -> Genome {
+        use std::collections::BTreeMap;
 
-        use RecombinationGeneTypes::*;
-        for g in RecombinationGenomesIterator::new(parent_a, parent_b) {
-            match g {
-                Matching(a, b) => {
-                    genome.connection_genome.push(a.clone());
-                    genome.connection_genome.last_mut().unwrap().weight += b.weight;
-                    genome.connection_genome.last_mut().unwrap().weight /= 2.0;
-                }
-                Disjoint(_, gene) => {
-                    genome.connection_genome.push(gene.clone());
-                }
-                Excess(_, gene) => {
-                    genome.connection_genome.push(gene.clone());
-                }
+        assert!(!parents.is_empty(), "Crossover needs at least one parent.");
+
+        let mut parents = parents.to_vec();
+        // Fittest first, this is a stable sort so equally fit parents stay in order.
+        parents.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+        let best_fitness = parents[0].1;
+
+        // Ordered by innovation number: the gene, the sum of its weights, the amount of parents with it
+        // and whether one of the fittest parents has it.
+        let mut genes: BTreeMap<usize, (ConnectionGene, f64, usize, bool)> = BTreeMap::new();
+        for (parent, fitness) in &parents {
+            let fittest = *fitness >= best_fitness;
+
+            for gene in &parent.connection_genome {
+                let entry = genes
+                    .entry(gene.innovation_number)
+                    .or_insert_with(|| (gene.clone(), 0.0, 0, false));
+                entry.1 += gene.weight;
+                entry.2 += 1;
+                entry.3 |= fittest;
             }
         }
 
+        let mutation_rate =
+            parents.iter().map(|(p, _)| p.mutation_rate).sum::<f64>() / parents.len() as f64;
+        let plasticities: Vec<_> = parents.iter().map(|(p, _)| p.plasticity).collect();
+        let plasticity = crate::brain::Plasticity::average(&plasticities);
+        let mut linked = std::collections::HashSet::new();
+        let mut genome = Genome {
+            node_genome: Vec::new(),
+            connection_genome: genes
+                .into_iter()
+                .filter(|(_, (_, _, _, fittest))| *fittest)
+                .filter(|(_, (gene, _, _, _))| linked.insert((gene.from, gene.to)))
+                .map(|(_, (mut gene, weight_sum, amount, _))| {
+                    gene.weight = weight_sum / amount as f64;
+                    gene
+                })
+                .collect(),
+            mutation_rate,
+            plasticity,
+        };
+
         // Make the node genome
-        genome.generate_nodes_from_connections(&parent_a.node_genome, &parent_b.node_genome);
+        let node_genomes: Vec<&Vec<NodeGene>> =
+            parents.iter().map(|(p, _)| &p.node_genome).collect();
+        genome.generate_nodes_from_connections(&node_genomes);
 
         return genome;
     }
 
-    fn generate_nodes_from_connections(
-        &mut self,
-        parent_a: &Vec<NodeGene>,
-        parent_b: &Vec<NodeGene>,
-    ) {
+    /// Takes the nodes used by the connection genome from `parents`, the first parent with a node decides its version.
+    fn generate_nodes_from_connections(&mut self, parents: &[&Vec<NodeGene>]) {
         use std::collections::HashSet;
 
         let mut neuron_ids = HashSet::new();
 
+        // Sensors and outputs are kept even when they lost all their links, so they can be linked again later.
+        for n in parents.iter().flat_map(|p| p.iter()) {
+            if !n.node_type.is_hidden() {
+                neuron_ids.insert(n.id);
+            }
+        }
         for i in &self.connection_genome {
             neuron_ids.insert(i.from);
             neuron_ids.insert(i.to);
         }
 
-        for a in parent_a {
-            if neuron_ids.remove(&a.id) {
-                self.node_genome.push(a.clone());
-            }
-        }
-
-        for b in parent_b {
-            if neuron_ids.remove(&b.id) {
-                self.node_genome.push(b.clone());
+        for n in parents.iter().flat_map(|p| p.iter()) {
+            if neuron_ids.remove(&n.id) {
+                self.node_genome.push(n.clone());
             }
         }
     }
@@ -1,13 +1,41 @@
 use super::utils::{RecombinationGeneTypes, RecombinationGenomesIterator};
 use super::Genome;
 
-// URGENT TODO: change these
-const COEFFICIENT_MATCHING: f64 = 1.0;
-const COEFFICIENT_DISJOINT: f64 = 1.0;
-const COEFFICIENT_EXCESS: f64 = 1.0;
+/// How much every kind of difference between two genomes counts in `Genome::genetical_distance_with`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DistanceCoefficients {
+    /// Multiplies the average weight difference of matching genes.
+    pub matching: f64,
+    /// Multiplies the fraction of disjoint genes.
+    pub disjoint: f64,
+    /// Multiplies the fraction of excess genes.
+    pub excess: f64,
+}
+
+impl Default for DistanceCoefficients {
+    fn default() -> Self {
+        DistanceCoefficients {
+            matching: 1.0,
+            disjoint: 1.0,
+            excess: 1.0,
+        }
+    }
+}
 
 impl Genome {
+    /// Returns the distance between two genomes using the default `DistanceCoefficients`.
     pub fn genetical_distance(&self, other: &Genome) -> f64 {
+        self.genetical_distance_with(other, &DistanceCoefficients::default())
+    }
+
+    /// Returns `c_1 * W + c_2 * D / N + c_3 * E / N`,
+    /// where `W` is the average weight difference of matching genes, `D` the amount of disjoint genes,
+    /// `E` the amount of excess genes and `N` the amount of genes in both genomes together.
+    pub fn genetical_distance_with(
+        &self,
+        other: &Genome,
+        coefficients: &DistanceCoefficients,
+    ) -> f64 {
         use RecombinationGeneTypes::*;
 
         let iter = RecombinationGenomesIterator::new(&self, other);
@@ -28,9 +56,17 @@ impl Genome {
         }
 
         let length = (counter_matching + counter_disjoint + counter_excess) as f64;
+        if length == 0.0 {
+            return 0.0;
+        }
+        let average_weight_difference = if counter_matching > 0 {
+            weight_differences / counter_matching as f64
+        } else {
+            0.0
+        };
 
-        return COEFFICIENT_MATCHING * weight_differences / counter_matching as f64
-            + COEFFICIENT_DISJOINT * counter_disjoint as f64 / length
-            + COEFFICIENT_EXCESS * counter_excess as f64 / length;
+        coefficients.matching * average_weight_difference
+            + coefficients.disjoint * counter_disjoint as f64 / length
+            + coefficients.excess * counter_excess as f64 / length
     }
 }
@@ -0,0 +1,424 @@
+//! Contains `HyperNeatBrain`, a large feed-forward network whose weights are drawn by a small NEAT network.
+//!
+//! The large network, the substrate, has a fixed layout: its neurons have a place on a plane. Sensors form a ring of
+//! eyes around the creature, the hidden neurons a square grid and the outputs a row.
+//! The small network, a CPPN (compositional pattern-producing network), is asked for the weight of every link
+//! between two layers given the places of the neurons it connects. Only the CPPN evolves, so regular patterns like
+//! symmetry or every eye being wired the same way are cheap to find and the substrate can be far bigger than its genome.
+
+extern crate nalgebra;
+extern crate serde_json;
+
+use self::nalgebra::{DMatrix, RowDVector};
+use super::input::{Coordinate, Eye, InputType};
+use super::output::OutputType;
+use super::species::{Speciate, SpeciesId};
+use super::{Genome, MutationConfig, NeatContext};
+use crate::brain::Activation;
+use std::f64::consts::PI;
+
+/// The amount of eyes evenly spread around the creature, each one looks for food level, food colour and fertility.
+const SUBSTRATE_EYES: usize = 8;
+/// The hidden layer is a square grid with this many neurons on a side.
+const SUBSTRATE_HIDDEN_SIDE: usize = 5;
+/// How far away the eyes look, in tiles.
+const EYE_DISTANCE: f64 = 2.0;
+/// CPPN outputs closer to 0 than this don't become a link.
+const WEIGHT_THRESHOLD: f64 = 0.2;
+/// The weight of a link for which the CPPN outputs 1 or more.
+const MAX_WEIGHT: f64 = 3.0;
+
+const INPUT_SIZE: usize = SUBSTRATE_EYES * 3 + 2;
+const OUTPUTS: [OutputType; 7] = [
+    OutputType::Turning,
+    OutputType::Accelerating,
+    OutputType::MouthHue,
+    OutputType::Eating,
+    OutputType::Fight,
+    OutputType::Birth,
+    OutputType::HelpBirth,
+];
+/// The hidden layer uses a sigmoid function and the output layer doesn't use one, like `Brain`.
+const ACTIVATIONS: [Activation; 2] = [Activation::Sigmoid, Activation::Identity];
+
+const CPPN_INPUTS: [InputType; 6] = [
+    InputType::Bias(1.0),
+    InputType::Coordinate(Coordinate::FromX),
+    InputType::Coordinate(Coordinate::FromY),
+    InputType::Coordinate(Coordinate::ToX),
+    InputType::Coordinate(Coordinate::ToY),
+    InputType::Coordinate(Coordinate::Distance),
+];
+const CPPN_OUTPUTS: [OutputType; 4] = [
+    OutputType::Weight(0),
+    OutputType::Weight(1),
+    OutputType::Bias(0),
+    OutputType::Bias(1),
+];
+
+type Position = (f64, f64);
+
+#[derive(Debug)]
+pub struct HyperNeatBrain {
+    cppn: Genome,
+    /// The values of every layer of the substrate, without bias nodes.
+    layers: Vec<RowDVector<f64>>,
+    /// One matrix for every layer but the input layer, laid out like the weights of a `Brain`.
+    weights: Vec<DMatrix<f64>>,
+    /// Assigned by the `SpeciesRegistry` using the CPPN.
+    species: Option<SpeciesId>,
+}
+
+impl From<Genome> for HyperNeatBrain {
+    fn from(cppn: Genome) -> Self {
+        let weights = express(&cppn);
+        let layers = get_layer_positions()
+            .iter()
+            .map(|l| RowDVector::zeros(l.len()))
+            .collect();
+
+        HyperNeatBrain {
+            cppn,
+            layers,
+            weights,
+            species: None,
+        }
+    }
+}
+
+impl HyperNeatBrain {
+    /// Returns the CPPN that drew the weights of this brain.
+    pub fn get_cppn(&self) -> &Genome {
+        &self.cppn
+    }
+
+    /// Returns the amount of links in the substrate that have a weight, including those of bias nodes.
+    pub fn get_expressed_links(&self) -> usize {
+        self.weights
+            .iter()
+            .map(|w| w.iter().filter(|v| **v != 0.0).count())
+            .sum()
+    }
+
+    fn get_output(&self, output: &OutputType) -> f64 {
+        let values = self.layers.last().unwrap();
+
+        OUTPUTS
+            .iter()
+            .position(|o| o == output)
+            .map_or(0.0, |i| values[i])
+    }
+}
+
+/// Returns the places of the neurons of every layer of the substrate, all within [-1, 1] on both axes.
+///
+/// The inputs are the eyes, in rings from the eyes for food level on the inside to those for fertility on the
+/// outside, followed by the energy and mouth hue of the creature in the corners.
+fn get_layer_positions() -> [Vec<Position>; 3] {
+    let mut inputs = Vec::with_capacity(INPUT_SIZE);
+    for i in 0..SUBSTRATE_EYES {
+        let angle = get_eye_angle(i);
+        for ring in 1..=3 {
+            let radius = ring as f64 / 3.0;
+            inputs.push((radius * angle.cos(), radius * angle.sin()));
+        }
+    }
+    inputs.push((-1.0, -1.0));
+    inputs.push((1.0, -1.0));
+
+    let spread = |i: usize, n: usize| -1.0 + 2.0 * i as f64 / (n - 1) as f64;
+
+    let mut hidden = Vec::with_capacity(SUBSTRATE_HIDDEN_SIDE * SUBSTRATE_HIDDEN_SIDE);
+    for y in 0..SUBSTRATE_HIDDEN_SIDE {
+        for x in 0..SUBSTRATE_HIDDEN_SIDE {
+            hidden.push((
+                spread(x, SUBSTRATE_HIDDEN_SIDE),
+                spread(y, SUBSTRATE_HIDDEN_SIDE),
+            ));
+        }
+    }
+
+    let outputs = (0..OUTPUTS.len())
+        .map(|i| (spread(i, OUTPUTS.len()), 0.0))
+        .collect();
+
+    [inputs, hidden, outputs]
+}
+
+/// Returns the direction eye `i` looks in relative to the creature, the first one looks straight ahead.
+fn get_eye_angle(i: usize) -> f64 {
+    2.0 * PI * i as f64 / SUBSTRATE_EYES as f64
+}
+
+/// Asks `cppn` for the weights of every layer of the substrate.
+///
+/// The bias of a neuron is asked for as if it were a link from the middle of the plane.
+fn express(cppn: &Genome) -> Vec<DMatrix<f64>> {
+    let mut net: super::NeuralNet = cppn.into();
+    let positions = get_layer_positions();
+
+    positions
+        .windows(2)
+        .enumerate()
+        .map(|(layer, pair)| {
+            let (from, to) = (&pair[0], &pair[1]);
+
+            DMatrix::from_fn(from.len() + 1, to.len(), |y, z| {
+                if y == 0 {
+                    query(&mut net, (0.0, 0.0), to[z], &OutputType::Bias(layer))
+                } else {
+                    query(&mut net, from[y - 1], to[z], &OutputType::Weight(layer))
+                }
+            })
+        })
+        .collect()
+}
+
+/// Returns the weight the CPPN `net` gives the link from `from` to `to`.
+///
+/// Outputs are clamped to [-1, 1], those close to 0 give no link and the others are scaled up to `MAX_WEIGHT`.
+fn query(net: &mut super::NeuralNet, from: Position, to: Position, output: &OutputType) -> f64 {
+    net.query(|input| match input {
+        InputType::Coordinate(c) => c.get_value(from, to),
+        InputType::Bias(v) => *v,
+        _ => 0.0,
+    });
+
+    let value = net.get_output(output).max(-1.0).min(1.0);
+    if value.abs() < WEIGHT_THRESHOLD {
+        0.0
+    } else {
+        value.signum() * (value.abs() - WEIGHT_THRESHOLD) / (1.0 - WEIGHT_THRESHOLD) * MAX_WEIGHT
+    }
+}
+
+/// The sensors of a CPPN are fixed, so the mutations that add, move or remove sensors are turned off.
+fn get_cppn_mutation_config(config: &MutationConfig) -> MutationConfig {
+    MutationConfig {
+        add_sensor: 0.0,
+        jitter_sensor: 0.0,
+        remove_sensor: 0.0,
+        ..config.clone()
+    }
+}
+
+impl crate::brain::NeuralNet for HyperNeatBrain {
+    type Context = NeatContext;
+
+    fn load_input(&mut self, env: &crate::brain::Environment) {
+        let input = &mut self.layers[0];
+
+        for i in 0..SUBSTRATE_EYES {
+            let data = Eye::get_data_of_all_three(EYE_DISTANCE, get_eye_angle(i), env);
+            for (j, value) in data.iter().enumerate() {
+                input[3 * i + j] = *value;
+            }
+        }
+
+        input[SUBSTRATE_EYES * 3] = env.this_body.get_energy();
+        input[SUBSTRATE_EYES * 3 + 1] = env.this_body.get_mouth_hue();
+    }
+
+    fn run(&mut self) {
+        crate::brain::feed_forward::feed_forward(&mut self.layers, &self.weights, &ACTIVATIONS);
+    }
+
+    fn use_output<B>(&self, env: &mut crate::brain::EnvironmentMut<B>, time_step: f64) {
+        let values = self.layers.last().unwrap();
+
+        for (output, value) in OUTPUTS.iter().zip(values.iter()) {
+            output.use_output(*value, env, time_step);
+        }
+    }
+
+    fn get_architecture(&self) -> &'static str {
+        "hyperneat"
+    }
+
+    /// Every link of the substrate with a weight costs one unit, the CPPN itself only runs when the creature is born.
+    fn metabolic_cost(&self) -> f64 {
+        self.get_expressed_links() as f64
+    }
+
+    fn get_mutability(&self) -> Option<f64> {
+        Some(self.cppn.get_mutation_rate())
+    }
+
+    fn get_species(&self) -> Option<usize> {
+        self.species
+    }
+
+    fn update_context(
+        context: &mut NeatContext,
+        creatures: &[crate::softbody::HLSoftBody<Self>],
+        time: f64,
+    ) {
+        context.species.update(creatures, time);
+    }
+}
+
+impl Speciate for HyperNeatBrain {
+    fn get_genome(&self) -> &Genome {
+        &self.cppn
+    }
+
+    fn set_species(&mut self, species: SpeciesId) {
+        self.species = Some(species);
+    }
+}
+
+impl crate::brain::Intentions for HyperNeatBrain {
+    fn wants_birth(&self) -> f64 {
+        self.get_output(&OutputType::Birth)
+    }
+
+    fn wants_help_birth(&self) -> f64 {
+        self.get_output(&OutputType::HelpBirth)
+    }
+}
+
+impl crate::brain::GenerateRandom for HyperNeatBrain {
+    /// Returns a brain with a CPPN that links every coordinate straight to every output.
+    fn new_random() -> Self {
+        Genome::new_fully_linked_with(&CPPN_INPUTS, &CPPN_OUTPUTS).into()
+    }
+}
+
+impl crate::brain::RecombinationTwoParents for HyperNeatBrain {
+    fn recombination_two_parents(parent_a: &Self, parent_b: &Self) -> Self {
+        Genome::new_from_2(&parent_a.cppn, &parent_b.cppn).into()
+    }
+}
+
+impl crate::brain::RecombinationInfinite for HyperNeatBrain {
+    fn recombination_infinite_brains(parents: &[(&Self, f64)], context: &NeatContext) -> Self {
+        if parents.len() == 1 {
+            let mut cppn = parents[0].0.cppn.clone();
+            cppn.mutate_with(
+                &context.innovations,
+                &get_cppn_mutation_config(&context.mutation),
+            );
+
+            cppn.into()
+        } else {
+            // The creatures with the most energy are the fittest, just like for `NeatBrain`.
+            let cppns: Vec<(&Genome, f64)> = parents
+                .iter()
+                .map(|(b, energy)| (&b.cppn, *energy))
+                .collect();
+
+            let mut cppn = Genome::new_from_parents(&cppns);
+            cppn.mutate_with(
+                &context.innovations,
+                &get_cppn_mutation_config(&context.mutation),
+            );
+
+            cppn.into()
+        }
+    }
+}
+
+impl crate::brain::ProvideInformation for HyperNeatBrain {
+    fn get_keys(&self) -> Vec<String> {
+        vec![
+            "species".to_string(),
+            "cppn nodes".to_string(),
+            "cppn connections".to_string(),
+            "links".to_string(),
+        ]
+    }
+
+    fn get_raw_values(&self) -> Vec<String> {
+        let species = match self.species {
+            Some(id) => format!("{}", id),
+            None => "none".to_string(),
+        };
+
+        vec![
+            species,
+            format!("{}", self.cppn.get_node_genome().len()),
+            format!("{}", self.cppn.get_connection_genome().len()),
+            format!("{}", self.get_expressed_links()),
+        ]
+    }
+}
+
+impl crate::brain::ExportBrain for HyperNeatBrain {
+    /// Returns the graph of the CPPN, the substrate is too big to read as a graph and is left to `to_json`.
+    fn to_dot(&self) -> String {
+        self.cppn.to_dot()
+    }
+
+    /// The substrate has the place of every neuron and, like a `Brain`, one row of weights per neuron of the
+    /// previous layer with the bias node first.
+    fn to_json(&self) -> serde_json::Value {
+        let weights: Vec<Vec<Vec<f64>>> = self
+            .weights
+            .iter()
+            .map(|theta| {
+                (0..theta.nrows())
+                    .map(|y| (0..theta.ncols()).map(|z| theta[(y, z)]).collect())
+                    .collect()
+            })
+            .collect();
+
+        serde_json::json!({
+            "type": "hyperneat",
+            "species": self.species,
+            "cppn": self.cppn.to_json(),
+            "substrate": {
+                "positions": get_layer_positions(),
+                "activations": ACTIVATIONS,
+                "weights": weights,
+            },
+        })
+    }
+}
+
+/// Only the CPPN is saved, the substrate is drawn again when loading.
+impl serde::Serialize for HyperNeatBrain {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let mut state = serializer.serialize_struct("HyperNeatBrain", 2)?;
+        state.serialize_field("cppn", &self.cppn)?;
+        state.serialize_field("species", &self.species)?;
+
+        state.end()
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for HyperNeatBrain {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<HyperNeatBrain, D::Error> {
+        use serde::de::*;
+
+        struct BrainVisitor;
+
+        impl<'de> Visitor<'de> for BrainVisitor {
+            type Value = HyperNeatBrain;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str("struct HyperNeatBrain")
+            }
+
+            fn visit_seq<V: SeqAccess<'de>>(self, mut seq: V) -> Result<HyperNeatBrain, V::Error> {
+                let cppn: Genome = seq
+                    .next_element()?
+                    .ok_or_else(|| Error::invalid_length(0, &self))?;
+                let species: Option<SpeciesId> = seq
+                    .next_element()?
+                    .ok_or_else(|| Error::invalid_length(1, &self))?;
+
+                let mut brain: HyperNeatBrain = cppn.into();
+                brain.species = species;
+
+                Ok(brain)
+            }
+        }
+
+        const FIELDS: &[&str] = &["cppn", "species"];
+        deserializer.deserialize_struct::<BrainVisitor>("HyperNeatBrain", FIELDS, BrainVisitor)
+    }
+}
@@ -1,4 +1,13 @@
+extern crate rand;
+
 use crate::brain::Environment;
+use rand::Rng;
+
+/// How far away an eye can look, in tiles.
+const MAX_EYE_DISTANCE: f64 = 4.0;
+/// The most an eye moves away from its place when it is jittered.
+const EYE_JITTER_DISTANCE: f64 = 0.5;
+const EYE_JITTER_ANGLE: f64 = 0.3;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum InputType {
@@ -6,9 +15,46 @@ pub enum InputType {
     Bias(f64),
     MouthHue,
     Energy,
+    /// An input of a CPPN, it is given its value by `NeuralNet::query` instead of by the environment.
+    Coordinate(Coordinate),
+}
+
+/// Describes the link between two neurons of a HyperNEAT substrate to its CPPN.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Coordinate {
+    FromX,
+    FromY,
+    ToX,
+    ToY,
+    /// The distance between the two neurons.
+    Distance,
+}
+
+impl Coordinate {
+    /// Returns the value of this coordinate for the link between neurons at `from` and `to`.
+    pub fn get_value(self, from: (f64, f64), to: (f64, f64)) -> f64 {
+        use Coordinate::*;
+
+        match self {
+            FromX => from.0,
+            FromY => from.1,
+            ToX => to.0,
+            ToY => to.1,
+            Distance => (to.0 - from.0).hypot(to.1 - from.1),
+        }
+    }
 }
 
 impl InputType {
+    /// Returns a random sensor, usually an eye.
+    pub fn new_random() -> Self {
+        match rand::thread_rng().gen_range(0, 5) {
+            0 => InputType::MouthHue,
+            1 => InputType::Energy,
+            _ => InputType::Eye(Eye::new_random()),
+        }
+    }
+
     pub fn get_data(&self, env: &Environment) -> f64 {
         use InputType::*;
 
@@ -17,6 +63,7 @@ impl InputType {
             Eye(s) => s.get_data(env),
             MouthHue => env.this_body.get_mouth_hue(),
             Energy => env.this_body.get_energy(),
+            Coordinate(_) => 0.0,
         }
     }
 }
@@ -35,17 +82,41 @@ enum EyeType {
     TileFertility,
 }
 
+impl EyeType {
+    fn new_random() -> Self {
+        match rand::thread_rng().gen_range(0, 3) {
+            0 => EyeType::FoodLevel,
+            1 => EyeType::FoodColor,
+            _ => EyeType::TileFertility,
+        }
+    }
+}
+
 impl Eye {
+    /// Returns an eye looking for something random at a random place around the creature.
+    pub fn new_random() -> Self {
+        let mut rng = rand::thread_rng();
+
+        Eye {
+            relative_distance: rng.gen_range(0.0, MAX_EYE_DISTANCE),
+            angle: rng.gen_range(-std::f64::consts::PI, std::f64::consts::PI),
+            what_to_look_for: EyeType::new_random(),
+        }
+    }
+
+    /// Moves this eye a little, it keeps looking for the same thing.
+    pub fn jitter(&mut self) {
+        let mut rng = rand::thread_rng();
+
+        self.relative_distance += rng.gen_range(-EYE_JITTER_DISTANCE, EYE_JITTER_DISTANCE);
+        self.relative_distance = self.relative_distance.max(0.0).min(MAX_EYE_DISTANCE);
+        self.angle += rng.gen_range(-EYE_JITTER_ANGLE, EYE_JITTER_ANGLE);
+    }
+
     pub fn get_data(&self, env: &Environment) -> f64 {
-        use crate::board::BoardPreciseCoordinate;
         use EyeType::*;
 
-        let real_angle = self.angle + env.this_body.get_rotation();
-        let x = real_angle.cos() * self.relative_distance;
-        let y = real_angle.sin() * self.relative_distance;
-        let pos = BoardPreciseCoordinate(x + env.this_body.get_px(), y + env.this_body.get_py());
-
-        let tile = env.terrain.get_tile_at(pos.into());
+        let tile = Eye::get_tile(self.relative_distance, self.angle, env);
         match self.what_to_look_for {
             FoodLevel => tile.get_food_level(),
             FoodColor => tile.get_food_type(),
@@ -53,6 +124,42 @@ impl Eye {
         }
     }
 
+    /// Returns what the eyes returned by `get_all_three` see, in the same order, looking at the tile only once.
+    pub fn get_data_of_all_three(
+        relative_distance: f64,
+        angle: f64,
+        env: &Environment,
+    ) -> [f64; 3] {
+        let tile = Eye::get_tile(relative_distance, angle, env);
+
+        [
+            tile.get_food_level(),
+            tile.get_food_type(),
+            tile.get_fertility(),
+        ]
+    }
+
+    fn get_tile(
+        relative_distance: f64,
+        angle: f64,
+        env: &Environment,
+    ) -> crate::terrain::tile::Tile {
+        use crate::board::BoardPreciseCoordinate;
+
+        // Creatures with better eyesight look further away.
+        let distance = relative_distance * env.this_body.get_body_genome().eyesight;
+        let real_angle = angle + env.this_body.get_rotation();
+        let x = real_angle.cos() * distance + env.this_body.get_px();
+        let y = real_angle.sin() * distance + env.this_body.get_py();
+        // Eyes near the edge of the world look at the closest tile on the board.
+        let max_x = (env.terrain.get_width() - 1) as f64;
+        let max_y = (env.terrain.get_height() - 1) as f64;
+        let pos = BoardPreciseCoordinate(x.max(0.0).min(max_x), y.max(0.0).min(max_y));
+
+        env.terrain
+            .get_updated_tile_at(pos.into(), env.time, env.climate)
+    }
+
     pub const fn get_all_three(relative_distance: f64, angle: f64) -> [Self; 3] {
         [
             Eye {
@@ -73,3 +180,51 @@ impl Eye {
         ]
     }
 }
+
+impl std::fmt::Display for InputType {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        use InputType::*;
+
+        match self {
+            Eye(eye) => write!(f, "{}", eye),
+            Bias(v) => write!(f, "bias {}", v),
+            MouthHue => write!(f, "mouth hue"),
+            Energy => write!(f, "energy"),
+            Coordinate(c) => write!(f, "{}", c),
+        }
+    }
+}
+
+impl std::fmt::Display for Coordinate {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        use Coordinate::*;
+
+        let name = match self {
+            FromX => "from x",
+            FromY => "from y",
+            ToX => "to x",
+            ToY => "to y",
+            Distance => "distance",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+impl std::fmt::Display for Eye {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        use EyeType::*;
+
+        let what = match self.what_to_look_for {
+            FoodLevel => "food level",
+            FoodColor => "food colour",
+            TileFertility => "fertility",
+        };
+
+        write!(
+            f,
+            "eye for {} at {:.2} tiles, {:.2} rad",
+            what, self.relative_distance, self.angle
+        )
+    }
+}
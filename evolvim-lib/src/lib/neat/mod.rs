@@ -1,26 +1,54 @@
 mod genome;
+mod hyperneat;
 mod input;
 mod output;
 mod phenotype;
+pub mod species;
 
-pub use genome::Genome;
+pub use genome::{DistanceCoefficients, Genome, InnovationRegistry, MutationConfig};
+pub use hyperneat::HyperNeatBrain;
 pub use phenotype::NeuralNet;
+pub use species::{Speciate, SpeciationConfig, SpeciesId, SpeciesRegistry};
 
 #[derive(Debug)]
 pub struct NeatBrain {
     genome: Genome,
     net: NeuralNet,
+    /// Assigned by the `SpeciesRegistry`, saved so creatures stay in their species after loading.
+    species: Option<SpeciesId>,
 }
 
 impl From<Genome> for NeatBrain {
     fn from(genome: Genome) -> Self {
         let net = (&genome).into();
 
-        NeatBrain { genome, net }
+        NeatBrain {
+            genome,
+            net,
+            species: None,
+        }
+    }
+}
+
+/// Everything NEAT needs to know about the whole world, owned by the `Board`.
+#[derive(Default, Serialize, Deserialize)]
+pub struct NeatContext {
+    pub innovations: InnovationRegistry,
+    pub species: SpeciesRegistry,
+    #[serde(default)]
+    pub mutation: MutationConfig,
+}
+
+impl NeatBrain {
+    /// Returns the genome this brain was built from.
+    pub fn get_genome(&self) -> &Genome {
+        &self.genome
     }
 }
 
 impl crate::brain::NeuralNet for NeatBrain {
+    type Context = NeatContext;
+
     fn load_input(&mut self, env: &crate::brain::Environment) {
         self.net.load_input(env);
     }
@@ -29,18 +57,65 @@ impl crate::brain::NeuralNet for NeatBrain {
         self.net.run_calculations();
     }
 
-    fn use_output(&self, env: &mut crate::brain::EnvironmentMut<Self>, time_step: f64) {
+    /// Only the weights of the phenotype change, children get the weights in the genome.
+    fn learn(&mut self, time_step: f64) {
+        let plasticity = self.genome.get_plasticity();
+        if plasticity.is_active() {
+            self.net.learn(plasticity, time_step);
+        }
+    }
+
+    fn use_output<B>(&self, env: &mut crate::brain::EnvironmentMut<B>, time_step: f64) {
         self.net.use_output(env, time_step);
     }
+
+    fn get_architecture(&self) -> &'static str {
+        "neat"
+    }
+
+    /// Every gene costs one unit, disabled links too because they are still passed on.
+    fn metabolic_cost(&self) -> f64 {
+        (self.genome.get_node_genome().len() + self.genome.get_connection_genome().len()) as f64
+    }
+
+    fn get_mutability(&self) -> Option<f64> {
+        Some(self.genome.get_mutation_rate())
+    }
+
+    fn get_plasticity(&self) -> Option<crate::brain::Plasticity> {
+        Some(*self.genome.get_plasticity())
+    }
+
+    fn get_species(&self) -> Option<usize> {
+        self.species
+    }
+
+    fn update_context(
+        context: &mut NeatContext,
+        creatures: &[crate::softbody::HLSoftBody<Self>],
+        time: f64,
+    ) {
+        context.species.update(creatures, time);
+    }
+}
+
+impl Speciate for NeatBrain {
+    fn get_genome(&self) -> &Genome {
+        &self.genome
+    }
+
+    fn set_species(&mut self, species: SpeciesId) {
+        self.species = Some(species);
+    }
 }
 
 impl crate::brain::Intentions for NeatBrain {
     fn wants_birth(&self) -> f64 {
-        unimplemented!()
+        self.net.get_output(&output::OutputType::Birth)
     }
 
     fn wants_help_birth(&self) -> f64 {
-        unimplemented!()
+        self.net.get_output(&output::OutputType::HelpBirth)
     }
 }
 
@@ -58,55 +133,87 @@ impl crate::brain::RecombinationTwoParents for NeatBrain {
 }
 
 impl crate::brain::RecombinationInfinite for NeatBrain {
-    fn recombination_infinite_parents(parents: &Vec<crate::softbody::HLSoftBody<Self>>) -> Self {
-        use crate::brain::RecombinationTwoParents;
-
+    fn recombination_infinite_brains(parents: &[(&Self, f64)], context: &NeatContext) -> Self {
         if parents.len() == 1 {
             // Only mutate this genome
 
-            let parent = parents[0].borrow();
             // Make a copy of the parent genome
-            let mut genome = parent.brain.genome.clone();
+            let mut genome = parents[0].0.genome.clone();
             // Mutate it
-            genome.mutate();
+            genome.mutate_with(&context.innovations, &context.mutation);
             // Generate a phenotype and return a NeatBrain
             genome.into()
         } else {
-            NeatBrain::recombination_two_parents(
-                &parents[0].borrow().brain,
-                &parents[1].borrow().brain,
-            )
+            // The creatures with the most energy are the fittest.
+            let genomes: Vec<(&Genome, f64)> = parents
+                .iter()
+                .map(|(b, energy)| (&b.genome, *energy))
+                .collect();
+
+            let mut genome = Genome::new_from_parents(&genomes);
+            genome.mutate_with(&context.innovations, &context.mutation);
+            genome.into()
         }
     }
 }
 
 impl crate::brain::ProvideInformation for NeatBrain {
     fn get_keys(&self) -> Vec<String> {
-        vec!["nodes".to_string(), "connections".to_string()]
+        vec![
+            "species".to_string(),
+            "nodes".to_string(),
+            "connections".to_string(),
+        ]
     }
 
     fn get_raw_values(&self) -> Vec<String> {
+        let species = match self.species {
+            Some(id) => format!("{}", id),
+            None => "none".to_string(),
+        };
+
         vec![
+            species,
             format!("{}", self.genome.get_node_genome().len()),
             format!("{}", self.genome.get_connection_genome().len()),
         ]
     }
 }
 
-// TODO: serialize the values of the nodes (which allows for memory)
+impl crate::brain::ExportBrain for NeatBrain {
+    /// Returns the graph of the genome, which includes the disabled links the phenotype leaves out.
+    fn to_dot(&self) -> String {
+        self.genome.to_dot()
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "neat",
+            "species": self.species,
+            "genome": self.genome.to_json(),
+            "phenotype": self.net.to_json(),
+        })
+    }
+}
+
+/// The phenotype isn't saved but rebuilt from the genome, only the values of its nodes and its learned weights are saved.
 impl serde::Serialize for NeatBrain {
     fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
         use serde::ser::SerializeStruct;
 
-        let mut state = serializer.serialize_struct("NeatBrain", 1)?;
+        let mut state = serializer.serialize_struct("NeatBrain", 5)?;
+        let (values, previous_values) = self.net.get_values();
 
         state.serialize_field("genome", &self.genome)?;
+        state.serialize_field("values", values)?;
+        state.serialize_field("previous_values", previous_values)?;
+        state.serialize_field("weights", &self.net.get_weights())?;
+        state.serialize_field("species", &self.species)?;
 
         state.end()
     }
 }
 
-// TODO: deserialize the values of the nodes (which allows for memory)
 impl<'de> serde::Deserialize<'de> for NeatBrain {
     fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<NeatBrain, D::Error> {
         use serde::de::*;
@@ -124,12 +231,29 @@ impl<'de> serde::Deserialize<'de> for NeatBrain {
                 let genome: Genome = seq
                     .next_element()?
                     .ok_or_else(|| Error::invalid_length(0, &self))?;
+                let values: Vec<f64> = seq
+                    .next_element()?
+                    .ok_or_else(|| Error::invalid_length(1, &self))?;
+                let previous_values: Vec<f64> = seq
+                    .next_element()?
+                    .ok_or_else(|| Error::invalid_length(2, &self))?;
+                let weights: Vec<f64> = seq
+                    .next_element()?
+                    .ok_or_else(|| Error::invalid_length(3, &self))?;
+                let species: Option<SpeciesId> = seq
+                    .next_element()?
+                    .ok_or_else(|| Error::invalid_length(4, &self))?;
+
+                let mut brain: NeatBrain = genome.into();
+                brain.net.set_values(values, previous_values);
+                brain.net.set_weights(weights);
+                brain.species = species;
 
-                Ok(genome.into())
+                Ok(brain)
             }
         }
 
-        const FIELDS: &[&str] = &["genome"];
+        const FIELDS: &[&str] = &["genome", "values", "previous_values", "weights", "species"];
         deserializer.deserialize_struct::<BrainVisitor>("NeatBrain", FIELDS, BrainVisitor)
     }
 }
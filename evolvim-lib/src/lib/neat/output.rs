@@ -1,10 +1,18 @@
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum OutputType {
     MouthHue,
     Eating,
     Turning,
     Accelerating,
     Fight,
+    /// Read by the board through `Intentions::wants_birth`.
+    Birth,
+    /// Read by the board through `Intentions::wants_help_birth`.
+    HelpBirth,
+    /// Read by `HyperNeatBrain`: the weight of links to the given layer of its substrate, the first hidden layer is 0.
+    Weight(usize),
+    /// Read by `HyperNeatBrain`: the bias of neurons in the given layer of its substrate.
+    Bias(usize),
 }
 
 impl OutputType {
@@ -20,9 +28,14 @@ impl OutputType {
             MouthHue => env.this_body.set_mouth_hue(value),
             Eating => {
                 let tile_pos = env.this_body.get_random_covered_tile(env.board_size);
-                let tile = env.terrain.get_tile_at_mut(tile_pos);
-                env.this_body
-                    .eat(value, time_step, env.time, env.climate, tile);
+                env.this_body.eat(
+                    value,
+                    time_step,
+                    env.time,
+                    env.climate,
+                    env.terrain,
+                    tile_pos,
+                );
             }
             Turning => env.this_body.turn(value, time_step),
             Accelerating => env.this_body.accelerate(value, time_step),
@@ -33,6 +46,30 @@ impl OutputType {
                 env.sbip,
                 env.self_pointer.clone(),
             ),
+            // Reproduction is handled by the board.
+            Birth | HelpBirth => {}
+            // CPPNs don't control a body.
+            Weight(_) | Bias(_) => {}
         };
     }
 }
+
+impl std::fmt::Display for OutputType {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        use OutputType::*;
+
+        let name = match self {
+            MouthHue => "mouth hue",
+            Eating => "eating",
+            Turning => "turning",
+            Accelerating => "accelerating",
+            Fight => "fight",
+            Birth => "birth",
+            HelpBirth => "help birth",
+            Weight(layer) => return write!(f, "weight {}", layer),
+            Bias(layer) => return write!(f, "bias {}", layer),
+        };
+
+        write!(f, "{}", name)
+    }
+}
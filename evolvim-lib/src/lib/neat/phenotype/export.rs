@@ -0,0 +1,107 @@
+extern crate serde_json;
+
+use super::NeuralNet;
+use crate::brain::export::{get_edge_attributes, write_dot_header};
+use crate::brain::ExportBrain;
+use serde_json::json;
+
+impl NeuralNet {
+    /// Returns every link as the index of the node it comes from, the index of the node it goes to,
+    /// its weight and whether it is recurrent.
+    fn get_links(&self) -> Vec<(usize, usize, f64, bool)> {
+        let mut links = Vec::with_capacity(self.connections.len());
+
+        for (to, node) in self.nodes.iter().enumerate() {
+            for i in node.start..node.end {
+                let c = &self.connections[i];
+                links.push((c.from, to, c.weight, i >= node.recurrent_start));
+            }
+        }
+
+        links
+    }
+}
+
+impl ExportBrain for NeuralNet {
+    /// Nodes are labeled with the id of their gene and their current value,
+    /// recurrent links don't affect the layout so the graph shows the order of evaluation.
+    fn to_dot(&self) -> String {
+        let mut dot = String::new();
+        write_dot_header(&mut dot, "phenotype");
+
+        for (i, node) in self.nodes.iter().enumerate() {
+            let input = self.inputs.iter().find(|input| input.node_index == i);
+            let output = self.outputs.iter().find(|output| output.node_index == i);
+            let (kind, shape) = match (input, output) {
+                (Some(input), _) => (format!("\\n{}", input.input_type), "box"),
+                (_, Some(output)) => (format!("\\n{}", output.output_type), "doublecircle"),
+                _ => (String::new(), "circle"),
+            };
+
+            dot.push_str(&format!(
+                "    n{} [label=\"{}{}\\n{} = {:.3}\", shape={}];\n",
+                node.id, node.id, kind, node.activation, self.values[i], shape
+            ));
+        }
+
+        for (from, to, weight, recurrent) in self.get_links() {
+            let extra = if recurrent { ", constraint=false" } else { "" };
+
+            dot.push_str(&format!(
+                "    n{} -> n{} [{}{}];\n",
+                self.nodes[from].id,
+                self.nodes[to].id,
+                get_edge_attributes(weight, true),
+                extra
+            ));
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// The nodes in order of evaluation with their current value, and the links between them by node id.
+    fn to_json(&self) -> serde_json::Value {
+        let nodes: Vec<_> = self
+            .nodes
+            .iter()
+            .zip(&self.values)
+            .map(|(node, value)| {
+                json!({
+                    "id": node.id,
+                    "activation": node.activation,
+                    "value": value,
+                })
+            })
+            .collect();
+        let links: Vec<_> = self
+            .get_links()
+            .into_iter()
+            .map(|(from, to, weight, recurrent)| {
+                json!({
+                    "from": self.nodes[from].id,
+                    "to": self.nodes[to].id,
+                    "weight": weight,
+                    "recurrent": recurrent,
+                })
+            })
+            .collect();
+        let inputs: Vec<_> = self
+            .inputs
+            .iter()
+            .map(|input| json!({ "node": self.nodes[input.node_index].id, "type": input.input_type }))
+            .collect();
+        let outputs: Vec<_> = self
+            .outputs
+            .iter()
+            .map(|output| json!({ "node": self.nodes[output.node_index].id, "type": output.output_type }))
+            .collect();
+
+        json!({
+            "nodes": nodes,
+            "links": links,
+            "inputs": inputs,
+            "outputs": outputs,
+        })
+    }
+}
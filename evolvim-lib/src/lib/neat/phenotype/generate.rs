@@ -1,69 +1,156 @@
 use super::super::genome::{Genome, Id, NodeType};
-use super::{Connection, NeuralNet, Node};
+use super::{Connection, Input, NeuralNet, Node, Output};
 use std::collections::HashMap;
 
-// TODO: clean this HORRIFIC code up...
+/// A link between two nodes, by their place in the node genome.
+struct Link {
+    from: usize,
+    to: usize,
+    weight: f64,
+    recurrent: bool,
+}
+
 impl From<&Genome> for NeuralNet {
     fn from(genome: &Genome) -> Self {
         let node_gen = genome.get_node_genome();
-        let mut nodes: Box<[Node]> = std::iter::repeat(Node::empty())
-            .take(node_gen.len())
-            .collect::<Vec<Node>>()
-            .into_boxed_slice();
+        let amount_nodes = node_gen.len();
+        let lookup: HashMap<Id, usize> = node_gen
+            .iter()
+            .enumerate()
+            .map(|(i, n)| (n.id, i))
+            .collect();
+
+        let mut links: Vec<Link> = genome
+            .get_connection_genome()
+            .iter()
+            .filter(|c| c.enabled)
+            .map(|c| Link {
+                from: lookup[&c.from],
+                to: lookup[&c.to],
+                weight: c.weight,
+                recurrent: false,
+            })
+            .collect();
+        let order = sort_topologically(amount_nodes, &mut links);
+
+        // From here on nodes are referred to by their place in `order`.
+        let mut position = vec![0; amount_nodes];
+        for (i, &n) in order.iter().enumerate() {
+            position[n] = i;
+        }
+        for l in &mut links {
+            l.from = position[l.from];
+            l.to = position[l.to];
+        }
+
         let mut inputs = Vec::new();
-        // Preallocate the memory so we don't have to reallocate and make the *mut-pointers invalid.
-        let mut outputs = Vec::with_capacity(
-            node_gen
-                .iter()
-                .filter(|node| {
-                    if let NodeType::Output(_) = node.node_type {
-                        true
-                    } else {
-                        false
-                    }
-                })
-                .count(),
-        );
-        let mut lookup: HashMap<Id, usize> = HashMap::new();
-
-        let mut counter = 0;
-        for i in node_gen {
-            lookup.insert(i.id, counter);
-
-            match &i.node_type {
-                NodeType::Sensor(in_type) => {
-                    inputs.push(super::Input::new(counter, in_type.clone()));
-                }
+        let mut outputs = Vec::new();
+        for (i, n) in node_gen.iter().enumerate() {
+            match &n.node_type {
+                NodeType::Sensor(in_type) => inputs.push(Input::new(position[i], in_type.clone())),
                 NodeType::Output(out_type) => {
-                    outputs.push(super::Output::new(counter, out_type.clone()));
-                    let to: *mut f64 = &mut outputs.last_mut().unwrap().value;
-                    nodes[counter]
-                        .connections
-                        .push(unsafe { Connection::new(to, 1.0) });
+                    outputs.push(Output::new(position[i], out_type.clone()))
                 }
-                _ => {}
+                NodeType::Hidden => {}
             }
-
-            counter += 1;
         }
 
-        for con in genome.get_connection_genome().iter().filter(|c| c.enabled) {
-            let from = get_usize_from_id(&lookup, con.from);
-            let to = &mut nodes[get_usize_from_id(&lookup, con.to)].value as *mut f64;
+        // Group the links by the node they go to, with the recurrent ones last.
+        links.sort_by_key(|l| (l.to, l.recurrent));
+        let mut nodes = Vec::with_capacity(amount_nodes);
+        let mut connections = Vec::with_capacity(links.len());
+        let mut links = links.into_iter().peekable();
+        for (i, &n) in order.iter().enumerate() {
+            let start = connections.len();
+            let mut recurrent_start = None;
+
+            while let Some(l) = links.peek() {
+                if l.to != i {
+                    break;
+                }
+
+                if l.recurrent && recurrent_start.is_none() {
+                    recurrent_start = Some(connections.len());
+                }
+                connections.push(Connection {
+                    from: l.from,
+                    weight: l.weight,
+                });
+                links.next();
+            }
 
-            nodes[from]
-                .connections
-                .push(unsafe { Connection::new(to, con.weight) });
+            nodes.push(Node {
+                id: node_gen[n].id,
+                activation: node_gen[n].activation,
+                start,
+                recurrent_start: recurrent_start.unwrap_or(connections.len()),
+                end: connections.len(),
+            });
         }
 
         NeuralNet {
             nodes,
-            inputs,
+            connections,
+
+            values: vec![0.0; amount_nodes],
+            previous_values: vec![0.0; amount_nodes],
+            external: vec![0.0; amount_nodes],
+
             outputs: outputs.into_boxed_slice(),
+            inputs,
         }
     }
 }
 
-fn get_usize_from_id(m: &HashMap<Id, usize>, id: Id) -> usize {
-    *m.get(&id).unwrap()
+/// Returns the nodes in an order where every link goes forward, except for the links it marks as `recurrent`.
+///
+/// This is a depth-first search starting at the first node in the genome (the sensors),
+/// links back to a node that is still being searched close a cycle and become recurrent.
+fn sort_topologically(amount_nodes: usize, links: &mut [Link]) -> Vec<usize> {
+    #[derive(Clone, Copy, PartialEq)]
+    enum State {
+        New,
+        Searching,
+        Done,
+    }
+
+    let mut outgoing = vec![Vec::new(); amount_nodes];
+    for (i, l) in links.iter().enumerate() {
+        outgoing[l.from].push(i);
+    }
+
+    let mut state = vec![State::New; amount_nodes];
+    let mut finished = Vec::with_capacity(amount_nodes);
+    // The nodes that are being searched, with the amount of their links that were already followed.
+    let mut stack: Vec<(usize, usize)> = Vec::new();
+
+    for root in 0..amount_nodes {
+        if state[root] != State::New {
+            continue;
+        }
+        state[root] = State::Searching;
+        stack.push((root, 0));
+
+        while let Some((node, followed)) = stack.pop() {
+            if let Some(&link) = outgoing[node].get(followed) {
+                stack.push((node, followed + 1));
+
+                let to = links[link].to;
+                match state[to] {
+                    State::New => {
+                        state[to] = State::Searching;
+                        stack.push((to, 0));
+                    }
+                    State::Searching => links[link].recurrent = true,
+                    State::Done => {}
+                }
+            } else {
+                state[node] = State::Done;
+                finished.push(node);
+            }
+        }
+    }
+
+    finished.reverse();
+    finished
 }
@@ -1,12 +1,32 @@
+//! Contains the neural network that is built from a `Genome`.
+//!
+//! Nodes are evaluated in topological order, so a signal travels through the whole feed-forward part of the
+//! network in a single tick no matter in which order its genes were added.
+//! Links that close a cycle (including links from a node to itself) are recurrent: they carry the value their
+//! source node had in the previous tick.
+
+mod export;
 mod generate;
 
+use super::genome::Id;
+use crate::brain::{Activation, Plasticity};
+
 use super::input::InputType;
 use super::output::OutputType;
 
-// TODO: use unsafe pointers or something to speed things up
 #[derive(Debug)]
 pub struct NeuralNet {
-    nodes: Box<[Node]>,
+    /// Every node in the order in which they are evaluated, the other fields use the same indices.
+    nodes: Vec<Node>,
+    /// Every link, grouped by the node it goes to.
+    connections: Vec<Connection>,
+
+    /// The value of every node in this tick.
+    values: Vec<f64>,
+    /// The value of every node in the previous tick.
+    previous_values: Vec<f64>,
+    /// Input from sensors that is added to a node in the next tick.
+    external: Vec<f64>,
 
     outputs: Box<[Output]>,
     inputs: Vec<Input>,
@@ -15,28 +35,113 @@ pub struct NeuralNet {
 impl NeuralNet {
     pub fn load_input(&mut self, env: &crate::brain::Environment) {
         for input in &self.inputs {
-            input.load_into(&mut self.nodes, env);
+            self.external[input.node_index] += input.input_type.get_data(env);
         }
     }
 
-    pub fn use_output(
-        &self,
-        env: &mut crate::brain::EnvironmentMut<super::NeatBrain>,
-        time_step: f64,
-    ) {
+    pub fn use_output<B>(&self, env: &mut crate::brain::EnvironmentMut<B>, time_step: f64) {
         for output in self.outputs.iter() {
-            output.use_output(env, time_step);
+            output.use_output(self.values[output.node_index], env, time_step);
         }
     }
 
-    pub fn run_calculations(&mut self) {
-        for n in self.outputs.iter_mut() {
-            // Reset the value to 0
-            n.value = 0.0;
+    /// Returns the sum of the values of all outputs of type `output_type`, 0.0 if there are none.
+    pub fn get_output(&self, output_type: &OutputType) -> f64 {
+        self.outputs
+            .iter()
+            .filter(|o| o.output_type == *output_type)
+            .map(|o| self.values[o.node_index])
+            .sum()
+    }
+
+    /// Returns the value of every node in this tick and in the previous tick, in the order they are evaluated.
+    ///
+    /// Recurrent links make these the memory of the network.
+    pub fn get_values(&self) -> (&[f64], &[f64]) {
+        (&self.values, &self.previous_values)
+    }
+
+    /// Restores the values returned by `get_values` of a network built from the same genome.
+    ///
+    /// Values that don't fit this network are ignored, it keeps its current values instead.
+    pub fn set_values(&mut self, values: Vec<f64>, previous_values: Vec<f64>) {
+        if values.len() == self.nodes.len() && previous_values.len() == self.nodes.len() {
+            self.values = values;
+            self.previous_values = previous_values;
         }
+    }
+
+    /// Returns the weight of every link, grouped by the node it goes to.
+    ///
+    /// These only differ from the weights in the genome when the network learned something.
+    pub fn get_weights(&self) -> Vec<f64> {
+        self.connections.iter().map(|c| c.weight).collect()
+    }
 
-        for n in self.nodes.iter_mut() {
-            n.calc();
+    /// Restores the weights returned by `get_weights` of a network built from the same genome.
+    ///
+    /// Weights that don't fit this network are ignored, it keeps its current weights instead.
+    pub fn set_weights(&mut self, weights: Vec<f64>) {
+        if weights.len() == self.connections.len() {
+            for (c, w) in self.connections.iter_mut().zip(weights) {
+                c.weight = w;
+            }
+        }
+    }
+
+    /// Changes the weight of every link with `plasticity` for `time_step` years, based on the values of this tick.
+    ///
+    /// Recurrent links learn from the value their source had in the previous tick, which is the value they carried.
+    pub fn learn(&mut self, plasticity: &Plasticity, time_step: f64) {
+        for (i, node) in self.nodes.iter().enumerate() {
+            let post = self.values[i];
+
+            for (j, c) in self.connections[node.start..node.end]
+                .iter_mut()
+                .enumerate()
+            {
+                let pre = if node.start + j < node.recurrent_start {
+                    self.values[c.from]
+                } else {
+                    self.previous_values[c.from]
+                };
+
+                c.weight = plasticity.update_weight(c.weight, pre, post, time_step);
+            }
+        }
+    }
+
+    /// Runs the network once from scratch, every sensor gets the value `get_input` returns for its type.
+    ///
+    /// Used to ask a CPPN about the links of a substrate, the answer doesn't depend on earlier questions.
+    pub fn query<F: Fn(&InputType) -> f64>(&mut self, get_input: F) {
+        for v in self.values.iter_mut() {
+            *v = 0.0;
+        }
+        for input in &self.inputs {
+            self.external[input.node_index] += get_input(&input.input_type);
+        }
+
+        self.run_calculations();
+    }
+
+    pub fn run_calculations(&mut self) {
+        std::mem::swap(&mut self.values, &mut self.previous_values);
+
+        for (i, node) in self.nodes.iter().enumerate() {
+            let mut sum = self.external[i];
+
+            let (forward, recurrent) =
+                self.connections[node.start..node.end].split_at(node.recurrent_start - node.start);
+            for c in forward {
+                sum += c.weight * self.values[c.from];
+            }
+            for c in recurrent {
+                sum += c.weight * self.previous_values[c.from];
+            }
+
+            self.values[i] = node.activation.apply(sum);
+            self.external[i] = 0.0;
         }
     }
 }
@@ -44,19 +149,17 @@ impl NeuralNet {
 #[derive(Debug)]
 struct Output {
     node_index: usize,
-    value: f64,
     output_type: OutputType,
 }
 
 impl Output {
-    fn use_output(&self, env: &mut crate::brain::EnvironmentMut<super::NeatBrain>, time_step: f64) {
-        self.output_type.use_output(self.value, env, time_step);
+    fn use_output<B>(&self, value: f64, env: &mut crate::brain::EnvironmentMut<B>, time_step: f64) {
+        self.output_type.use_output(value, env, time_step);
     }
 
     pub fn new(node_index: usize, output_type: OutputType) -> Self {
         Output {
             node_index,
-            value: 0.0,
             output_type,
         }
     }
@@ -69,11 +172,6 @@ struct Input {
 }
 
 impl Input {
-    pub fn load_into(&self, nodes: &mut [Node], env: &crate::brain::Environment) {
-        let data = self.input_type.get_data(env);
-        nodes[self.node_index].add_to_value(data);
-    }
-
     pub fn new(node_index: usize, input_type: InputType) -> Self {
         Input {
             node_index,
@@ -84,55 +182,20 @@ impl Input {
 
 #[derive(Clone, Debug)]
 struct Node {
-    pub value: f64,
-    pub connections: Vec<Connection>,
-}
-
-impl Node {
-    pub fn add_to_value(&mut self, n: f64) {
-        self.value += n;
-    }
-
-    pub fn perform_sigmoid(&mut self) -> f64 {
-        return sigmoid(self.value);
-    }
-
-    pub fn calc(&mut self) {
-        let sig_value = self.perform_sigmoid();
-
-        self.value = 0.0;
-
-        for c in &self.connections {
-            unsafe {
-                *c.to += c.weight * sig_value;
-            }
-        }
-    }
-
-    pub fn empty() -> Self {
-        Node {
-            value: 0.0,
-            connections: Vec::new(),
-        }
-    }
+    /// The id of the gene this node was built from.
+    id: Id,
+    activation: Activation,
+    // This node's incoming links are `NeuralNet::connections[start..end]`.
+    // The ones from `recurrent_start` onwards carry the value of the previous tick,
+    // the others come from nodes earlier in the order.
+    start: usize,
+    recurrent_start: usize,
+    end: usize,
 }
 
+/// A weighted link from the node at index `from`, stored with the node it goes to.
 #[derive(Clone, Debug)]
 struct Connection {
-    to: *mut f64,
+    from: usize,
     weight: f64,
 }
-
-impl Connection {
-    /// Weighted connection to another point in memory
-    ///
-    /// This is unsafe, to use this you must manually guarantee that the pointer stays valid
-    /// at least until we destroy this Neural Network struct.
-    pub unsafe fn new(to: *mut f64, weight: f64) -> Self {
-        Connection { to, weight }
-    }
-}
-
-fn sigmoid(n: f64) -> f64 {
-    1.0 / (1.0 + n.exp())
-}
@@ -0,0 +1,188 @@
+//! Contains `SpeciesRegistry`, which groups NEAT creatures into species by how different their genomes are.
+//!
+//! Every creature is compared to the representatives of the living species and joins the first one within
+//! `SpeciationConfig::threshold`, if none is close enough it founds a new species.
+//! Once a year every species gets a random member as its new representative and its size is recorded.
+
+extern crate rand;
+
+use super::genome::{DistanceCoefficients, Genome};
+use crate::brain::NeuralNet;
+use crate::softbody::HLSoftBody;
+use std::collections::BTreeMap;
+
+pub type SpeciesId = usize;
+
+/// The amount of extinct species that are remembered, the oldest are forgotten first.
+const MAX_EXTINCT_SPECIES: usize = 1000;
+
+/// Decides how far apart two genomes can be while still being the same species.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SpeciationConfig {
+    /// Genomes with a `genetical_distance_with` below this are the same species.
+    pub threshold: f64,
+    pub coefficients: DistanceCoefficients,
+}
+
+impl Default for SpeciationConfig {
+    fn default() -> Self {
+        SpeciationConfig {
+            threshold: 0.5,
+            coefficients: DistanceCoefficients::default(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Species {
+    pub id: SpeciesId,
+    /// New creatures are compared to this genome.
+    representative: Genome,
+    pub birth_time: f64,
+    /// `None` while the species is still alive.
+    pub extinction_time: Option<f64>,
+    /// The current amount of members.
+    pub size: usize,
+    pub max_size: usize,
+    /// The size at the start of every year since this species appeared.
+    pub size_history: Vec<usize>,
+}
+
+impl Species {
+    fn new(id: SpeciesId, representative: Genome, time: f64) -> Self {
+        Species {
+            id,
+            representative,
+            birth_time: time,
+            extinction_time: None,
+            size: 0,
+            max_size: 0,
+            size_history: Vec::new(),
+        }
+    }
+
+    pub fn get_representative(&self) -> &Genome {
+        &self.representative
+    }
+}
+
+/// A brain with a NEAT genome, `SpeciesRegistry` sorts it into a species by that genome.
+pub trait Speciate: NeuralNet {
+    /// Returns the genome that decides the species.
+    fn get_genome(&self) -> &Genome;
+
+    fn set_species(&mut self, species: SpeciesId);
+}
+
+/// Keeps track of every living species and remembers the ones that died out, owned by the `Board`.
+#[derive(Default, Serialize, Deserialize)]
+pub struct SpeciesRegistry {
+    pub config: SpeciationConfig,
+    alive: BTreeMap<SpeciesId, Species>,
+    extinct: Vec<Species>,
+    next_id: SpeciesId,
+    /// The last year in which a census was held.
+    last_census: Option<i64>,
+}
+
+impl SpeciesRegistry {
+    /// Returns all living species, ordered by id.
+    pub fn get_alive(&self) -> impl Iterator<Item = &Species> {
+        self.alive.values()
+    }
+
+    /// Returns the most recently extinct species, from old to new.
+    pub fn get_extinct(&self) -> &[Species] {
+        &self.extinct
+    }
+
+    pub fn get_species(&self, id: SpeciesId) -> Option<&Species> {
+        self.alive
+            .get(&id)
+            .or_else(|| self.extinct.iter().find(|s| s.id == id))
+    }
+
+    /// Assigns new creatures to a species, counts the members of every species and removes the ones that died out.
+    pub fn update<B: Speciate>(&mut self, creatures: &[HLSoftBody<B>], time: f64) {
+        let mut creatures: Vec<_> = creatures.iter().map(|c| c.borrow_mut()).collect();
+
+        self.update_brains(creatures.iter_mut().map(|c| &mut c.brain), time);
+    }
+
+    /// The same as `update` for the brains of every living creature, for when they are part of another brain.
+    pub fn update_brains<'a, B: Speciate + 'a>(
+        &mut self,
+        brains: impl Iterator<Item = &'a mut B>,
+        time: f64,
+    ) {
+        use self::rand::Rng;
+
+        let year = time.floor() as i64;
+        let census = self.last_census != Some(year);
+        let mut rng = rand::thread_rng();
+
+        for s in self.alive.values_mut() {
+            s.size = 0;
+        }
+
+        for brain in brains {
+            let id = match brain.get_species() {
+                Some(id) if self.alive.contains_key(&id) => id,
+                _ => self.classify(brain.get_genome(), time),
+            };
+            brain.set_species(id);
+
+            let species = self.alive.get_mut(&id).unwrap();
+            species.size += 1;
+
+            // Pick a random member as the new representative, every member has the same chance.
+            if census && rng.gen_range(0, species.size) == 0 {
+                species.representative = brain.get_genome().clone();
+            }
+        }
+
+        let (extinct, alive): (BTreeMap<_, _>, BTreeMap<_, _>) =
+            std::mem::replace(&mut self.alive, BTreeMap::new())
+                .into_iter()
+                .partition(|(_, s)| s.size == 0);
+        self.alive = alive;
+
+        for (_, mut s) in extinct {
+            s.extinction_time = Some(time);
+            self.extinct.push(s);
+        }
+        if self.extinct.len() > MAX_EXTINCT_SPECIES {
+            let too_many = self.extinct.len() - MAX_EXTINCT_SPECIES;
+            self.extinct.drain(..too_many);
+        }
+
+        for s in self.alive.values_mut() {
+            s.max_size = s.max_size.max(s.size);
+
+            if census {
+                s.size_history.push(s.size);
+            }
+        }
+        self.last_census = Some(year);
+    }
+
+    /// Returns the species `genome` belongs to, founding a new one if it isn't close to any living species.
+    fn classify(&mut self, genome: &Genome, time: f64) -> SpeciesId {
+        let config = &self.config;
+
+        let existing = self.alive.values().find(|s| {
+            genome.genetical_distance_with(&s.representative, &config.coefficients)
+                < config.threshold
+        });
+        if let Some(s) = existing {
+            return s.id;
+        }
+
+        let id = self.next_id;
+        self.next_id += 1;
+        self.alive
+            .insert(id, Species::new(id, genome.clone(), time));
+
+        id
+    }
+}
@@ -4,6 +4,8 @@ use crate::board::Board;
 use crate::terrain::Terrain;
 use crate::softbody::SoftBody;
 use crate::climate::Climate;
+use crate::disaster::DisasterScheduler;
+use crate::event_log::EventLog;
 use super::version::Version;
 
 use serde_derive::{Deserialize, Serialize};
@@ -28,10 +30,19 @@ pub struct BoardSerde<B: NeuralNet> {
 
     // Fields relevant for time or history
     year: f64,
+    pub event_log: EventLog,
 
     // Fields relevant for temperature
     pub climate: Climate,
 
+    // Fields relevant for disasters
+    pub disasters: DisasterScheduler,
+
+    // Fields relevant for brains
+    pub brain_context: B::Context,
+    brain_cost: f64,
+    lifetime_learning: bool,
+
     // Miscelanious
     // pub selected_creature: SelectedCreature<B>,
 }
@@ -43,6 +54,9 @@ impl<B: NeuralNet> From<Board<B>> for BoardSerde<B> {
         let creature_id_up_to = bd.get_creature_id_up_to();
         let year = bd.get_time();
 
+        // These hold pointers to the creatures as well, they have to go before the creatures can be unwrapped.
+        drop(bd.soft_bodies_in_positions);
+        drop(bd.selected_creature);
         let creatures: Vec<SoftBody<B>> = bd.creatures.into_iter().map(|c| c.into_inner()).collect();
 
         BoardSerde {
@@ -57,8 +71,15 @@ impl<B: NeuralNet> From<Board<B>> for BoardSerde<B> {
             creature_id_up_to,
             
             year,
+            event_log: bd.event_log,
 
             climate: bd.climate,
+
+            disasters: bd.disasters,
+
+            brain_context: bd.brain_context,
+            brain_cost: bd.brain_cost,
+            lifetime_learning: bd.lifetime_learning,
         }
     }
 }
@@ -97,9 +118,16 @@ impl<B: NeuralNet> From<BoardSerde<B>> for Board<B> {
             bs.creature_id_up_to,
 
             bs.year,
+            bs.event_log,
 
             bs.climate,
 
+            bs.disasters,
+
+            bs.brain_context,
+            bs.brain_cost,
+            bs.lifetime_learning,
+
             SelectedCreature::default(),
         )
     }
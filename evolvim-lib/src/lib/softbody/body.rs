@@ -0,0 +1,130 @@
+extern crate rand;
+
+use self::rand::distributions::StandardNormal;
+use self::rand::Rng;
+use crate::constants::*;
+
+const DENSITY_RANGE: (f64, f64) = (0.25, 4.0);
+const MIN_MAX_SPEED: f64 = 0.05;
+const TURN_EFFICIENCY_RANGE: (f64, f64) = (0.25, 4.0);
+const FOOD_SENSITIVITY_RANGE: (f64, f64) = (0.1, 1.0);
+const SWIM_EFFICIENCY_RANGE: (f64, f64) = (0.0, 1.0);
+const EYESIGHT_RANGE: (f64, f64) = (0.25, 4.0);
+const LIFESPAN_RANGE: (f64, f64) = (0.25, 4.0);
+
+/// The heritable traits of a creature's body, every birth changes them a little.
+///
+/// Every trait comes at a price, so no value is simply the best.
+/// The default genome gives a body that behaves exactly like bodies did before they could evolve.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BodyGenome {
+    /// Multiplies the mass: heavy bodies are hard to push around, but also hard to get moving.
+    pub density: f64,
+    /// The highest speed this body can reach, accelerating costs more energy for bodies with a higher top speed.
+    ///
+    /// This is infinite for bodies without a top speed, see `MAX_FINITE_SPEED`.
+    pub max_speed: f64,
+    /// Multiplies how fast this body turns, agile bodies accelerate worse.
+    pub turn_efficiency: f64,
+    /// How far the hue of food can be from the mouth hue before it becomes poisonous.
+    /// Bodies that tolerate a lot spend more energy on eating.
+    pub food_sensitivity: f64,
+    /// Between 0 and 1, the share of the swimming cost this body saves.
+    /// A body built for the water pays that share on land instead.
+    pub swim_efficiency: f64,
+    /// Multiplies how far away eyes look, only NEAT and HyperNEAT brains have eyes.
+    pub eyesight: f64,
+    /// Slows down aging, but creatures with a long lifespan also take longer to mature.
+    pub lifespan: f64,
+}
+
+impl Default for BodyGenome {
+    fn default() -> Self {
+        BodyGenome {
+            density: CREATURE_DENSITY,
+            max_speed: DEFAULT_MAX_SPEED,
+            turn_efficiency: 1.0,
+            food_sensitivity: FOOD_SENSITIVITY,
+            swim_efficiency: 0.0,
+            eyesight: 1.0,
+            lifespan: 1.0,
+        }
+    }
+}
+
+impl BodyGenome {
+    /// Returns the genome of a child: the average of its parents with a bit of mutation.
+    ///
+    /// # Panics
+    /// If `parents` is empty.
+    pub fn new_from_parents(parents: &[&BodyGenome]) -> BodyGenome {
+        let mut genome = BodyGenome::average(parents);
+        genome.mutate();
+
+        genome
+    }
+
+    /// Returns the average of every trait of `genomes`.
+    ///
+    /// The average has no top speed if one of the genomes has none.
+    ///
+    /// # Panics
+    /// If `genomes` is empty.
+    pub fn average(genomes: &[&BodyGenome]) -> BodyGenome {
+        assert!(!genomes.is_empty(), "An average needs at least one genome.");
+
+        let amount = genomes.len() as f64;
+        let average =
+            |get: fn(&BodyGenome) -> f64| genomes.iter().map(|g| get(g)).sum::<f64>() / amount;
+
+        BodyGenome {
+            density: average(|g| g.density),
+            max_speed: average(|g| g.max_speed),
+            turn_efficiency: average(|g| g.turn_efficiency),
+            food_sensitivity: average(|g| g.food_sensitivity),
+            swim_efficiency: average(|g| g.swim_efficiency),
+            eyesight: average(|g| g.eyesight),
+            lifespan: average(|g| g.lifespan),
+        }
+    }
+
+    /// Changes every trait a little, see `BODY_GENE_CHANGE`.
+    ///
+    /// Traits that multiply something change by a factor, so halving them is as likely as doubling them.
+    pub fn mutate(&mut self) {
+        let mut rng = rand::thread_rng();
+        let mut change = || BODY_GENE_CHANGE * rng.sample::<f64, _>(StandardNormal);
+        let keep_in = |value: f64, (min, max): (f64, f64)| value.max(min).min(max);
+
+        self.density = keep_in(self.density * change().exp(), DENSITY_RANGE);
+        // A body without a top speed can get one just below `MAX_FINITE_SPEED`, and lose it again.
+        let max_speed = self.max_speed.min(MAX_FINITE_SPEED) * change().exp();
+        self.max_speed = if max_speed < MAX_FINITE_SPEED {
+            max_speed.max(MIN_MAX_SPEED)
+        } else {
+            std::f64::INFINITY
+        };
+        self.turn_efficiency =
+            keep_in(self.turn_efficiency * change().exp(), TURN_EFFICIENCY_RANGE);
+        self.food_sensitivity = keep_in(
+            self.food_sensitivity * change().exp(),
+            FOOD_SENSITIVITY_RANGE,
+        );
+        self.swim_efficiency = keep_in(self.swim_efficiency + change(), SWIM_EFFICIENCY_RANGE);
+        self.eyesight = keep_in(self.eyesight * change().exp(), EYESIGHT_RANGE);
+        self.lifespan = keep_in(self.lifespan * change().exp(), LIFESPAN_RANGE);
+    }
+
+    /// Returns the name and value of every trait.
+    pub fn get_traits(&self) -> [(&'static str, f64); 7] {
+        [
+            ("density", self.density),
+            ("max speed", self.max_speed),
+            ("turn efficiency", self.turn_efficiency),
+            ("food sensitivity", self.food_sensitivity),
+            ("swim efficiency", self.swim_efficiency),
+            ("eyesight", self.eyesight),
+            ("lifespan", self.lifespan),
+        ]
+    }
+}
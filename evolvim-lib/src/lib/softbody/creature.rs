@@ -26,21 +26,36 @@ impl<B> std::ops::DerefMut for Creature<B> {
 
 impl<B: GenerateRandom> Creature<B> {
     pub fn new_random(board_size: BoardSize, time: f64) -> Self {
+        Creature::with_random_body(board_size, time, B::new_random())
+    }
+
+    fn with_random_body(board_size: BoardSize, time: f64, brain: B) -> Self {
         let energy = CREATURE_MIN_ENERGY
             + rand::random::<f64>() * (CREATURE_MAX_ENERGY - CREATURE_MIN_ENERGY);
         let base = Rock::new_random(board_size, CREATURE_DENSITY, energy, time);
-        let brain = B::new_random();
         // TODO: add id
 
         Creature { base, brain }
     }
 }
 
+impl<B: NeuralNet + GenerateRandom> Creature<B> {
+    /// Returns a random creature with a random brain for a world with the given context.
+    pub fn new_random_with_context(board_size: BoardSize, time: f64, context: &B::Context) -> Self {
+        Creature::with_random_body(board_size, time, B::new_random_with_context(context))
+    }
+}
+
 impl<B: NeuralNet + RecombinationInfinite> Creature<B> {
     /// Create a new baby, it isn't in `SoftBodiesInPositions` so please fix that.
     /// While you're at it, also add it to `Board.creatures`.
-    pub fn new_baby(parents: Vec<HLSoftBody<B>>, energy: f64, time: f64) -> Creature<B> {
-        let brain = B::recombination_infinite_parents(&parents);
+    pub fn new_baby(
+        parents: Vec<HLSoftBody<B>>,
+        energy: f64,
+        time: f64,
+        brain_context: &B::Context,
+    ) -> Creature<B> {
+        let brain = B::recombination_infinite_parents(&parents, brain_context);
         let base = Rock::new_from_parents(&parents, energy, time);
 
         Creature { base, brain }
@@ -50,10 +65,15 @@ impl<B: NeuralNet + RecombinationInfinite> Creature<B> {
 impl<B> Creature<B> {
     // The `Creature` version of `apply_motions`, this is different to the `Rock` version.
     pub fn apply_motions(&mut self, time_step: f64, terrain: &Terrain, board_size: BoardSize) {
-        if self.is_on_water(terrain, board_size) {
-            let energy_to_lose = time_step * SWIM_ENERGY * self.get_energy();
-            self.lose_energy(energy_to_lose);
-        }
+        // A body built for swimming saves energy on water but wastes it on land.
+        let swim_efficiency = self.get_body_genome().swim_efficiency;
+        let share = if self.is_on_water(terrain, board_size) {
+            1.0 - swim_efficiency
+        } else {
+            swim_efficiency
+        };
+        let energy_to_lose = time_step * SWIM_ENERGY * share * self.get_energy();
+        self.lose_energy(energy_to_lose);
 
         self.base.apply_motions(time_step, board_size);
     }
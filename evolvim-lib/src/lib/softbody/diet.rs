@@ -0,0 +1,52 @@
+use crate::constants::*;
+use crate::terrain::nutrient::{Nutrient, Nutrients};
+
+/// Remembers what a creature has eaten recently.
+///
+/// Only creatures eating every `Nutrient` in the right proportions (see `DIET_REQUIREMENT`)
+/// get all the energy out of their food.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Diet {
+    intake: Nutrients,
+}
+
+impl Default for Diet {
+    /// Starts out with a perfectly balanced diet.
+    fn default() -> Self {
+        Diet {
+            intake: Nutrients::new(DIET_REQUIREMENT),
+        }
+    }
+}
+
+impl Diet {
+    /// Records a meal, older meals are slowly forgotten.
+    pub fn record(&mut self, eaten: &Nutrients, time_step: f64) {
+        self.intake.scale((-time_step / DIET_MEMORY).exp());
+        self.intake.add(eaten);
+    }
+
+    /// Returns the share (between 0 and 1) of the given `Nutrient` in the recent diet.
+    pub fn get_share(&self, nutrient: Nutrient) -> f64 {
+        let total = self.intake.total();
+
+        if total > 0.0 {
+            self.intake[nutrient] / total
+        } else {
+            DIET_REQUIREMENT[nutrient.index()]
+        }
+    }
+
+    /// Returns the fraction of energy this creature gets out of its food.
+    ///
+    /// This is 1 for a perfectly balanced diet and `DIET_BASE_EFFICIENCY` when eating a single `Nutrient`
+    /// that isn't needed at all.
+    pub fn get_efficiency(&self) -> f64 {
+        let balance: f64 = Nutrient::ALL
+            .iter()
+            .map(|n| self.get_share(*n).min(DIET_REQUIREMENT[n.index()]))
+            .sum();
+
+        DIET_BASE_EFFICIENCY + (1.0 - DIET_BASE_EFFICIENCY) * balance
+    }
+}
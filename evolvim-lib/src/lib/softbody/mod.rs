@@ -1,10 +1,14 @@
 use self::constants::*;
 use super::*;
 
+mod body;
 mod creature;
+mod diet;
 mod rock;
 
+pub use self::body::*;
 pub use self::creature::*;
+pub use self::diet::*;
 pub use self::rock::*;
 use std::cell::{Ref, RefMut};
 
@@ -203,9 +207,12 @@ impl<B> HLSoftBody<B> {
 
             for _i in 0..PIECES {
                 let tile_pos = self_deref.get_random_covered_tile(board_size);
-                terrain.add_food_or_nothing_at(tile_pos, self_deref.get_energy() / PIECES as f64);
-
-                terrain.update_at(tile_pos, time, climate);
+                terrain.add_food_or_nothing_at(
+                    tile_pos,
+                    self_deref.get_energy() / PIECES as f64,
+                    time,
+                    climate,
+                );
             }
         }
 
@@ -219,7 +226,7 @@ impl<B: Intentions> HLSoftBody<B> {
 
         temp.get_energy() > SAFE_SIZE
             && temp.brain.wants_birth() > 0.0
-            && temp.get_age(time) > MATURE_AGE
+            && temp.get_age(time) > temp.get_mature_age()
     }
 }
 
@@ -231,12 +238,15 @@ impl<B: NeuralNet + Intentions + RecombinationInfinite> HLSoftBody<B> {
         time: f64,
         sbip: &mut SoftBodiesInPositions<B>,
         board_size: BoardSize,
+        brain_context: &B::Context,
     ) -> Option<HLSoftBody<B>> {
         if self.wants_primary_birth(time) {
             let self_px = self.borrow().get_px();
             let self_py = self.borrow().get_py();
             let self_radius = self.borrow().get_radius();
 
+            let architecture = self.borrow().brain.get_architecture();
+
             let mut colliders = self.borrow().get_colliders(sbip);
 
             // Remove self
@@ -251,6 +261,7 @@ impl<B: NeuralNet + Intentions + RecombinationInfinite> HLSoftBody<B> {
 
                     c.brain.wants_help_birth() > -1.0 // must be a willing creature
                             && dist < combined_radius // must be close enough
+                            && c.brain.get_architecture() == architecture // must have the same kind of brain
 
                     // TODO: find out if this addition to the Processing code works
                     // && c.get_age(time) >= MATURE_AGE // creature must be old enough
@@ -275,7 +286,7 @@ impl<B: NeuralNet + Intentions + RecombinationInfinite> HLSoftBody<B> {
                     c.lose_energy(energy_to_lose);
                 });
 
-                let sb = HLSoftBody::from(Creature::new_baby(parents, energy, time));
+                let sb = HLSoftBody::from(Creature::new_baby(parents, energy, time, brain_context));
 
                 sb.set_sbip(sbip, board_size);
                 sb.set_sbip(sbip, board_size);
@@ -296,13 +307,16 @@ impl<B: NeuralNet + Intentions + RecombinationInfinite> HLSoftBody<B> {
 pub type SoftBody<B = Brain> = Creature<B>;
 
 // Here are all the functions only applicable to `Creature`s.
-impl<B> SoftBody<B> {
-    /// Performs the energy requirement to keep living.
-    pub fn metabolize(&mut self, time_step: f64, time: f64) {
+impl<B: NeuralNet> SoftBody<B> {
+    /// Performs the energy requirement to keep living, `brain_cost` is the energy per year for every unit of brain.
+    ///
+    /// Older creatures need more energy, how fast they age depends on their lifespan.
+    pub fn metabolize(&mut self, time_step: f64, time: f64, brain_cost: f64) {
         // TODO: fix ugly code.
-        let age = AGE_FACTOR * (time - self.get_birth_time());
+        let age = AGE_FACTOR * (time - self.get_birth_time()) / self.get_body_genome().lifespan;
+        let brain = brain_cost * self.brain.metabolic_cost();
         let creature = self;
-        let energy_to_lose = creature.get_energy() * METABOLISM_ENERGY * age * time_step;
+        let energy_to_lose = (creature.get_energy() * METABOLISM_ENERGY * age + brain) * time_step;
         creature.lose_energy(energy_to_lose);
 
         // Creature should die if it doesn't have enough energy, this is done by `Board`.
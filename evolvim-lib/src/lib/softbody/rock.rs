@@ -2,7 +2,7 @@ extern crate rand;
 
 use self::rand::Rng;
 
-use super::HLSoftBody;
+use super::{BodyGenome, Diet, HLSoftBody};
 use crate::board::{BoardCoordinate, BoardPreciseCoordinate, BoardSize};
 use crate::climate::Climate;
 use crate::constants::*;
@@ -28,7 +28,8 @@ pub struct Rock {
     vr: f64,
     // Energy
     energy: f64,
-    density: f64,
+    // Heritable traits
+    body: BodyGenome,
     // Soft Bodies In Positions
     sbip_min_x: usize,
     sbip_min_y: usize,
@@ -43,9 +44,11 @@ pub struct Rock {
     birth_time: f64,
     // Miscellanious
     mouth_hue: f64,
+    diet: Diet,
 }
 
 impl Rock {
+    /// Returns a body at a random place, with the default `BodyGenome` apart from its `density`.
     pub fn new_random(board_size: BoardSize, density: f64, energy: f64, time: f64) -> Self {
         let (board_width, board_height) = board_size;
 
@@ -64,7 +67,10 @@ impl Rock {
             vr: 0.0,
 
             energy,
-            density,
+            body: BodyGenome {
+                density,
+                ..BodyGenome::default()
+            },
 
             sbip_min_x: 0,
             sbip_min_y: 0,
@@ -79,9 +85,12 @@ impl Rock {
             birth_time: time,
 
             mouth_hue,
+            diet: Diet::default(),
         }
     }
 
+    /// The `BodyGenome` of the child is that of its parents with a bit of mutation.
+    ///
     /// TODO: prevent px and py from being directly on top of the parent.
     pub fn new_from_parents<B>(parents: &Vec<HLSoftBody<B>>, energy: f64, time: f64) -> Rock {
         let parent_amount = parents.len();
@@ -101,7 +110,9 @@ impl Rock {
             acc + parent.borrow().mouth_hue / parent_amount as f64
         });
 
-        let density = parents[0].borrow().density;
+        let parents_borrowed: Vec<_> = parents.iter().map(|p| p.borrow()).collect();
+        let parent_bodies: Vec<&BodyGenome> = parents_borrowed.iter().map(|p| &p.body).collect();
+        let body = BodyGenome::new_from_parents(&parent_bodies);
 
         Rock {
             px,
@@ -113,7 +124,7 @@ impl Rock {
             vr: 0.0,
 
             energy,
-            density,
+            body,
 
             sbip_min_x: 0,
             sbip_min_y: 0,
@@ -128,6 +139,7 @@ impl Rock {
             birth_time: time,
 
             mouth_hue,
+            diet: Diet::default(),
         }
     }
 
@@ -138,7 +150,7 @@ impl Rock {
 
     /// Return this body's mass, used for physics, etc.
     pub fn get_mass(&self) -> f64 {
-        return self.energy / ENERGY_DENSITY * self.density;
+        return self.energy / ENERGY_DENSITY * self.body.density;
     }
 
     /// Returns the total velocity.
@@ -155,7 +167,8 @@ impl Rock {
         time_step: f64,
         time: f64,
         climate: &Climate,
-        tile: &mut crate::terrain::tile::Tile,
+        terrain: &mut Terrain,
+        pos: BoardCoordinate,
     ) {
         let amount = attempted_amount
             / (1.0 + self.get_total_velocity() * EAT_WHILE_MOVING_INEFFICIENCY_MULTIPLIER);
@@ -164,26 +177,28 @@ impl Rock {
             // TODO: implement vomiting.
         } else {
             // Eat
-            let food_level = tile.get_food_level();
-
-            let mut food_to_eat = food_level * (1.0 - (1.0 - EAT_SPEED).powf(amount * time_step));
-            food_to_eat = food_to_eat.min(food_level);
+            let fraction = 1.0 - (1.0 - EAT_SPEED).powf(amount * time_step);
             // Remove eaten food from tile.
-            tile.remove_food(food_to_eat);
-            tile.update(time, climate);
+            let eaten = terrain.remove_food_fraction_at(pos, fraction, time, climate);
 
-            let multiplier = tile
-                .get_food_multiplier(self.get_mouth_hue())
-                .unwrap_or(0.0);
+            self.diet.record(&eaten, time_step);
+            let food_to_eat = eaten.total();
+
+            let sensitivity = self.body.food_sensitivity;
+            let multiplier = terrain
+                .get_tile_at(pos)
+                .get_food_multiplier(self.get_mouth_hue(), sensitivity);
             if multiplier < 0.0 {
                 // Poison
                 self.lose_energy(food_to_eat * -multiplier);
             } else {
-                // Healthy food
-                self.add_energy(food_to_eat * multiplier);
+                // Healthy food, only fully digested with a balanced diet
+                self.add_energy(food_to_eat * multiplier * self.diet.get_efficiency());
             }
 
-            self.lose_energy(attempted_amount * EAT_ENERGY * time_step);
+            // Digesting food of many hues takes more effort.
+            let effort = sensitivity / FOOD_SENSITIVITY;
+            self.lose_energy(attempted_amount * EAT_ENERGY * effort * time_step);
         }
     }
 
@@ -195,10 +210,9 @@ impl Rock {
         sbip: &SoftBodiesInPositions<B>,
         self_pointer: HLSoftBody<B>,
     ) {
-        use super::MATURE_AGE;
         use crate::sbip::SoftBodyBucket;
 
-        if amount > 0.0 && self.get_age(time) >= MATURE_AGE {
+        if amount > 0.0 && self.get_age(time) >= self.get_mature_age() {
             self.lose_energy(amount * time_step * FIGHT_ENERGY);
 
             let self_x = self.get_px();
@@ -224,18 +238,19 @@ impl Rock {
 
     /// Accelerate
     ///
-    /// Costs energy.
+    /// Costs energy, more for bodies with a higher top speed. Bodies that turn well accelerate worse.
     pub fn accelerate(&mut self, amount: f64, time_step: f64) {
-        let multiplier = amount * time_step / self.get_mass();
+        let multiplier = amount * time_step / self.get_mass() / self.body.turn_efficiency;
         self.vx += self.rotation.cos() * multiplier;
         self.vy += self.rotation.sin() * multiplier;
 
+        let speed_cost = self.body.max_speed.min(MAX_FINITE_SPEED) / MAX_FINITE_SPEED;
         if amount >= 0.0 {
             // Moving forward
-            self.lose_energy(amount * time_step * ACCELERATION_ENERGY);
+            self.lose_energy(amount * time_step * ACCELERATION_ENERGY * speed_cost);
         } else {
             // Moving backward
-            self.lose_energy(amount * time_step * ACCELERATION_BACK_ENERGY);
+            self.lose_energy(amount * time_step * ACCELERATION_BACK_ENERGY * speed_cost);
         }
     }
 
@@ -243,7 +258,7 @@ impl Rock {
     ///
     /// Costs energy.
     pub fn turn(&mut self, amount: f64, time_step: f64) {
-        self.vr += 0.04 * amount * time_step / self.get_mass();
+        self.vr += 0.04 * amount * time_step * self.body.turn_efficiency / self.get_mass();
 
         // Call `abs()` because we can turn both ways.
         let energy_to_lose = (amount * self.energy * time_step * TURN_ENERGY).abs();
@@ -264,6 +279,13 @@ impl Rock {
         self.vx *= 0f64.max(1.0 - FRICTION / self.get_mass());
         self.vy *= 0f64.max(1.0 - FRICTION / self.get_mass());
         self.vr *= 0f64.max(1.0 - FRICTION / self.get_mass());
+
+        // Bodies can't go faster than their top speed.
+        let speed = self.get_total_velocity();
+        if speed > self.body.max_speed {
+            self.vx *= self.body.max_speed / speed;
+            self.vy *= self.body.max_speed / speed;
+        }
     }
 
     pub fn moved_between_tiles(&self) -> bool {
@@ -303,8 +325,7 @@ impl Rock {
     pub fn is_on_water(&self, terrain: &Terrain, board_size: BoardSize) -> bool {
         // TODO: determine whether this is desirable and maybe come up with a better system.
         let pos = self.get_random_covered_tile(board_size);
-        let tile = terrain.get_tile_at(pos);
-        return tile.is_water();
+        return terrain.is_water_at(pos);
     }
 }
 
@@ -395,6 +416,11 @@ impl Rock {
     pub fn set_mouth_hue(&mut self, value: f64) {
         self.mouth_hue = value.min(1.0).max(0.0);
     }
+
+    /// Replaces the heritable traits of this body, its children inherit the new ones.
+    pub fn set_body_genome(&mut self, body: BodyGenome) {
+        self.body = body;
+    }
 }
 
 // Here are all the functions to simply get a property.
@@ -423,6 +449,10 @@ impl Rock {
         return self.mouth_hue;
     }
 
+    pub fn get_diet(&self) -> &Diet {
+        &self.diet
+    }
+
     /// Returns the time when this creature was born.
     pub fn get_birth_time(&self) -> f64 {
         return self.birth_time;
@@ -434,6 +464,16 @@ impl Rock {
     pub fn get_age(&self, time: f64) -> f64 {
         return time - self.birth_time;
     }
+
+    /// Returns the age from which this creature can fight and give birth, this depends on its lifespan.
+    pub fn get_mature_age(&self) -> f64 {
+        super::MATURE_AGE * self.body.lifespan
+    }
+
+    /// Returns the heritable traits of this body.
+    pub fn get_body_genome(&self) -> &BodyGenome {
+        &self.body
+    }
 }
 
 /// Checks if the center is inside of the world, possibly corrects it and returns it.
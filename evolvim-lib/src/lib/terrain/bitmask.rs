@@ -0,0 +1,40 @@
+//! Contains `BitMask`, a compact list of booleans used to remember which tiles are water.
+
+const BITS: usize = 64;
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct BitMask {
+    bits: Vec<u64>,
+    len: usize,
+}
+
+impl BitMask {
+    /// Creates a mask of `len` bits, all set to false.
+    pub fn new(len: usize) -> Self {
+        BitMask {
+            bits: vec![0; (len + BITS - 1) / BITS],
+            len,
+        }
+    }
+
+    pub fn get(&self, index: usize) -> bool {
+        debug_assert!(index < self.len);
+
+        self.bits[index / BITS] & (1 << (index % BITS)) != 0
+    }
+
+    pub fn set(&mut self, index: usize, value: bool) {
+        debug_assert!(index < self.len);
+
+        if value {
+            self.bits[index / BITS] |= 1 << (index % BITS);
+        } else {
+            self.bits[index / BITS] &= !(1 << (index % BITS));
+        }
+    }
+
+    /// Returns the amount of bits set to true.
+    pub fn count_ones(&self) -> usize {
+        self.bits.iter().map(|b| b.count_ones() as usize).sum()
+    }
+}
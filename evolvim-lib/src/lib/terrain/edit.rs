@@ -0,0 +1,243 @@
+//! Contains the public API for changing a `Terrain` after it has been generated.
+//!
+//! Every edit returns a `TerrainChange` holding the previous state of all tiles it touched,
+//! keep those in an `EditHistory` to undo them later.
+//!
+//! Edits never change the size of the `Terrain` so `SoftBodiesInPositions` and the positions of all creatures stay valid.
+
+use super::tile::Tile;
+use super::*;
+use std::collections::{HashMap, VecDeque};
+
+/// How far `TerrainEdit::Water` and `TerrainEdit::Land` put a tile below or above the current sea level.
+///
+/// This keeps edited tiles from flooding or drying out again as soon as the sea moves a little.
+const EDIT_SEA_MARGIN: f64 = 0.25;
+
+/// The maximum amount of changes an `EditHistory` remembers.
+const EDIT_HISTORY_LENGTH: usize = 100;
+
+/// Something to do with a single `Tile`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TerrainEdit {
+    /// Turn the tile into water by lowering it below the sea level.
+    Water,
+    /// Turn the tile into land by raising it above the sea level.
+    Land,
+    /// Set the fertility of a land tile, does nothing for water.
+    Fertility(f64),
+    /// Set the food type (hue) of the tile.
+    FoodType(f64),
+    /// Remove all food from the tile.
+    ClearFood,
+    /// Set how fast food grows on the tile, see `Tile::set_growth_multiplier`.
+    GrowthMultiplier(f64),
+}
+
+/// The previous state of every `Tile` changed by one or more edits, give it to `Terrain::undo` to restore them.
+#[derive(Default)]
+pub struct TerrainChange {
+    tiles: HashMap<BoardCoordinate, Tile>,
+}
+
+impl TerrainChange {
+    /// Remembers `tile` as the state of `pos`, unless an older state of `pos` is already known.
+    fn record(&mut self, pos: BoardCoordinate, tile: Tile) {
+        self.tiles.entry(pos).or_insert(tile);
+    }
+
+    /// Adds a later change to this one, so both can be undone at once.
+    ///
+    /// For tiles changed by both only the state before `self` is kept.
+    pub fn merge(&mut self, later: TerrainChange) {
+        for (pos, tile) in later.tiles {
+            self.record(pos, tile);
+        }
+    }
+
+    /// Returns the position and previous state of every changed `Tile`.
+    pub fn get_tiles(&self) -> impl Iterator<Item = (BoardCoordinate, &Tile)> {
+        self.tiles.iter().map(|(pos, tile)| (*pos, tile))
+    }
+
+    /// Returns true if no `Tile` was changed.
+    pub fn is_empty(&self) -> bool {
+        self.tiles.is_empty()
+    }
+}
+
+/// Remembers the latest changes to a `Terrain` so they can be undone.
+#[derive(Default)]
+pub struct EditHistory {
+    changes: VecDeque<TerrainChange>,
+}
+
+impl EditHistory {
+    /// Remembers `change`, forgets the oldest change if the history is full.
+    pub fn push(&mut self, change: TerrainChange) {
+        if change.is_empty() {
+            return;
+        }
+
+        if self.changes.len() == EDIT_HISTORY_LENGTH {
+            self.changes.pop_front();
+        }
+
+        self.changes.push_back(change);
+    }
+
+    /// Undoes the latest change, returns false if there was nothing to undo.
+    pub fn undo(&mut self, terrain: &mut Terrain) -> bool {
+        if let Some(change) = self.changes.pop_back() {
+            terrain.undo(change);
+
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.changes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+impl Terrain {
+    /// Applies `edit` to the `Tile` at `pos`.
+    pub fn edit(
+        &mut self,
+        pos: BoardCoordinate,
+        edit: TerrainEdit,
+        time: f64,
+        climate: &Climate,
+    ) -> TerrainChange {
+        let mut change = TerrainChange::default();
+        self.edit_and_record(pos, edit, time, climate, &mut change);
+
+        change
+    }
+
+    /// Applies `edit` to every `Tile` with its center within `radius` of `center`.
+    ///
+    /// The `Tile` directly under `center` is always changed, even for a radius of 0.
+    pub fn paint(
+        &mut self,
+        center: BoardPreciseCoordinate,
+        radius: f64,
+        edit: TerrainEdit,
+        time: f64,
+        climate: &Climate,
+    ) -> TerrainChange {
+        let mut change = TerrainChange::default();
+        let (cx, cy) = center.unpack();
+        let center_tile = BoardCoordinate::from(center.clone());
+
+        let min_x = (cx - radius).floor().max(0.0) as usize;
+        let min_y = (cy - radius).floor().max(0.0) as usize;
+        let max_x = ((cx + radius).ceil() as usize).min(self.get_width() - 1);
+        let max_y = ((cy + radius).ceil() as usize).min(self.get_height() - 1);
+
+        for x in min_x..=max_x {
+            for y in min_y..=max_y {
+                let dist = crate::softbody::distance(cx, cy, x as f64 + 0.5, y as f64 + 0.5);
+
+                if dist <= radius || (x, y) == center_tile {
+                    self.edit_and_record((x, y), edit, time, climate, &mut change);
+                }
+            }
+        }
+
+        change
+    }
+
+    /// Applies `edit` to the `Tile` at `start` and every `Tile` connected to it that is also water or also land.
+    pub fn fill(
+        &mut self,
+        start: BoardCoordinate,
+        edit: TerrainEdit,
+        time: f64,
+        climate: &Climate,
+    ) -> TerrainChange {
+        let mut change = TerrainChange::default();
+        let (width, height) = (self.get_width(), self.get_height());
+        let is_water = self.is_water_at(start);
+
+        let mut visited = vec![vec![false; height]; width];
+        let mut to_visit = vec![start];
+        visited[start.0][start.1] = true;
+
+        // First gather the region, editing while searching could change which tiles are water.
+        let mut region = Vec::new();
+        while let Some((x, y)) = to_visit.pop() {
+            region.push((x, y));
+
+            let neighbours = [
+                (x.wrapping_sub(1), y),
+                (x + 1, y),
+                (x, y.wrapping_sub(1)),
+                (x, y + 1),
+            ];
+            for &(nx, ny) in neighbours.iter() {
+                if nx < width
+                    && ny < height
+                    && !visited[nx][ny]
+                    && self.is_water_at((nx, ny)) == is_water
+                {
+                    visited[nx][ny] = true;
+                    to_visit.push((nx, ny));
+                }
+            }
+        }
+
+        for pos in region {
+            self.edit_and_record(pos, edit, time, climate, &mut change);
+        }
+
+        change
+    }
+
+    /// Restores all tiles in `change` to the state they were in before it was made.
+    pub fn undo(&mut self, change: TerrainChange) {
+        for (pos, tile) in change.tiles {
+            self.set_tile_at(pos, tile);
+        }
+    }
+
+    fn edit_and_record(
+        &mut self,
+        pos: BoardCoordinate,
+        edit: TerrainEdit,
+        time: f64,
+        climate: &Climate,
+        change: &mut TerrainChange,
+    ) {
+        let sea_level = self.sea_level;
+
+        // Let the tile catch up so the growth formulas don't get applied over the edit.
+        let mut tile = self.get_updated_tile_at(pos, time, climate);
+        change.record(pos, tile.clone());
+
+        match edit {
+            TerrainEdit::Water => {
+                if !tile.is_water() {
+                    tile.set_elevation(sea_level - EDIT_SEA_MARGIN, sea_level, time);
+                }
+            }
+            TerrainEdit::Land => {
+                if tile.is_water() {
+                    tile.set_elevation(sea_level + EDIT_SEA_MARGIN, sea_level, time);
+                }
+            }
+            TerrainEdit::Fertility(fertility) => tile.set_fertility(fertility),
+            TerrainEdit::FoodType(food_type) => tile.set_food_type(food_type),
+            TerrainEdit::ClearFood => tile.clear_food(time),
+            TerrainEdit::GrowthMultiplier(multiplier) => tile.set_growth_multiplier(multiplier),
+        }
+
+        self.set_tile_at(pos, tile);
+    }
+}
@@ -1,6 +1,6 @@
 //! Contains structs and functions useful for terrain.
 //!
-//! Here you will find `Terrain`, this struct contains a matrix of `Tile`s and its methods are frequently used.
+//! Here you will find `Terrain`, this struct contains all `Tile`s and its methods are frequently used.
 //! Speeding them up a little will certainly help performance!
 //!
 //! TODO: drastically improve documentation.
@@ -10,36 +10,87 @@
 extern crate noise;
 extern crate rand;
 
+mod bitmask;
+mod edit;
+pub mod nutrient;
 pub mod tile;
 
+pub use self::edit::{EditHistory, TerrainChange, TerrainEdit};
+
+use self::bitmask::BitMask;
 use self::noise::{NoiseFn, Point2, Seedable};
+use self::nutrient::Nutrients;
 use self::tile::Tile;
+use super::constants::{DEFAULT_SEA_LEVEL, SEA_LEVEL_UPDATE_THRESHOLD};
 use super::*;
 
 /// Contains the terrain of the world.
 ///
-/// TODO: possibly speed up with `nalgebra`.
+/// Tiles are stored as a struct of arrays: every property has its own column with a value for every tile,
+/// the tile at `(x, y)` can be found at index `x * height + y` of every column.
+///
+/// Tiles are only updated when they are needed, e.g. when a creature eats from them or when they are drawn.
+/// The growth of food doesn't depend on how often a tile is updated so this doesn't change the outcome.
 #[derive(Serialize, Deserialize)]
 pub struct Terrain {
-    tiles: Vec<Vec<Tile>>,
+    width: usize,
+    height: usize,
+
+    elevation: Vec<f64>,
+    fertility: Vec<f64>,
+    nutrients: Vec<Nutrients>,
+    food_type: Vec<f64>,
+    growth_multiplier: Vec<f64>,
+    last_update_time: Vec<f64>,
+    water: BitMask,
+
+    /// The sea level the tiles were last flooded or dried out with.
+    sea_level: f64,
 }
 
 impl Terrain {
     #[cfg(multithreading)]
     pub fn update_all(&mut self, time: f64, climate: &Climate) {
         extern crate rayon;
+        use self::rayon::prelude::*;
+
+        let sea_level = climate.get_sea_level();
+        let elevation = &self.elevation;
+        let fertility = &self.fertility;
+        let growth_multiplier = &self.growth_multiplier;
+        let water = &self.water;
 
-        self.tiles.par_iter_mut().flatten().for_each(|t| {
-            t.update(time, climate);
-        })
+        self.nutrients
+            .par_iter_mut()
+            .zip(self.last_update_time.par_iter_mut())
+            .enumerate()
+            .for_each(|(i, (nutrients, last_update_time))| {
+                let is_water = water.get(i);
+                let fertility = if is_water {
+                    tile::get_richness(elevation[i], sea_level)
+                } else {
+                    fertility[i]
+                };
+
+                tile::catch_up(
+                    nutrients,
+                    last_update_time,
+                    time,
+                    growth_multiplier[i],
+                    fertility,
+                    is_water,
+                    climate,
+                );
+            })
     }
     #[cfg(not(multithreading))]
     pub fn update_all(&mut self, time: f64, climate: &Climate) {
-        self.tiles.iter_mut().flatten().for_each(|t| {
-            t.update(time, climate);
-        })
+        for i in 0..self.nutrients.len() {
+            self.update_index(i, time, climate);
+        }
     }
 
+    /// Updates every `Tile` in the given ranges, e.g. the part of the board that is visible.
     pub fn update_all_at(
         &mut self,
         time: f64,
@@ -49,13 +100,87 @@ impl Terrain {
     ) {
         for x in x_range {
             for y in y_range.clone() {
-                self.tiles[x][y].update(time, climate);
+                let i = self.index((x, y));
+                self.update_index(i, time, climate);
             }
         }
     }
 
-    /// Gets a mutable reference to that tile, this function should be used as little as possible.
-    pub fn get_tile_at_mut(&mut self, pos: BoardCoordinate) -> &mut Tile {
+    /// Returns a copy of that tile as it was last updated.
+    ///
+    /// Use `get_updated_tile_at` to see how much food there is right now.
+    pub fn get_tile_at(&self, pos: BoardCoordinate) -> Tile {
+        let i = self.index(pos);
+
+        Tile {
+            elevation: self.elevation[i],
+            fertility: self.fertility[i],
+            nutrients: self.nutrients[i].clone(),
+            food_type: self.food_type[i],
+            growth_multiplier: self.growth_multiplier[i],
+            water: self.water.get(i),
+
+            last_update_time: self.last_update_time[i],
+        }
+    }
+
+    /// Returns a copy of that tile as it is at `time`, without changing the `Terrain`.
+    pub fn get_updated_tile_at(&self, pos: BoardCoordinate, time: f64, climate: &Climate) -> Tile {
+        let mut tile = self.get_tile_at(pos);
+        tile.update(time, climate);
+
+        tile
+    }
+
+    /// Overwrites the tile at `pos` with `tile`.
+    fn set_tile_at(&mut self, pos: BoardCoordinate, tile: Tile) {
+        let i = self.index(pos);
+
+        self.elevation[i] = tile.elevation;
+        self.fertility[i] = tile.fertility;
+        self.nutrients[i] = tile.nutrients;
+        self.food_type[i] = tile.food_type;
+        self.growth_multiplier[i] = tile.growth_multiplier;
+        self.water.set(i, tile.water);
+        self.last_update_time[i] = tile.last_update_time;
+    }
+
+    /// Returns true if the tile at `pos` is water, cheaper than `get_tile_at(pos).is_water()`.
+    pub fn is_water_at(&self, pos: BoardCoordinate) -> bool {
+        self.water.get(self.index(pos))
+    }
+
+    /// Returns the amount of tiles that are water.
+    pub fn get_water_tile_count(&self) -> usize {
+        self.water.count_ones()
+    }
+
+    pub fn update_at(&mut self, pos: BoardCoordinate, time: f64, climate: &Climate) {
+        let i = self.index(pos);
+        self.update_index(i, time, climate);
+    }
+
+    fn update_index(&mut self, i: usize, time: f64, climate: &Climate) {
+        let water = self.water.get(i);
+        let fertility = if water {
+            tile::get_richness(self.elevation[i], climate.get_sea_level())
+        } else {
+            self.fertility[i]
+        };
+
+        tile::catch_up(
+            &mut self.nutrients[i],
+            &mut self.last_update_time[i],
+            time,
+            self.growth_multiplier[i],
+            fertility,
+            water,
+            climate,
+        );
+    }
+
+    /// Returns the index of that tile in every column.
+    fn index(&self, pos: BoardCoordinate) -> usize {
         let (x, y) = pos;
 
         assert!(
@@ -69,32 +194,48 @@ impl Terrain {
             y
         );
 
-        return &mut self.tiles[x][y];
+        x * self.height + y
     }
 
-    /// Gets a reference to that tile, this function should be used as little as possible.
+    /// Floods every `Tile` below `sea_level` and dries out every `Tile` above it.
     ///
-    /// Usage of this function is however encouraged inside the `terrain` module.
-    pub fn get_tile_at(&self, pos: BoardCoordinate) -> &Tile {
-        let (x, y) = pos;
+    /// Returns the amount of tiles that changed.
+    pub fn set_sea_level(&mut self, sea_level: f64, time: f64, climate: &Climate) -> usize {
+        self.sea_level = sea_level;
 
-        assert!(
-            x < self.get_width(),
-            "There is no `Tile` at the given x coordinate: {}.",
-            x
-        );
-        assert!(
-            y < self.get_height(),
-            "There is no `Tile` at the given y coordinate: {}.",
-            y
-        );
+        let mut changed = 0;
+        for x in 0..self.width {
+            for y in 0..self.height {
+                let i = self.index((x, y));
+                if (self.elevation[i] < sea_level) == self.water.get(i) {
+                    continue;
+                }
+
+                // Let the tile catch up first, the growth formulas assume it didn't change type in the meantime.
+                self.update_index(i, time, climate);
 
-        return &self.tiles[x][y];
+                let mut tile = self.get_tile_at((x, y));
+                tile.set_sea_level(sea_level, time);
+                self.set_tile_at((x, y), tile);
+
+                changed += 1;
+            }
+        }
+
+        changed
     }
 
-    pub fn update_at(&mut self, pos: BoardCoordinate, time: f64, climate: &Climate) {
-        let (x, y) = pos;
-        self.tiles[x][y].update(time, climate);
+    /// Calls `set_sea_level` if the sea level of `climate` differs enough from the current one.
+    pub fn follow_sea_level(&mut self, time: f64, climate: &Climate) {
+        let sea_level = climate.get_sea_level();
+
+        if (sea_level - self.sea_level).abs() > SEA_LEVEL_UPDATE_THRESHOLD {
+            self.set_sea_level(sea_level, time, climate);
+        }
+    }
+
+    pub fn get_sea_level(&self) -> f64 {
+        self.sea_level
     }
 
     pub fn generate_perlin(board_size: BoardSize, step_size: f64) -> Self {
@@ -106,11 +247,34 @@ impl Terrain {
         return Terrain::generate_terrain_with_noise(noise_generator, board_size, step_size);
     }
 
-    /// Tries to add `food` to the food level of that `Tile`.
+    /// Adds `food` to the main nutrient of that `Tile`, after letting it catch up to `time`.
+    pub fn add_food_or_nothing_at(
+        &mut self,
+        pos: BoardCoordinate,
+        food: f64,
+        time: f64,
+        climate: &Climate,
+    ) {
+        let i = self.index(pos);
+        self.update_index(i, time, climate);
+
+        tile::add_main_food(&mut self.nutrients[i], self.water.get(i), food);
+    }
+
+    /// Removes the given fraction of every nutrient of that `Tile`, after letting it catch up to `time`.
     ///
-    /// Does nothing for water tiles.
-    pub fn add_food_or_nothing_at(&mut self, pos: BoardCoordinate, food: f64) {
-        self.tiles[pos.0][pos.1].add_food_or_nothing(food);
+    /// Returns how much of each nutrient was removed.
+    pub fn remove_food_fraction_at(
+        &mut self,
+        pos: BoardCoordinate,
+        fraction: f64,
+        time: f64,
+        climate: &Climate,
+    ) -> Nutrients {
+        let i = self.index(pos);
+        self.update_index(i, time, climate);
+
+        self.nutrients[i].take_fraction(fraction)
     }
 
     fn generate_terrain_with_noise<N: NoiseFn<Point2<f64>>>(
@@ -119,11 +283,25 @@ impl Terrain {
         step_size: f64,
     ) -> Self {
         let (board_width, board_height) = board_size;
+        let sea_level = DEFAULT_SEA_LEVEL;
+        let amount = board_width * board_height;
+
+        let mut terrain = Terrain {
+            width: board_width,
+            height: board_height,
 
-        let mut tiles = Vec::with_capacity(board_width);
+            elevation: vec![0.0; amount],
+            fertility: vec![0.0; amount],
+            nutrients: vec![Nutrients::default(); amount],
+            food_type: vec![0.0; amount],
+            growth_multiplier: vec![1.0; amount],
+            last_update_time: vec![0.0; amount],
+            water: BitMask::new(amount),
+
+            sea_level,
+        };
 
         for x in 0..board_width {
-            tiles.push(Vec::with_capacity(board_height));
             for y in 0..board_height {
                 let big_force = (y as f64 / board_height as f64).sqrt();
 
@@ -145,20 +323,23 @@ impl Terrain {
                     - 0.4;
 
                 climate_type = climate_type.max(0.0).min(0.8);
-                tiles[x].push(Tile::new(fertility, climate_type));
+
+                // Everything above a fertility of 1 used to be water.
+                let elevation = 1.0 - fertility;
+                terrain.set_tile_at((x, y), Tile::new(elevation, climate_type, sea_level));
             }
         }
 
         // Return the generated terrain.
-        Terrain { tiles }
+        terrain
     }
 
     pub fn get_width(&self) -> usize {
-        return self.tiles.len();
+        return self.width;
     }
 
     pub fn get_height(&self) -> usize {
-        return self.tiles[0].len();
+        return self.height;
     }
 }
 
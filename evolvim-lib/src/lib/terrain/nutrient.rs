@@ -0,0 +1,157 @@
+//! Contains the different kinds of food that can grow on a `Tile`.
+//!
+//! Every `Tile` keeps track of a level for each `Nutrient`, these all grow and decay at their own pace.
+//! Creatures eat all of them at once but need a balanced diet to get the most out of it, see `Diet`.
+
+use crate::constants::*;
+use std::ops::{Index, IndexMut};
+
+/// The amount of different nutrients, equal to `Nutrient::ALL.len()`.
+pub const AMOUNT_NUTRIENTS: usize = 3;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Nutrient {
+    /// The common food of land tiles, grows fast in summer and dies off in winter.
+    Plant,
+    /// Grows slowly on land but survives the winter a lot better than `Plant`.
+    Seed,
+    /// The only thing growing on water tiles.
+    Algae,
+}
+
+impl Nutrient {
+    pub const ALL: [Nutrient; AMOUNT_NUTRIENTS] =
+        [Nutrient::Plant, Nutrient::Seed, Nutrient::Algae];
+
+    pub fn index(self) -> usize {
+        match self {
+            Nutrient::Plant => 0,
+            Nutrient::Seed => 1,
+            Nutrient::Algae => 2,
+        }
+    }
+
+    pub fn get_name(self) -> &'static str {
+        match self {
+            Nutrient::Plant => "Plant",
+            Nutrient::Seed => "Seed",
+            Nutrient::Algae => "Algae",
+        }
+    }
+
+    /// Returns true if this nutrient grows on water instead of on land.
+    pub fn grows_on_water(self) -> bool {
+        match self {
+            Nutrient::Algae => true,
+            Nutrient::Plant | Nutrient::Seed => false,
+        }
+    }
+
+    pub fn get_growth_rate(self) -> f64 {
+        match self {
+            Nutrient::Plant => FOOD_GROWTH_RATE,
+            Nutrient::Seed => SEED_GROWTH_RATE,
+            Nutrient::Algae => ALGAE_GROWTH_RATE,
+        }
+    }
+
+    pub fn get_decay_rate(self) -> f64 {
+        match self {
+            Nutrient::Plant => FOOD_GROWTH_RATE,
+            Nutrient::Seed => SEED_DECAY_RATE,
+            Nutrient::Algae => ALGAE_DECAY_RATE,
+        }
+    }
+
+    /// The level this nutrient grows towards, it can only get higher when food is added (e.g. by a dying creature).
+    pub fn get_max_level(self) -> f64 {
+        match self {
+            Nutrient::Plant => MAX_GROWTH_LEVEL,
+            Nutrient::Seed => MAX_SEED_LEVEL,
+            Nutrient::Algae => MAX_ALGAE_LEVEL,
+        }
+    }
+}
+
+/// A value for every `Nutrient`, index it with a `Nutrient`.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Nutrients([f64; AMOUNT_NUTRIENTS]);
+
+impl Index<Nutrient> for Nutrients {
+    type Output = f64;
+
+    fn index(&self, nutrient: Nutrient) -> &f64 {
+        &self.0[nutrient.index()]
+    }
+}
+
+impl IndexMut<Nutrient> for Nutrients {
+    fn index_mut(&mut self, nutrient: Nutrient) -> &mut f64 {
+        &mut self.0[nutrient.index()]
+    }
+}
+
+impl Nutrients {
+    pub fn new(values: [f64; AMOUNT_NUTRIENTS]) -> Self {
+        Nutrients(values)
+    }
+
+    /// Returns the sum of all nutrients.
+    pub fn total(&self) -> f64 {
+        self.0.iter().sum()
+    }
+
+    /// Iterates over every `Nutrient` and its value.
+    pub fn iter<'a>(&'a self) -> impl Iterator<Item = (Nutrient, f64)> + 'a {
+        Nutrient::ALL.iter().map(move |n| (*n, self[*n]))
+    }
+
+    /// Multiplies every value with `factor`.
+    pub fn scale(&mut self, factor: f64) {
+        for v in self.0.iter_mut() {
+            *v *= factor;
+        }
+    }
+
+    /// Adds every value of `other` to the matching value of `self`.
+    pub fn add(&mut self, other: &Nutrients) {
+        for (v, o) in self.0.iter_mut().zip(other.0.iter()) {
+            *v += o;
+        }
+    }
+
+    /// Removes the given fraction of every nutrient and returns how much was removed.
+    pub fn take_fraction(&mut self, fraction: f64) -> Nutrients {
+        let fraction = fraction.max(0.0).min(1.0);
+        let mut taken = Nutrients::default();
+
+        for n in Nutrient::ALL.iter() {
+            taken[*n] = self[*n] * fraction;
+            self[*n] -= taken[*n];
+        }
+
+        taken
+    }
+
+    /// Lets every nutrient that grows on this kind of tile grow or decay according to `growth_change`.
+    ///
+    /// This is the growth curve carykh used for the single food level, every `Nutrient` has its own rates.
+    pub fn grow(&mut self, growth_change: f64, fertility: f64, on_water: bool) {
+        for n in Nutrient::ALL
+            .iter()
+            .filter(|n| n.grows_on_water() == on_water)
+        {
+            let level = self[*n];
+
+            if growth_change <= 0.0 {
+                let food_to_remove = level - level * (growth_change * n.get_decay_rate()).exp();
+                self[*n] = 0f64.max(level - food_to_remove);
+            } else if level < n.get_max_level() {
+                let new_dist_to_max = (n.get_max_level() - level)
+                    * (-growth_change * fertility * n.get_growth_rate()).exp();
+
+                self[*n] = n.get_max_level() - new_dist_to_max;
+            }
+        }
+    }
+}
@@ -1,194 +1,295 @@
 use super::constants::*;
+use super::nutrient::{Nutrient, Nutrients};
 use super::*;
 
-#[derive(Serialize, Deserialize)]
-pub enum Tile {
-    Water,
-    Land(LandTile),
+/// A copy of everything the `Terrain` knows about a single tile.
+///
+/// The `Terrain` stores its tiles as a struct of arrays, use `Terrain::get_tile_at` to get a `Tile`
+/// and the methods on `Terrain` to change one.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Tile {
+    pub(super) elevation: f64,
+    /// How well plants grow here, only used on land.
+    pub(super) fertility: f64,
+    pub(super) nutrients: Nutrients,
+    pub(super) food_type: f64,
+    pub(super) growth_multiplier: f64,
+    pub(super) water: bool,
+
+    pub(super) last_update_time: f64,
 }
 
 impl Tile {
-    pub fn new(fertility: f64, food_type: f64) -> Self {
-        if fertility > 1.0 {
-            // Tile is water
-            Tile::Water
+    /// Creates a water tile if `elevation` is below `sea_level` and a land tile otherwise.
+    ///
+    /// Low land is the most fertile, anything higher than 1 is barren.
+    /// Land begins with the `Plant` level set to its fertility, water with the `Algae` level set to its richness.
+    pub fn new(elevation: f64, food_type: f64, sea_level: f64) -> Self {
+        let water = elevation < sea_level;
+        let fertility = get_land_fertility(elevation);
+        let mut nutrients = Nutrients::default();
+        if water {
+            nutrients[Nutrient::Algae] = get_richness(elevation, sea_level);
         } else {
-            // Tile is land
-            let t = LandTile::new(fertility.max(0.0), food_type);
+            nutrients[Nutrient::Plant] = fertility;
+        }
+
+        Tile {
+            elevation,
+            fertility,
+            nutrients,
+            food_type,
+            growth_multiplier: 1.0,
+            water,
 
-            Tile::Land(t)
+            last_update_time: 0.0,
         }
     }
 
-    pub fn is_water(&self) -> bool {
-        match self {
-            Tile::Water => true,
-            Tile::Land(_) => false,
+    /// Floods or dries out this tile if the sea has risen above or sunk below it.
+    ///
+    /// All food on the tile is lost when this happens, its fertility and growth multiplier are kept.
+    /// Returns true if the tile changed.
+    pub fn set_sea_level(&mut self, sea_level: f64, time: f64) -> bool {
+        if (self.elevation < sea_level) == self.water {
+            return false;
         }
+
+        let fertility = self.fertility;
+        let growth_multiplier = self.growth_multiplier;
+        *self = Tile::new(self.elevation, self.food_type, sea_level);
+        self.clear_food(time);
+        self.fertility = fertility;
+        self.growth_multiplier = growth_multiplier;
+
+        true
     }
 
-    /// Get the `food_level` of this `Tile`, returns 0 if it is water.
+    pub fn is_water(&self) -> bool {
+        self.water
+    }
+
+    /// Get the total `food_level` of this `Tile`, i.e. the sum of all its nutrients.
     pub fn get_food_level(&self) -> f64 {
-        match self {
-            Tile::Water => 0.0,
-            Tile::Land(t) => t.food_level,
+        self.nutrients.total()
+    }
+
+    /// Get the level of a single `Nutrient` of this `Tile`.
+    pub fn get_nutrient_level(&self, nutrient: Nutrient) -> f64 {
+        self.nutrients[nutrient]
+    }
+
+    pub fn get_nutrients(&self) -> &Nutrients {
+        &self.nutrients
+    }
+
+    /// Changes the elevation of this tile, flooding or drying it out if it crosses `sea_level`.
+    ///
+    /// A tile that crosses it gets the fertility that belongs to its new elevation.
+    pub fn set_elevation(&mut self, elevation: f64, sea_level: f64, time: f64) {
+        self.elevation = elevation;
+
+        if self.set_sea_level(sea_level, time) {
+            self.fertility = get_land_fertility(elevation);
+        }
+    }
+
+    /// Changes the fertility of a land tile, does nothing for water tiles.
+    pub fn set_fertility(&mut self, fertility: f64) {
+        if !self.water {
+            self.fertility = fertility.max(0.0).min(1.0);
         }
     }
 
+    /// Removes all food from this tile, e.g. after a meteor impact.
+    pub fn clear_food(&mut self, time: f64) {
+        self.nutrients = Nutrients::default();
+        self.last_update_time = time;
+    }
+
+    /// Changes how fast food grows here, a multiplier of 0 means nothing grows (e.g. during a drought).
+    ///
+    /// Food still decays in the cold as usual. Make sure the tile is up to date before calling this.
+    pub fn set_growth_multiplier(&mut self, multiplier: f64) {
+        self.growth_multiplier = multiplier.max(0.0);
+    }
+
+    pub fn get_growth_multiplier(&self) -> f64 {
+        self.growth_multiplier
+    }
+
+    pub fn set_food_type(&mut self, food_type: f64) {
+        self.food_type = food_type.max(0.0).min(1.0);
+    }
+
+    pub fn get_elevation(&self) -> f64 {
+        self.elevation
+    }
+
     /// Get the `fertility` of this `Tile`, returns 0 if it is water.
     pub fn get_fertility(&self) -> f64 {
-        match self {
-            Tile::Water => 0.0,
-            Tile::Land(t) => t.fertility,
+        if self.water {
+            0.0
+        } else {
+            self.fertility
         }
     }
 
-    /// Get the `food_type` of this `Tile`, returns 0 if it is water.
+    /// Get the `food_type` of this `Tile`.
     pub fn get_food_type(&self) -> f64 {
-        match self {
-            Tile::Water => 0.0,
-            Tile::Land(t) => t.food_type,
-        }
+        self.food_type
+    }
+
+    pub fn get_last_update_time(&self) -> f64 {
+        self.last_update_time
     }
 
     pub fn get_hsba_color(&self) -> [f32; 4] {
-        match self {
-            Tile::Water => COLOR_WATER,
-            Tile::Land(t) => {
-                let food_color = [t.food_type as f32, 1.0, 1.0];
-
-                if t.food_level < MAX_GROWTH_LEVEL {
-                    if t.food_level > 0.0 {
-                        let c = inter_color(COLOR_BARREN, COLOR_FERTILE, t.fertility as f32);
-                        return inter_color_fixed_hue(
-                            c,
-                            food_color,
-                            (t.food_level / MAX_GROWTH_LEVEL) as f32,
-                            t.food_type as f32,
-                        );
-                    } else {
-                        return [COLOR_BARREN[0], COLOR_BARREN[1], COLOR_BARREN[2], 1.0];
-                    }
-                } else {
-                    return inter_color_fixed_hue(
-                        food_color,
-                        COLOR_BLACK,
-                        1.0 - (MAX_GROWTH_LEVEL / t.food_level) as f32,
-                        t.food_type as f32,
-                    );
-                }
+        if self.water {
+            let algae = (self.nutrients[Nutrient::Algae] / MAX_ALGAE_LEVEL).min(1.0) as f32;
+
+            return inter_color_fixed_hue(
+                [COLOR_WATER[0], COLOR_WATER[1], COLOR_WATER[2]],
+                COLOR_ALGAE,
+                algae,
+                self.food_type as f32,
+            );
+        }
+
+        let food_color = [self.food_type as f32, 1.0, 1.0];
+        let food_level = self.nutrients.total();
+
+        if food_level < MAX_GROWTH_LEVEL {
+            if food_level > 0.0 {
+                let c = inter_color(COLOR_BARREN, COLOR_FERTILE, self.fertility as f32);
+                return inter_color_fixed_hue(
+                    c,
+                    food_color,
+                    (food_level / MAX_GROWTH_LEVEL) as f32,
+                    self.food_type as f32,
+                );
+            } else {
+                return [COLOR_BARREN[0], COLOR_BARREN[1], COLOR_BARREN[2], 1.0];
             }
+        } else {
+            return inter_color_fixed_hue(
+                food_color,
+                COLOR_BLACK,
+                1.0 - (MAX_GROWTH_LEVEL / food_level) as f32,
+                self.food_type as f32,
+            );
         }
     }
 
     /// Update this tile
     pub fn update(&mut self, time: f64, climate: &Climate) {
-        match self {
-            Tile::Water => {}
-            Tile::Land(t) => t.update(time, climate),
-        }
+        let fertility = if self.water {
+            get_richness(self.elevation, climate.get_sea_level())
+        } else {
+            self.fertility
+        };
+
+        catch_up(
+            &mut self.nutrients,
+            &mut self.last_update_time,
+            time,
+            self.growth_multiplier,
+            fertility,
+            self.water,
+            climate,
+        );
     }
 
-    /// Adds the given value to the food level if it's possible.
+    /// Adds the given value to the main nutrient of this tile: `Plant` on land and `Algae` on water.
     ///
-    /// This does nothing for water tiles.
+    /// Makes sure the level can't get negative.
     pub fn add_food_or_nothing(&mut self, food_to_add: f64) {
-        match self {
-            Tile::Water => {}
-            Tile::Land(t) => t.add_food(food_to_add),
-        }
+        add_main_food(&mut self.nutrients, self.water, food_to_add);
     }
 
-    /// Removes the given value from the food level.
-    ///
-    /// This panics for water tiles since you should never try gaining food from them.
-    pub fn remove_food(&mut self, food_to_remove: f64) {
-        match self {
-            Tile::Water => {
-                if food_to_remove > 0.0 {
-                    panic!("You called `remove_food` on a water tile, water tiles don't have any food and should not be eaten.")
-                }
-            }
-            Tile::Land(t) => t.remove_food(food_to_remove),
-        }
+    /// Removes the given fraction of every nutrient and returns how much of each was removed.
+    pub fn remove_food_fraction(&mut self, fraction: f64) -> Nutrients {
+        self.nutrients.take_fraction(fraction)
     }
 
-    pub fn get_food_multiplier(&self, hue: f64) -> Option<f64> {
-        match self {
-            // Tile::Water => panic!("You called `get_food_multiplier` on a water tile, water tiles don't have any food and should not be eaten."),
-            Tile::Water => None,
-            Tile::Land(t) => Some(t.get_food_multiplier(hue)),
-        }
+    /// Returns how much energy a mouth of the given hue gets out of the food on this tile, negative values are poison.
+    ///
+    /// `sensitivity` is how far the hues can be apart before the food becomes poisonous, usually `FOOD_SENSITIVITY`.
+    pub fn get_food_multiplier(&self, hue: f64, sensitivity: f64) -> f64 {
+        1.0 - (self.food_type - hue).abs() / sensitivity
     }
 }
 
-#[derive(Serialize, Deserialize)]
-pub struct LandTile {
-    fertility: f64,
-    food_level: f64,
-    food_type: f64,
+/// How well plants grow on land at the given elevation.
+///
+/// Low land is the most fertile, anything higher than 1 is barren.
+pub fn get_land_fertility(elevation: f64) -> f64 {
+    (1.0 - elevation).max(0.0).min(1.0)
+}
 
-    last_update_time: f64,
+/// How well algae grow on water at the given elevation, the equivalent of fertility on land.
+///
+/// Shallow water is the richest, anything deeper than 1 is barren.
+pub fn get_richness(elevation: f64, sea_level: f64) -> f64 {
+    (1.0 - (sea_level - elevation)).max(0.0).min(1.0)
 }
 
-impl LandTile {
-    /// Creates a new tile with the given `fertility` and `food_type`.
-    ///
-    /// Begins with `food_level` set to `fertility` and `last_update_time` set to `0`.
-    pub fn new(fertility: f64, food_type: f64) -> Self {
-        LandTile {
-            fertility,
-            food_level: fertility,
-            food_type,
+/// Adds `food_to_add` to `Algae` on water and to `Plant` on land, the level can't get negative.
+///
+/// NOTE: Doesn't call `update()` like in carykh's Processing code.
+pub(super) fn add_main_food(nutrients: &mut Nutrients, water: bool, food_to_add: f64) {
+    let main = if water {
+        Nutrient::Algae
+    } else {
+        Nutrient::Plant
+    };
 
-            last_update_time: 0.0,
-        }
+    nutrients[main] = 0f64.max(nutrients[main] + food_to_add);
+}
+
+/// Lets `nutrients` grow from `last_update_time` up to `time` and sets `last_update_time` to `time`.
+///
+/// Food grows while the temperature is positive and decays while it is negative, and the two don't commute.
+/// So the time range is split every time the temperature crosses 0.
+/// This makes the result the same no matter how often a tile is updated,
+/// which is what allows tiles to only be updated when they are needed.
+/// Water tiles are the exception if the sea moves: their richness uses the sea level at `time` for the whole range.
+///
+/// NOTE: the formulas were almost directly copied from carykh's original Processing version.
+pub(super) fn catch_up(
+    nutrients: &mut Nutrients,
+    last_update_time: &mut f64,
+    time: f64,
+    multiplier: f64,
+    fertility: f64,
+    water: bool,
+    climate: &Climate,
+) {
+    if time - *last_update_time <= 0.00001 {
+        return;
     }
 
-    /// Update this tile
-    ///
-    /// NOTE: code was almost directly copied from carykh's original Processing version and is pretty messy.
-    fn update(&mut self, time: f64, climate: &Climate) {
-        // TODO: clean up this mess!
-        if time - self.last_update_time > 0.00001 {
-            let growth_change = climate.get_growth_over_time_range(time, self.last_update_time);
-
-            if growth_change <= 0.0 {
-                let food_to_remove =
-                    self.food_level - self.food_level * (growth_change * FOOD_GROWTH_RATE).exp();
-                self.remove_food(food_to_remove);
-            } else if self.food_level < MAX_GROWTH_LEVEL {
-                let new_dist_to_max = (MAX_GROWTH_LEVEL - self.food_level)
-                    * (-growth_change * self.fertility * FOOD_GROWTH_RATE).exp();
-
-                let food_to_add = MAX_GROWTH_LEVEL - new_dist_to_max - self.food_level;
-                self.add_food(food_to_add);
-            }
+    let mut from = *last_update_time;
+    while from < time {
+        let to = climate.get_next_zero_temperature(from).min(time);
 
-            self.last_update_time = time;
-        }
-    }
+        let growth_change = get_growth_change(to, from, multiplier, climate);
+        nutrients.grow(growth_change, fertility, water);
 
-    pub fn get_food_multiplier(&self, hue: f64) -> f64 {
-        return 1.0 - (self.food_type - hue).abs() / FOOD_SENSITIVITY;
+        from = to;
     }
 
-    /// Subtracts the given amount of food from `self.food_level` and makes sure it can't get negative.
-    ///
-    /// This takes the maximum of 0 and `food_level` after subtraction.
-    ///
-    /// NOTE: Doesn't call `update()` like in carykh's Processing code.
-    fn remove_food(&mut self, food_to_remove: f64) {
-        self.food_level = 0f64.max(self.food_level - food_to_remove);
-    }
+    *last_update_time = time;
+}
 
-    /// Adds the given amount of food from `self.food_level` and makes sure it can't get negative.
-    ///
-    /// This takes the maximum of 0 and `food_level` after adding.
-    ///
-    /// NOTE: Doesn't call `update()` like in carykh's Processing code.
-    pub fn add_food(&mut self, food_to_add: f64) {
-        self.food_level = 0f64.max(self.food_level + food_to_add);
+/// Returns the growth since `last_updated`, only growth (not decay) is affected by `multiplier`.
+fn get_growth_change(time: f64, last_updated: f64, multiplier: f64, climate: &Climate) -> f64 {
+    let growth_change = climate.get_growth_over_time_range(time, last_updated);
+
+    if growth_change > 0.0 {
+        growth_change * multiplier
+    } else {
+        growth_change
     }
 }
 
@@ -14,3 +14,83 @@ fn test_board_update() {
 fn test_board_default_intialise() {
     let _board = Board::<Brain>::default();
 }
+
+#[test]
+fn test_scheduled_disasters_start_once() {
+    use lib_evolvim::disaster::{Disaster, ScheduledDisaster};
+
+    // One disaster at the very start and one on every border between two time steps after it.
+    let mut board = Board::<Brain>::default();
+    for i in 0..10 {
+        board.disasters.scheduled.push(ScheduledDisaster {
+            year: i as f64 * 0.001,
+            disaster: Disaster::Plague { mortality: 0.0 },
+        });
+    }
+
+    board.update(0.001);
+    assert_eq!(board.event_log.len(), 2);
+
+    for _i in 0..10 {
+        board.update(0.001);
+    }
+    assert_eq!(board.event_log.len(), 10);
+}
+
+#[test]
+fn test_board_disasters() {
+    use lib_evolvim::disaster::{Disaster, ScheduledDisaster};
+
+    let mut board = Board::<Brain>::default();
+    board.disasters.scheduled.push(ScheduledDisaster {
+        year: 0.002,
+        disaster: Disaster::Drought {
+            center: None,
+            radius: 10.0,
+            duration: 0.005,
+        },
+    });
+
+    for _i in 0..10 {
+        board.update(0.001);
+    }
+    // The drought started and ended.
+    assert_eq!(board.event_log.len(), 2);
+
+    board.trigger_disaster(Disaster::Meteor {
+        center: Some((50.0, 50.0)),
+        radius: 1000.0,
+    });
+    assert_eq!(board.event_log.len(), 3);
+    for c in &board.creatures {
+        assert_eq!(c.borrow().get_energy(), 0.0);
+    }
+
+    // Overlapping droughts give back the growth multiplier that was painted before them.
+    let time = board.get_time();
+    board.terrain.edit(
+        (50, 50),
+        TerrainEdit::GrowthMultiplier(0.5),
+        time,
+        &board.climate,
+    );
+    for &(center, duration) in &[((50.0, 50.0), 0.002), ((52.0, 50.0), 0.004)] {
+        board.trigger_disaster(Disaster::Drought {
+            center: Some(center),
+            radius: 5.0,
+            duration,
+        });
+    }
+    board.update(0.001);
+    assert_eq!(
+        board.terrain.get_tile_at((50, 50)).get_growth_multiplier(),
+        0.0
+    );
+    for _i in 0..5 {
+        board.update(0.001);
+    }
+    assert_eq!(
+        board.terrain.get_tile_at((50, 50)).get_growth_multiplier(),
+        0.5
+    );
+}
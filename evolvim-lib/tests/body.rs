@@ -0,0 +1,64 @@
+extern crate lib_evolvim;
+
+use lib_evolvim::constants::*;
+use lib_evolvim::*;
+
+#[test]
+fn test_body_genome_inheritance() {
+    let light = BodyGenome {
+        density: 0.5,
+        ..BodyGenome::default()
+    };
+    let heavy = BodyGenome {
+        density: 1.5,
+        swim_efficiency: 1.0,
+        ..BodyGenome::default()
+    };
+
+    let average = BodyGenome::average(&[&light, &heavy]);
+    assert_eq!(average.density, 1.0);
+    assert_eq!(average.swim_efficiency, 0.5);
+
+    for _i in 0..100 {
+        let child = BodyGenome::new_from_parents(&[&light, &heavy]);
+        assert!(child.density > 0.5 && child.density < 2.0);
+        assert!(child.swim_efficiency >= 0.0 && child.swim_efficiency <= 1.0);
+    }
+
+    // Bodies without a top speed can get one.
+    let uncapped = BodyGenome::default();
+    let capped = (0..100)
+        .map(|_| BodyGenome::new_from_parents(&[&uncapped]).max_speed)
+        .filter(|s| s.is_finite())
+        .inspect(|s| assert!(*s < MAX_FINITE_SPEED))
+        .count();
+    assert!(capped > 0 && capped < 100);
+}
+
+#[test]
+fn test_body_traits() {
+    let mut rock = Rock::new_random(DEFAULT_BOARD_SIZE, CREATURE_DENSITY, 2.0, 0.0);
+    assert_eq!(rock.get_body_genome(), &BodyGenome::default());
+    let mass = rock.get_mass();
+
+    rock.set_body_genome(BodyGenome {
+        density: 2.0,
+        max_speed: 0.1,
+        ..BodyGenome::default()
+    });
+    assert_eq!(rock.get_mass(), mass * 2.0);
+
+    for _i in 0..100 {
+        rock.accelerate(100.0, 0.001);
+        rock.apply_motions(0.001, DEFAULT_BOARD_SIZE);
+        assert!(rock.get_total_velocity() <= 0.1 + 1e-9);
+    }
+
+    // The default body has no top speed.
+    let mut rock = Rock::new_random(DEFAULT_BOARD_SIZE, CREATURE_DENSITY, 2.0, 0.0);
+    for _i in 0..100 {
+        rock.accelerate(30.0, 0.001);
+        rock.apply_motions(0.001, DEFAULT_BOARD_SIZE);
+    }
+    assert!(rock.get_total_velocity() > MAX_FINITE_SPEED);
+}
@@ -1,4 +1,6 @@
+extern crate bincode;
 extern crate lib_evolvim;
+extern crate nalgebra;
 
 // use lib_evolvim::graphics::*;
 use lib_evolvim::*;
@@ -8,5 +10,399 @@ fn test_brain_evolve() {
     let c_1 = HLSoftBody::from(Creature::new_random((100, 100), 0.0));
     let c_2 = HLSoftBody::from(Creature::new_random((100, 100), 0.0));
 
-    let _new_brain = Brain::recombination_infinite_parents(&vec![c_1, c_2]);
+    let _new_brain = Brain::recombination_infinite_parents(&vec![c_1, c_2], &Default::default());
+}
+
+#[test]
+fn test_brain_shapes() {
+    let deep = Brain::new_random_with_shape(BrainShape::new(vec![16, 8]));
+    let other_deep = Brain::new_random_with_shape(BrainShape::new(vec![16, 8]));
+    let default = Brain::new_random();
+
+    let mut child = Brain::recombine(&[&deep, &other_deep]).unwrap();
+    assert_eq!(child.get_shape(), deep.get_shape());
+    child.run();
+    assert_eq!(child.get_layers().len(), 4);
+
+    assert!(Brain::recombine(&[&deep, &default]).is_err());
+
+    let mut shallow = Brain::new_random_with_shape(BrainShape::new(vec![]));
+    shallow.run();
+    assert_eq!(shallow.get_hidden_layer(), None);
+    assert_eq!(child.get_hidden_layer().unwrap().len(), 16);
+}
+
+#[test]
+fn test_world_brain_shape() {
+    use lib_evolvim::constants::*;
+    use lib_evolvim::disaster::Disaster;
+
+    let shape = BrainShape::new(vec![16, 8]);
+    let context = RecombinationConfig {
+        shape: shape.clone(),
+        ..RecombinationConfig::default()
+    };
+    let mut board = Board::<Brain>::new_random_with_context(
+        DEFAULT_BOARD_SIZE,
+        DEFAULT_NOISE_STEP_SIZE,
+        DEFAULT_CREATURE_MINIMUM,
+        DEFAULT_MIN_TEMP,
+        DEFAULT_MAX_TEMP,
+        context,
+    );
+    assert!(board
+        .creatures
+        .iter()
+        .all(|c| c.borrow().brain.get_shape() == &shape));
+
+    // The creatures that replace the dead ones get the shape of the world as well.
+    let shape = BrainShape::new(vec![]);
+    board.brain_context.shape = shape.clone();
+    board.trigger_disaster(Disaster::Meteor {
+        center: None,
+        radius: 1000.0,
+    });
+    board.update(0.001);
+    assert!(!board.creatures.is_empty());
+    assert!(board
+        .creatures
+        .iter()
+        .all(|c| c.borrow().brain.get_shape() == &shape));
+}
+
+#[test]
+fn test_recurrent_brain_evolve() {
+    use lib_evolvim::brain::ExportBrain;
+
+    let c_1 = HLSoftBody::<RecurrentBrain>::from(Creature::new_random((100, 100), 0.0));
+    let c_2 = HLSoftBody::<RecurrentBrain>::from(Creature::new_random((100, 100), 0.0));
+
+    let config = RecurrentConfig::default();
+    let mut child = RecurrentBrain::recombination_infinite_parents(&vec![c_1, c_2], &config);
+    assert_eq!(child.get_hidden_size(), recurrent::DEFAULT_HIDDEN_SIZE);
+
+    child.run();
+    assert!(child.get_hidden_state().iter().all(|h| h.abs() <= 1.0));
+
+    // The weights are mutated with the `WeightMutation` of the world.
+    for &mutation in &[WeightMutation::Processing, WeightMutation::Gaussian] {
+        let config = RecurrentConfig { mutation };
+        let clone = RecurrentBrain::recombination_infinite_brains(&[(&child, 1.0)], &config);
+        assert_ne!(clone.to_json()["candidate"], child.to_json()["candidate"]);
+    }
+}
+
+#[test]
+fn test_activations() {
+    assert_eq!(Activation::Sigmoid.apply(0.0), 0.5);
+    assert!(Activation::Sigmoid.apply(5.0) > 0.99);
+    assert_eq!(Activation::ReLU.apply(-2.0), 0.0);
+    assert_eq!(Activation::Step.apply(0.3), 1.0);
+    assert_eq!(Activation::Identity.apply(-2.0), -2.0);
+
+    let shape = BrainShape::new(vec![10]);
+    let mut brain =
+        Brain::new_random_with_activations(shape, vec![Activation::ReLU, Activation::Tanh]);
+    brain.run();
+    assert!(brain.get_hidden_layer().unwrap().iter().all(|v| *v >= 0.0));
+    assert!(brain.get_output().iter().all(|v| v.abs() <= 1.0));
+}
+
+#[test]
+fn test_metabolic_cost() {
+    let small = Brain::new_random_with_shape(BrainShape::new(vec![4]));
+    let big = Brain::new_random_with_shape(BrainShape::new(vec![16, 8]));
+    assert!(small.metabolic_cost() < big.metabolic_cost());
+
+    // A newborn doesn't pay for its age yet, only for its brain.
+    let mut creature = Creature::<Brain>::new_random((100, 100), 0.0);
+    let energy = creature.get_energy();
+    creature.metabolize(0.001, 0.0, 0.0);
+    assert_eq!(creature.get_energy(), energy);
+    creature.metabolize(0.001, 0.0, 1.0);
+    assert!(creature.get_energy() < energy);
+}
+
+#[test]
+fn test_mutability() {
+    let c_1 = HLSoftBody::<Brain>::from(Creature::new_random((100, 100), 0.0));
+    let c_2 = HLSoftBody::<Brain>::from(Creature::new_random((100, 100), 0.0));
+    assert_eq!(
+        c_1.borrow().brain.get_mutability(),
+        Some(feed_forward::DEFAULT_MUTABILITY)
+    );
+
+    for _i in 0..100 {
+        let child = Brain::recombination_infinite_parents(
+            &vec![c_1.clone(), c_2.clone()],
+            &Default::default(),
+        );
+        let mutability = child.get_mutability().unwrap();
+
+        assert!(mutability >= feed_forward::MIN_MUTABILITY);
+        assert!(mutability <= feed_forward::MAX_MUTABILITY);
+    }
+}
+
+#[test]
+fn test_brain_memory_round_trip() {
+    let mut brain = Brain::new_random();
+    brain.run();
+
+    let bytes = bincode::serialize(&brain).unwrap();
+    let loaded: Brain = bincode::deserialize(&bytes).unwrap();
+
+    assert_eq!(loaded.get_layers(), brain.get_layers());
+}
+
+#[test]
+fn test_export_brain() {
+    let brain = Brain::new_random_with_shape(BrainShape::new(vec![4, 3]));
+
+    let dot = brain.to_dot();
+    assert!(dot.starts_with("digraph"));
+    assert_eq!(dot.matches("->").count() as f64, brain.metabolic_cost());
+
+    let json = brain.to_json();
+    assert_eq!(json["layers"].as_array().unwrap().len(), 3);
+    assert_eq!(
+        json["layers"][0]["weights"].as_array().unwrap().len(),
+        feed_forward::INPUT_LAYER_SIZE + 1
+    );
+}
+
+#[test]
+fn test_brain_batch() {
+    let board = Board::<Brain>::default();
+    let mut brains: Vec<Brain> = (0..20).map(|_| Brain::new_random()).collect();
+    brains[0].set_activation(0, Activation::Tanh);
+    for (brain, creature) in brains.iter_mut().zip(&board.creatures) {
+        let creature = creature.borrow();
+        brain.load_input(&Environment::new(
+            &board.terrain,
+            &creature.base,
+            0.0,
+            &board.climate,
+        ));
+    }
+
+    // The batch has to give the same values as running every brain on its own.
+    let mut expected: Vec<Brain> = brains
+        .iter()
+        .map(|b| bincode::deserialize(&bincode::serialize(b).unwrap()).unwrap())
+        .collect();
+    for brain in &mut expected {
+        brain.run();
+    }
+
+    let inputs: Vec<&Brain> = brains.iter().collect();
+    let mut batch = BrainBatch::new(&inputs).unwrap();
+    assert_eq!(batch.len(), brains.len());
+    batch.load_inputs(&inputs);
+    batch.run();
+    let mut outputs: Vec<&mut Brain> = brains.iter_mut().collect();
+    batch.store_values(&mut outputs);
+
+    for (brain, expected) in brains.iter().zip(&expected) {
+        for (layer, expected) in brain.get_layers().iter().zip(expected.get_layers()) {
+            for (v, e) in layer.iter().zip(expected.iter()) {
+                assert!((v - e).abs() < 1e-9);
+            }
+        }
+    }
+    assert_ne!(brains[0].get_output(), brains[1].get_output());
+
+    let deep = Brain::new_random_with_shape(BrainShape::new(vec![16, 8]));
+    assert!(BrainBatch::new(&[&deep, &brains[0]]).is_err());
+}
+
+#[test]
+fn test_brain_learning() {
+    let mut brain = Brain::new_random();
+    brain.run();
+    let output = brain.get_output().to_vec();
+    let weights = brain.to_json()["layers"].clone();
+
+    // New brains don't learn.
+    brain.learn(0.001);
+    brain.run();
+    assert_eq!(brain.get_output(), &output[..]);
+
+    brain.set_plasticity(Plasticity {
+        learning_rate: 100.0,
+        a: 0.0,
+        b: 0.0,
+        c: 0.0,
+        d: 1.0,
+    });
+    brain.learn(0.001);
+    brain.run();
+    assert_ne!(brain.get_output(), &output[..]);
+
+    // Only the inherited weights are exported, learning doesn't change them.
+    assert_eq!(brain.to_json()["layers"], weights);
+
+    let bytes = bincode::serialize(&brain).unwrap();
+    let mut loaded: Brain = bincode::deserialize(&bytes).unwrap();
+    loaded.run();
+    assert_eq!(loaded.get_output(), brain.get_output());
+
+    // How much is learned doesn't depend on the length of a tick.
+    let plasticity = Plasticity {
+        learning_rate: 10.0,
+        a: 0.0,
+        b: 0.5,
+        c: -0.2,
+        d: 0.1,
+    };
+    let once = plasticity.update_weight(0.3, 0.4, 0.7, 0.002);
+    let twice = plasticity.update_weight(
+        plasticity.update_weight(0.3, 0.4, 0.7, 0.001),
+        0.4,
+        0.7,
+        0.001,
+    );
+    assert!((once - twice).abs() < 1e-12);
+}
+
+#[test]
+fn test_brain_crossover() {
+    let parent = Brain::new_random_with_shape(BrainShape::new(vec![4, 3]));
+    let other = Brain::new_random_with_shape(BrainShape::new(vec![4, 3]));
+    // A single weight never changes more than this with the mutation of the Processing code.
+    let max_change = feed_forward::MAX_MUTABILITY / 0.5f64.powi(9);
+
+    let crossovers = [
+        Crossover::Axons,
+        Crossover::Uniform,
+        Crossover::Neuron,
+        Crossover::Points(1),
+        Crossover::Points(2),
+        Crossover::Blend,
+    ];
+    for &crossover in &crossovers {
+        for &mutation in &[WeightMutation::Processing, WeightMutation::Gaussian] {
+            let config = RecombinationConfig {
+                crossover,
+                mutation,
+                ..RecombinationConfig::default()
+            };
+
+            let mut child = Brain::recombine_with(&[&parent, &other], &config).unwrap();
+            child.run();
+            let sizes: Vec<usize> = child.get_layers().iter().map(|l| l.len()).collect();
+            let parent_sizes: Vec<usize> = parent.get_layers().iter().map(|l| l.len()).collect();
+            assert_eq!(sizes, parent_sizes);
+        }
+
+        // Whatever the crossover, the child of a brain with itself only differs by the mutation.
+        let config = RecombinationConfig {
+            crossover,
+            mutation: WeightMutation::Processing,
+            ..RecombinationConfig::default()
+        };
+        let child = Brain::recombine_with(&[&parent, &parent], &config).unwrap();
+        let parent_layers = parent.to_json()["layers"].clone();
+        let child_layers = child.to_json()["layers"].clone();
+        for (p, c) in parent_layers
+            .as_array()
+            .unwrap()
+            .iter()
+            .zip(child_layers.as_array().unwrap())
+        {
+            let p_rows = p["weights"].as_array().unwrap();
+            let c_rows = c["weights"].as_array().unwrap();
+            assert_eq!(p_rows.len(), c_rows.len());
+
+            for (p_row, c_row) in p_rows.iter().zip(c_rows) {
+                for (p_w, c_w) in p_row
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .zip(c_row.as_array().unwrap())
+                {
+                    let change = (p_w.as_f64().unwrap() - c_w.as_f64().unwrap()).abs();
+                    assert!(change <= max_change + 1e-9);
+                }
+            }
+        }
+    }
+
+    // Without mutation every operator has to keep to its own rules.
+    // The weights of the first parent are all positive and those of the second negative,
+    // so the sign of a weight tells where it came from.
+    use nalgebra::DMatrix;
+    let shapes = [(5, 4), (5, 7), (8, 3)];
+    let first: Vec<DMatrix<f64>> = shapes
+        .iter()
+        .map(|&(rows, cols)| DMatrix::from_fn(rows, cols, |y, z| 0.1 + (y + z * rows) as f64))
+        .collect();
+    let second: Vec<DMatrix<f64>> = first.iter().map(|theta| theta * -1.0).collect();
+    let parents = [&first[..], &second[..]];
+    let from_first = |x: usize, i: usize, w: f64| {
+        if w == first[x].as_slice()[i] {
+            true
+        } else {
+            assert_eq!(w, second[x].as_slice()[i]);
+            false
+        }
+    };
+
+    for _i in 0..20 {
+        for &crossover in &[Crossover::Axons, Crossover::Uniform, Crossover::Points(1)] {
+            let mut sources = Vec::new();
+            for (x, theta) in crossover.cross(&parents).iter().enumerate() {
+                for (i, w) in theta.iter().enumerate() {
+                    sources.push(from_first(x, i, *w));
+                }
+            }
+
+            if crossover == Crossover::Points(1) {
+                let switches = sources.windows(2).filter(|s| s[0] != s[1]).count();
+                assert!(switches <= 1);
+            }
+        }
+
+        // Every neuron gets all its weights from one parent.
+        for (x, theta) in Crossover::Neuron.cross(&parents).iter().enumerate() {
+            for (z, column) in theta.column_iter().enumerate() {
+                let sources: Vec<bool> = column
+                    .iter()
+                    .enumerate()
+                    .map(|(y, w)| from_first(x, z * theta.nrows() + y, *w))
+                    .collect();
+                assert!(sources.iter().all(|s| *s == sources[0]));
+            }
+        }
+
+        // Every weight is between those of the parents.
+        for (x, theta) in Crossover::Blend.cross(&parents).iter().enumerate() {
+            for (i, w) in theta.iter().enumerate() {
+                let (a, b) = (first[x].as_slice()[i], second[x].as_slice()[i]);
+                assert!(*w <= a + 1e-12 && *w >= b - 1e-12);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_recombination_config_save_and_load() {
+    let mut board = Board::<Brain>::default();
+    board.brain_context = RecombinationConfig {
+        crossover: Crossover::Points(2),
+        mutation: WeightMutation::Gaussian,
+        shape: BrainShape::new(vec![16, 8]),
+    };
+    for _i in 0..20 {
+        board.update(0.001);
+    }
+
+    let path = std::env::temp_dir().join("evolvim_test_recombination_config.bin");
+    board.save_to(&path).unwrap();
+    let board = Board::<Brain>::load_from(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(board.brain_context.crossover, Crossover::Points(2));
+    assert_eq!(board.brain_context.mutation, WeightMutation::Gaussian);
+    assert_eq!(board.brain_context.shape, BrainShape::new(vec![16, 8]));
 }
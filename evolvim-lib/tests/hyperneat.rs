@@ -0,0 +1,76 @@
+extern crate bincode;
+extern crate lib_evolvim;
+
+use lib_evolvim::neat::HyperNeatBrain;
+use lib_evolvim::*;
+
+#[test]
+fn test_hyperneat_board_update() {
+    let mut board = Board::<HyperNeatBrain>::default();
+
+    for _i in 0..200 {
+        board.update(0.001);
+    }
+
+    let counted: usize = board
+        .get_species_counts()
+        .iter()
+        .map(|(_, size)| size)
+        .sum();
+    assert_eq!(counted, board.get_population_size());
+}
+
+#[test]
+fn test_hyperneat_substrate() {
+    let brain = HyperNeatBrain::new_random();
+    let json = brain.to_json();
+    let substrate = &json["substrate"];
+
+    let sizes: Vec<usize> = substrate["positions"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|l| l.as_array().unwrap().len())
+        .collect();
+    assert_eq!(sizes, vec![26, 25, 7]);
+
+    // Every layer but the input layer has a row of weights for every neuron of the previous layer and the bias node.
+    let mut links = 0;
+    for (i, layer) in substrate["weights"].as_array().unwrap().iter().enumerate() {
+        let rows = layer.as_array().unwrap();
+        assert_eq!(rows.len(), sizes[i] + 1);
+
+        for row in rows {
+            let row = row.as_array().unwrap();
+            assert_eq!(row.len(), sizes[i + 1]);
+            links += row.iter().filter(|w| w.as_f64().unwrap() != 0.0).count();
+        }
+    }
+    assert_eq!(brain.metabolic_cost(), links as f64);
+}
+
+#[test]
+fn test_hyperneat_brain_evolve_and_load() {
+    let c_1 = HLSoftBody::<HyperNeatBrain>::from(Creature::new_random((100, 100), 0.0));
+    let c_2 = HLSoftBody::<HyperNeatBrain>::from(Creature::new_random((100, 100), 0.0));
+
+    let context = neat::NeatContext::default();
+
+    let mutability = c_1.borrow().brain.get_mutability();
+
+    let mut child = HyperNeatBrain::recombination_infinite_parents(&vec![c_1, c_2], &context);
+    // The CPPN of a child of several parents mutates, its mutation rate included.
+    assert_ne!(child.get_mutability(), mutability);
+    for _i in 0..20 {
+        let parent = HLSoftBody::<HyperNeatBrain>::from(Creature::new_random((100, 100), 0.0));
+        parent.borrow_mut().brain = child;
+        child = HyperNeatBrain::recombination_infinite_parents(&vec![parent], &context);
+    }
+    child.run();
+    child.wants_birth();
+
+    // Only the CPPN is saved, it has to draw the same substrate again.
+    let bytes = bincode::serialize(&child).unwrap();
+    let loaded: HyperNeatBrain = bincode::deserialize(&bytes).unwrap();
+    assert_eq!(loaded.to_json(), child.to_json());
+}
@@ -0,0 +1,52 @@
+extern crate lib_evolvim;
+
+use lib_evolvim::neat::{HyperNeatBrain, NeatBrain};
+use lib_evolvim::*;
+
+#[test]
+fn test_mixed_board_save_and_load() {
+    let mut board = Board::<MixedBrain>::default();
+    for _i in 0..200 {
+        board.update(0.001);
+    }
+
+    let architectures = board.get_architecture_counts();
+    let counted: usize = architectures.iter().map(|(_, size)| size).sum();
+    assert_eq!(counted, board.get_population_size());
+    assert!(architectures.len() > 1);
+
+    let path = std::env::temp_dir().join("evolvim_test_mixed_board.bin");
+    board.save_to(&path).unwrap();
+    let mut board = Board::<MixedBrain>::load_from(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(board.get_architecture_counts(), architectures);
+    board.update(0.001);
+}
+
+#[test]
+fn test_mixed_recombination() {
+    let new_creature = |brain: MixedBrain| {
+        let c = HLSoftBody::<MixedBrain>::from(Creature::new_random((100, 100), 0.0));
+        c.borrow_mut().brain = brain;
+        c
+    };
+    let context = MixedContext::default();
+
+    // Parents with another kind of brain than the first parent are left out.
+    let parents = vec![
+        new_creature(MixedBrain::Neat(NeatBrain::new_random())),
+        new_creature(MixedBrain::FeedForward(Brain::new_random())),
+        new_creature(MixedBrain::HyperNeat(HyperNeatBrain::new_random())),
+        new_creature(MixedBrain::Neat(NeatBrain::new_random())),
+    ];
+    let mut child = MixedBrain::recombination_infinite_parents(&parents, &context);
+    assert_eq!(child.get_architecture(), "neat");
+    child.run();
+
+    let parents = vec![new_creature(MixedBrain::Recurrent(
+        RecurrentBrain::new_random(),
+    ))];
+    let child = MixedBrain::recombination_infinite_parents(&parents, &context);
+    assert_eq!(child.get_architecture(), "recurrent");
+}
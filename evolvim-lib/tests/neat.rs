@@ -13,12 +13,13 @@ fn test_construct_random() {
 
 #[test]
 fn test_recombination() {
+    let innovations = neat::InnovationRegistry::default();
     let mut gen1 = neat::Genome::new_fully_linked();
     let mut gen2 = neat::Genome::new_fully_linked();
 
     for _i in 0..5 {
-        gen1.mutate();
-        gen2.mutate();
+        gen1.mutate(&innovations);
+        gen2.mutate(&innovations);
     }
 
     println!("Parent A:");
@@ -37,6 +38,7 @@ fn test_recombination() {
 
 #[test]
 fn test_mutation() {
+    let innovations = neat::InnovationRegistry::default();
     let mut gen = neat::Genome::new_fully_linked();
 
     println!("Before mutation:");
@@ -44,7 +46,7 @@ fn test_mutation() {
     gen.log_connections();
 
     for _i in 0..10 {
-        gen.mutate();
+        gen.mutate(&innovations);
     }
 
     println!("\nAfter mutation:");
@@ -54,12 +56,13 @@ fn test_mutation() {
 
 #[test]
 fn test_genetical_distance() {
+    let innovations = neat::InnovationRegistry::default();
     let mut gen1 = neat::Genome::new_fully_linked();
     let mut gen2 = neat::Genome::new_fully_linked();
 
     for _i in 0..5 {
-        gen1.mutate();
-        gen2.mutate();
+        gen1.mutate(&innovations);
+        gen2.mutate(&innovations);
     }
 
     let distance = gen1.genetical_distance(&gen2);
@@ -82,3 +85,279 @@ fn test_run_phenotype() {
 
     phen.run_calculations();
 }
+
+#[test]
+fn test_mutate_activation() {
+    let mut gen = neat::Genome::new_fully_linked();
+    let before: Vec<_> = gen.get_node_genome().iter().map(|n| n.activation).collect();
+
+    // Without hidden nodes there is nothing to change.
+    gen.mutate_activation();
+    let after: Vec<_> = gen.get_node_genome().iter().map(|n| n.activation).collect();
+    assert_eq!(after, before);
+
+    gen.mutate_connection_to_node(&neat::InnovationRegistry::default());
+    let before: Vec<_> = gen.get_node_genome().iter().map(|n| n.activation).collect();
+
+    gen.mutate_activation();
+
+    let changed: Vec<_> = gen
+        .get_node_genome()
+        .iter()
+        .zip(before.iter())
+        .filter(|(n, b)| n.activation != **b)
+        .collect();
+    assert_eq!(changed.len(), 1);
+    assert!(changed[0].0.node_type.is_hidden());
+}
+
+#[test]
+fn test_innovation_registry() {
+    let innovations = neat::InnovationRegistry::default();
+
+    let first = innovations.get_connection_innovation(1, 20);
+    assert_eq!(innovations.get_connection_innovation(1, 20), first);
+    assert_ne!(innovations.get_connection_innovation(20, 1), first);
+
+    let node = innovations.get_split_node(first);
+    assert_eq!(innovations.get_split_node(first), node);
+    assert_ne!(innovations.get_new_node_id(), node);
+
+    // The links every genome starts with are known as well.
+    let gen = neat::Genome::new_fully_linked();
+    for c in gen.get_connection_genome() {
+        assert_eq!(
+            innovations.get_connection_innovation(c.from, c.to),
+            c.innovation_number
+        );
+    }
+}
+
+#[test]
+fn test_no_duplicate_connections() {
+    use std::collections::HashSet;
+
+    let innovations = neat::InnovationRegistry::default();
+    let mut gen = neat::Genome::new_fully_linked();
+    let mut other = neat::Genome::new_fully_linked();
+    for _i in 0..200 {
+        gen.mutate_add_connection(&innovations);
+        gen.mutate_connection_to_node(&innovations);
+        other.mutate_add_connection(&innovations);
+    }
+    let child = neat::Genome::new_from_2(&gen, &other);
+
+    for g in &[gen, child] {
+        let links: HashSet<_> = g
+            .get_connection_genome()
+            .iter()
+            .map(|c| (c.from, c.to))
+            .collect();
+        assert_eq!(links.len(), g.get_connection_genome().len());
+    }
+}
+
+#[test]
+fn test_run_phenotype_with_cycles() {
+    let innovations = neat::InnovationRegistry::default();
+    let mut gen = neat::Genome::new_fully_linked();
+
+    // Adds plenty of random links, some of them will close cycles or loop back to the same node.
+    for _i in 0..100 {
+        gen.mutate_add_connection(&innovations);
+        gen.mutate_connection_to_node(&innovations);
+    }
+
+    let mut phen: neat::NeuralNet = (&gen).into();
+    for _i in 0..10 {
+        phen.run_calculations();
+    }
+}
+
+#[test]
+fn test_rearrangement_mutations() {
+    let innovations = neat::InnovationRegistry::default();
+    let mut gen = neat::Genome::new_fully_linked();
+    for _i in 0..20 {
+        gen.mutate_connection_to_node(&innovations);
+        gen.mutate_add_connection(&innovations);
+    }
+    let links = |gen: &neat::Genome| -> Vec<_> {
+        gen.get_connection_genome()
+            .iter()
+            .map(|c| (c.from, c.to, c.innovation_number))
+            .collect()
+    };
+    let weights = |gen: &neat::Genome| -> Vec<f64> {
+        let mut weights: Vec<_> = gen
+            .get_connection_genome()
+            .iter()
+            .map(|c| c.weight)
+            .collect();
+        weights.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        weights
+    };
+
+    // An inversion only moves weights around.
+    let before = gen.clone();
+    for _i in 0..20 {
+        gen.mutate_inversion();
+    }
+    assert_eq!(links(&gen), links(&before));
+    assert_eq!(weights(&gen), weights(&before));
+
+    // A disabled link stays disabled when it moves.
+    let mut gen = neat::Genome::new_fully_linked();
+    gen.mutate_toggle_gene();
+    let disabled = |gen: &neat::Genome| {
+        gen.get_connection_genome()
+            .iter()
+            .filter(|c| !c.enabled)
+            .count()
+    };
+    for _i in 0..200 {
+        gen.mutate_translocation(&innovations);
+        assert_eq!(disabled(&gen), 1);
+    }
+}
+
+#[test]
+fn test_structural_mutations() {
+    let innovations = neat::InnovationRegistry::default();
+    let mut gen = neat::Genome::new_fully_linked();
+    let count_outputs = |gen: &neat::Genome| {
+        gen.get_node_genome()
+            .iter()
+            .filter(|n| !n.node_type.is_sensor() && !n.node_type.is_hidden())
+            .count()
+    };
+    let outputs = count_outputs(&gen);
+
+    for _i in 0..100 {
+        gen.mutate(&innovations);
+    }
+    let mut phen: neat::NeuralNet = (&gen).into();
+    phen.run_calculations();
+
+    // Only deleting genes has to shrink the genome down to its sensors and outputs without panicking.
+    let deletion_only = neat::MutationConfig {
+        new_link: 0.0,
+        link_to_node: 0.0,
+        duplication: 0.0,
+        insertion: 0.0,
+        add_sensor: 0.0,
+        remove_sensor: 0.0,
+        ..Default::default()
+    };
+    for _i in 0..10000 {
+        gen.mutate_with(&innovations, &deletion_only);
+    }
+    assert!(gen.get_connection_genome().is_empty());
+    assert!(gen
+        .get_node_genome()
+        .iter()
+        .all(|n| !n.node_type.is_hidden()));
+    assert_eq!(count_outputs(&gen), outputs);
+
+    gen.mutate(&innovations);
+}
+
+#[test]
+fn test_sensor_mutations() {
+    let innovations = neat::InnovationRegistry::default();
+    let mut gen = neat::Genome::new_fully_linked();
+    let count_sensors = |gen: &neat::Genome| {
+        gen.get_node_genome()
+            .iter()
+            .filter(|n| n.node_type.is_sensor())
+            .count()
+    };
+    let sensors = count_sensors(&gen);
+
+    // Every sensor is linked to something, none of them can be removed.
+    gen.mutate_remove_sensor();
+    assert_eq!(count_sensors(&gen), sensors);
+
+    for _i in 0..10 {
+        gen.mutate_add_sensor(&innovations);
+        gen.mutate_jitter_sensor();
+    }
+    assert_eq!(count_sensors(&gen), sensors + 10);
+
+    let mut phen: neat::NeuralNet = (&gen).into();
+    phen.run_calculations();
+}
+
+#[test]
+fn test_recombination_fitness() {
+    let innovations = neat::InnovationRegistry::default();
+    let mut parents = vec![neat::Genome::new_fully_linked(); 3];
+    for p in &mut parents {
+        for _i in 0..20 {
+            p.mutate_add_connection(&innovations);
+            p.mutate_connection_to_node(&innovations);
+        }
+    }
+    let innovation_numbers = |gen: &neat::Genome| -> Vec<usize> {
+        gen.get_connection_genome()
+            .iter()
+            .map(|c| c.innovation_number)
+            .collect()
+    };
+
+    // Only the genes of the fittest parent are inherited.
+    let baby = neat::Genome::new_from_parents(&[
+        (&parents[0], 1.0),
+        (&parents[1], 3.0),
+        (&parents[2], 2.0),
+    ]);
+    assert_eq!(innovation_numbers(&baby), innovation_numbers(&parents[1]));
+    assert_eq!(
+        baby.get_node_genome().len(),
+        parents[1].get_node_genome().len()
+    );
+
+    // Equally fit parents pass on all their genes.
+    let baby = neat::Genome::new_from_parents(&[(&parents[0], 1.0), (&parents[2], 1.0)]);
+    for i in innovation_numbers(&parents[0])
+        .into_iter()
+        .chain(innovation_numbers(&parents[2]))
+    {
+        assert!(innovation_numbers(&baby).contains(&i));
+    }
+
+    // The plasticity of the parents is averaged, `mutate_with` changes it afterwards.
+    assert_eq!(*baby.get_plasticity(), *parents[0].get_plasticity());
+}
+
+#[test]
+fn test_export_genome() {
+    use lib_evolvim::brain::ExportBrain;
+
+    let innovations = neat::InnovationRegistry::default();
+    let mut gen = neat::Genome::new_fully_linked();
+    for _i in 0..10 {
+        gen.mutate_connection_to_node(&innovations);
+    }
+
+    // Splitting a link disables it, those have to be dashed.
+    let dot = gen.to_dot();
+    assert!(dot.starts_with("digraph"));
+    assert!(dot.contains("style=dashed"));
+    assert_eq!(dot.matches("->").count(), gen.get_connection_genome().len());
+
+    let json = gen.to_json();
+    assert_eq!(
+        json["connection_genome"].as_array().unwrap().len(),
+        gen.get_connection_genome().len()
+    );
+
+    let phen: neat::NeuralNet = (&gen).into();
+    let enabled = gen
+        .get_connection_genome()
+        .iter()
+        .filter(|c| c.enabled)
+        .count();
+    assert_eq!(phen.to_json()["links"].as_array().unwrap().len(), enabled);
+    assert_eq!(phen.to_dot().matches("->").count(), enabled);
+}
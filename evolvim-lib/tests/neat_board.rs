@@ -0,0 +1,144 @@
+extern crate bincode;
+extern crate lib_evolvim;
+
+use lib_evolvim::neat::NeatBrain;
+use lib_evolvim::*;
+
+#[test]
+fn test_neat_board_update() {
+    let mut board = Board::<NeatBrain>::default();
+
+    for _i in 0..200 {
+        board.update(0.001);
+    }
+}
+
+/// Takes several minutes, run it with `cargo test --release -- --ignored`.
+#[test]
+#[ignore]
+fn test_neat_board_long_run() {
+    let mut board = Board::<NeatBrain>::default();
+
+    for _year in 0..500 {
+        for _i in 0..1000 {
+            board.update(0.001);
+        }
+
+        assert!(board.get_population_size() >= board.get_creature_minimum());
+        // Genomes grow, but not out of control.
+        for c in &board.creatures {
+            assert!(c.borrow().brain.metabolic_cost() < 10_000.0);
+        }
+    }
+}
+
+#[test]
+fn test_neat_board_save_and_load() {
+    let mut board = Board::<NeatBrain>::default();
+    board.update(0.001);
+    let population = board.get_population_size();
+
+    let path = std::env::temp_dir().join("evolvim_test_neat_board.bin");
+    board.save_to(&path).unwrap();
+    let mut board = Board::<NeatBrain>::load_from(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(board.get_population_size(), population);
+    board.update(0.001);
+}
+
+#[test]
+fn test_neat_brain_evolve() {
+    let c_1 = HLSoftBody::<NeatBrain>::from(Creature::new_random((100, 100), 0.0));
+    let c_2 = HLSoftBody::<NeatBrain>::from(Creature::new_random((100, 100), 0.0));
+
+    let context = neat::NeatContext::default();
+
+    let mutability = c_1.borrow().brain.get_mutability();
+    let plasticity = c_1.borrow().brain.get_plasticity();
+
+    let _mutated = NeatBrain::recombination_infinite_parents(&vec![c_1.clone()], &context);
+    let mut child = NeatBrain::recombination_infinite_parents(&vec![c_1, c_2], &context);
+
+    // Children of several parents mutate just like those of a single parent, their mutation rate included.
+    assert_ne!(child.get_mutability(), mutability);
+    assert_ne!(child.get_plasticity(), plasticity);
+
+    child.run();
+    child.wants_birth();
+    child.wants_help_birth();
+}
+
+#[test]
+fn test_neat_board_species() {
+    let mut board = Board::<NeatBrain>::default();
+    board.update(0.001);
+
+    let counts = board.get_species_counts();
+    let counted: usize = counts.iter().map(|(_, size)| size).sum();
+    assert_eq!(counted, board.get_population_size());
+
+    let registry = &board.brain_context.species;
+    assert_eq!(registry.get_alive().count(), counts.len());
+    for (id, size) in counts {
+        assert_eq!(registry.get_species(id).unwrap().size, size);
+    }
+}
+
+#[test]
+fn test_neat_brain_memory_round_trip() {
+    let innovations = neat::InnovationRegistry::default();
+    let mut genome = neat::Genome::new_fully_linked();
+    for _i in 0..20 {
+        genome.mutate_add_connection(&innovations);
+        genome.mutate_connection_to_node(&innovations);
+    }
+
+    let mut brain = NeatBrain::from(genome);
+    for _i in 0..5 {
+        brain.run();
+    }
+
+    let bytes = bincode::serialize(&brain).unwrap();
+    let mut loaded: NeatBrain = bincode::deserialize(&bytes).unwrap();
+
+    for _i in 0..5 {
+        brain.run();
+        loaded.run();
+        assert_eq!(loaded.wants_birth(), brain.wants_birth());
+        assert_eq!(loaded.wants_help_birth(), brain.wants_help_birth());
+    }
+}
+
+#[test]
+fn test_neat_brain_learning() {
+    let mut genome = neat::Genome::new_fully_linked();
+    genome.set_plasticity(Plasticity {
+        learning_rate: 100.0,
+        a: 1.0,
+        b: 0.0,
+        c: 0.0,
+        d: 0.5,
+    });
+    let mut brain = NeatBrain::from(genome.clone());
+    let links = brain.to_json()["phenotype"]["links"].clone();
+
+    brain.run();
+    brain.learn(0.001);
+    assert_ne!(brain.to_json()["phenotype"]["links"], links);
+    // The genome stays the same, so children don't inherit what was learned.
+    assert_eq!(brain.get_genome().to_json(), genome.to_json());
+
+    let bytes = bincode::serialize(&brain).unwrap();
+    let loaded: NeatBrain = bincode::deserialize(&bytes).unwrap();
+    assert_eq!(loaded.to_json(), brain.to_json());
+
+    let mut board = Board::<NeatBrain>::default();
+    board.lifetime_learning = true;
+    for c in &board.creatures {
+        c.borrow_mut().brain = NeatBrain::from(genome.clone());
+    }
+    for _i in 0..100 {
+        board.update(0.001);
+    }
+}
@@ -0,0 +1,273 @@
+extern crate lib_evolvim;
+
+use lib_evolvim::constants::*;
+use lib_evolvim::*;
+
+fn count_water(terrain: &Terrain) -> usize {
+    let mut counter = 0;
+
+    for x in 0..terrain.get_width() {
+        for y in 0..terrain.get_height() {
+            if terrain.get_tile_at((x, y)).is_water() {
+                counter += 1;
+            }
+        }
+    }
+
+    counter
+}
+
+#[test]
+fn test_terrain_paint_and_undo() {
+    let mut terrain = Terrain::generate_perlin(DEFAULT_BOARD_SIZE, DEFAULT_NOISE_STEP_SIZE);
+    let climate = Climate::new(DEFAULT_MIN_TEMP, DEFAULT_MAX_TEMP);
+    let mut history = EditHistory::default();
+    let water_before = count_water(&terrain);
+
+    let change = terrain.paint(
+        BoardPreciseCoordinate(50.0, 50.0),
+        5.0,
+        TerrainEdit::Water,
+        0.0,
+        &climate,
+    );
+    history.push(change);
+    assert!(terrain.get_tile_at((50, 50)).is_water());
+
+    assert!(history.undo(&mut terrain));
+    assert_eq!(water_before, count_water(&terrain));
+    assert!(!history.undo(&mut terrain));
+}
+
+#[test]
+fn test_terrain_fill() {
+    let mut terrain = Terrain::generate_perlin(DEFAULT_BOARD_SIZE, DEFAULT_NOISE_STEP_SIZE);
+    let climate = Climate::new(DEFAULT_MIN_TEMP, DEFAULT_MAX_TEMP);
+
+    // Make the whole board land, then fill it with water.
+    terrain.paint(
+        BoardPreciseCoordinate(50.0, 50.0),
+        100.0,
+        TerrainEdit::Land,
+        0.0,
+        &climate,
+    );
+    assert_eq!(count_water(&terrain), 0);
+
+    terrain.fill((0, 0), TerrainEdit::Water, 0.0, &climate);
+    assert_eq!(
+        count_water(&terrain),
+        terrain.get_width() * terrain.get_height()
+    );
+}
+
+#[test]
+fn test_terrain_edits_survive_the_sea() {
+    let mut terrain = Terrain::generate_perlin(DEFAULT_BOARD_SIZE, DEFAULT_NOISE_STEP_SIZE);
+    let climate = Climate::new(DEFAULT_MIN_TEMP, DEFAULT_MAX_TEMP);
+    let sea_level = terrain.get_sea_level();
+    let pos = (50, 50);
+
+    terrain.edit(pos, TerrainEdit::Water, 0.0, &climate);
+    terrain.edit(pos, TerrainEdit::Land, 0.0, &climate);
+    terrain.edit(pos, TerrainEdit::Fertility(0.2), 0.0, &climate);
+
+    // Painted land doesn't flood as soon as the sea rises a little.
+    terrain.set_sea_level(sea_level + 0.1, 0.0, &climate);
+    assert!(!terrain.get_tile_at(pos).is_water());
+
+    // The edited fertility is back when the tile dries out again.
+    terrain.set_sea_level(sea_level + 1.0, 0.0, &climate);
+    assert!(terrain.get_tile_at(pos).is_water());
+    terrain.set_sea_level(sea_level, 0.0, &climate);
+    assert_eq!(terrain.get_tile_at(pos).get_fertility(), 0.2);
+}
+
+#[test]
+fn test_tile_catch_up() {
+    use lib_evolvim::terrain::tile::Tile;
+    let climate = Climate::new(DEFAULT_MIN_TEMP, DEFAULT_MAX_TEMP);
+
+    for &elevation in &[0.3, -0.3] {
+        let mut often = Tile::new(elevation, 0.5, DEFAULT_SEA_LEVEL);
+        often.remove_food_fraction(0.9);
+        let mut once = often.clone();
+
+        // Updating every tick gives the same food as catching up once, whenever the updates happen.
+        for i in 1..=2500 {
+            often.update(i as f64 * 0.001, &climate);
+        }
+        once.update(2.5, &climate);
+
+        for (n, level) in once.get_nutrients().iter() {
+            assert!((level - often.get_nutrient_level(n)).abs() < 1e-9);
+        }
+    }
+}
+
+#[test]
+fn test_nutrient_growth() {
+    use lib_evolvim::terrain::nutrient::{Nutrient, Nutrients};
+    let close = |a: f64, b: f64| (a - b).abs() < 1e-9;
+
+    // Plants and seeds grow on land towards their own maximum at their own pace, algae don't grow there.
+    let mut land = Nutrients::new([1.0, 0.5, 0.5]);
+    land.grow(0.1, 1.0, false);
+    assert!(close(
+        land[Nutrient::Plant],
+        MAX_GROWTH_LEVEL - (MAX_GROWTH_LEVEL - 1.0) * (-0.1 * FOOD_GROWTH_RATE).exp()
+    ));
+    assert!(close(
+        land[Nutrient::Seed],
+        MAX_SEED_LEVEL - (MAX_SEED_LEVEL - 0.5) * (-0.1 * SEED_GROWTH_RATE).exp()
+    ));
+    assert_eq!(land[Nutrient::Algae], 0.5);
+
+    // Only algae grow on water, a less fertile tile grows slower.
+    let mut water = Nutrients::new([1.0, 0.5, 0.5]);
+    water.grow(0.1, 0.5, true);
+    assert_eq!(water[Nutrient::Plant], 1.0);
+    assert_eq!(water[Nutrient::Seed], 0.5);
+    assert!(close(
+        water[Nutrient::Algae],
+        MAX_ALGAE_LEVEL - (MAX_ALGAE_LEVEL - 0.5) * (-0.1 * 0.5 * ALGAE_GROWTH_RATE).exp()
+    ));
+
+    // In winter seeds survive a lot better than plants.
+    let mut winter = Nutrients::new([1.0, 1.0, 1.0]);
+    winter.grow(-0.1, 1.0, false);
+    assert!(close(
+        winter[Nutrient::Plant],
+        (-0.1 * FOOD_GROWTH_RATE).exp()
+    ));
+    assert!(close(
+        winter[Nutrient::Seed],
+        (-0.1 * SEED_DECAY_RATE).exp()
+    ));
+    assert!(winter[Nutrient::Seed] > winter[Nutrient::Plant]);
+    winter.grow(-0.1, 1.0, true);
+    assert!(close(
+        winter[Nutrient::Algae],
+        (-0.1 * ALGAE_DECAY_RATE).exp()
+    ));
+
+    // Food added above the maximum doesn't grow any further.
+    let mut full = Nutrients::new([MAX_GROWTH_LEVEL + 1.0, 0.0, 0.0]);
+    full.grow(0.1, 1.0, false);
+    assert_eq!(full[Nutrient::Plant], MAX_GROWTH_LEVEL + 1.0);
+}
+
+#[test]
+fn test_diet_efficiency() {
+    use lib_evolvim::terrain::nutrient::{Nutrient, Nutrients};
+    let close = |a: f64, b: f64| (a - b).abs() < 1e-6;
+
+    assert!(close(Diet::default().get_efficiency(), 1.0));
+
+    // Eating a single nutrient only counts its own share of the requirement, older meals are forgotten.
+    for n in Nutrient::ALL.iter() {
+        let mut meal = Nutrients::default();
+        meal[*n] = 1.0;
+        let mut diet = Diet::default();
+        diet.record(&meal, 1.0);
+
+        assert!(close(diet.get_share(*n), 1.0));
+        assert!(close(
+            diet.get_efficiency(),
+            DIET_BASE_EFFICIENCY + (1.0 - DIET_BASE_EFFICIENCY) * DIET_REQUIREMENT[n.index()]
+        ));
+    }
+
+    // Too much of one nutrient doesn't make up for too little of another.
+    let mut diet = Diet::default();
+    diet.record(&Nutrients::new([0.5, 0.5, 0.0]), 1.0);
+    assert!(close(
+        diet.get_efficiency(),
+        DIET_BASE_EFFICIENCY + (1.0 - DIET_BASE_EFFICIENCY) * (0.5 + 0.3)
+    ));
+    diet.record(&Nutrients::new(DIET_REQUIREMENT), 1.0);
+    assert!(close(diet.get_efficiency(), 1.0));
+}
+
+#[test]
+fn test_eating_nutrients() {
+    use lib_evolvim::terrain::nutrient::Nutrient;
+
+    let mut terrain = Terrain::generate_perlin(DEFAULT_BOARD_SIZE, DEFAULT_NOISE_STEP_SIZE);
+    let climate = Climate::new(DEFAULT_MIN_TEMP, DEFAULT_MAX_TEMP);
+    let land = (0..100)
+        .flat_map(|x| (0..100).map(move |y| (x, y)))
+        .find(|pos| !terrain.get_tile_at(*pos).is_water())
+        .unwrap();
+    let water = (0..100)
+        .flat_map(|x| (0..100).map(move |y| (x, y)))
+        .find(|pos| terrain.get_tile_at(*pos).is_water())
+        .unwrap();
+    let time = 0.3;
+
+    for &pos in &[land, water] {
+        let before = terrain
+            .get_updated_tile_at(pos, time, &climate)
+            .get_nutrients()
+            .clone();
+        assert!(before.total() > 0.0);
+
+        // Eating takes the same fraction of every nutrient.
+        let eaten = terrain.remove_food_fraction_at(pos, 0.25, time, &climate);
+        let after = terrain.get_tile_at(pos);
+        for n in Nutrient::ALL.iter() {
+            assert!((eaten[*n] - before[*n] * 0.25).abs() < 1e-9);
+            assert!((after.get_nutrient_level(*n) - before[*n] * 0.75).abs() < 1e-9);
+        }
+
+        // A creature's diet ends up with the nutrients of the tile it eats from.
+        let mut creature = Creature::<Brain>::new_random(DEFAULT_BOARD_SIZE, time);
+        creature.eat(1.0, 1.0, time, &climate, &mut terrain, pos);
+        for n in Nutrient::ALL.iter() {
+            let share = creature.get_diet().get_share(*n);
+            assert!((share - before[*n] / before.total()).abs() < 1e-6);
+        }
+    }
+}
+
+#[test]
+fn test_scheduled_sea_level() {
+    let mut terrain = Terrain::generate_perlin(DEFAULT_BOARD_SIZE, DEFAULT_NOISE_STEP_SIZE);
+    let mut climate = Climate::new(DEFAULT_MIN_TEMP, DEFAULT_MAX_TEMP);
+    climate.set_sea_level_settings(SeaLevel {
+        schedule: vec![(0.0, DEFAULT_SEA_LEVEL), (1.0, 0.5), (2.0, -0.2)],
+        ..SeaLevel::default()
+    });
+    let water_before = count_water(&terrain);
+
+    // Every tile below the sea is water and every tile above it is land.
+    let follows = |terrain: &Terrain, sea_level: f64| {
+        (0..terrain.get_width())
+            .flat_map(|x| (0..terrain.get_height()).map(move |y| (x, y)))
+            .all(|pos| {
+                let tile = terrain.get_tile_at(pos);
+                tile.is_water() == (tile.get_elevation() < sea_level)
+            })
+    };
+
+    // A change below the threshold isn't worth updating every tile for.
+    let time = SEA_LEVEL_UPDATE_THRESHOLD;
+    climate.update(time);
+    terrain.follow_sea_level(time, &climate);
+    assert_eq!(terrain.get_sea_level(), DEFAULT_SEA_LEVEL);
+    assert_eq!(count_water(&terrain), water_before);
+
+    // The rising sea floods the low land.
+    climate.update(1.0);
+    terrain.follow_sea_level(1.0, &climate);
+    assert_eq!(terrain.get_sea_level(), 0.5);
+    assert!(count_water(&terrain) > water_before);
+    assert!(follows(&terrain, 0.5));
+
+    // And falling below where it started dries out the shallow sea.
+    climate.update(2.0);
+    terrain.follow_sea_level(2.0, &climate);
+    assert_eq!(terrain.get_sea_level(), -0.2);
+    assert!(count_water(&terrain) < water_before);
+    assert!(follows(&terrain, -0.2));
+}
@@ -1,16 +1,27 @@
 extern crate clap;
 extern crate ctrlc;
 extern crate lib_evolvim;
+extern crate serde;
+extern crate serde_json;
 
-use clap::{App, Arg};
-use lib_evolvim::Board;
-use std::sync::atomic::Ordering;
-
-// type BrainType = lib_evolvim::neat::NeatBrain;
-type BrainType = lib_evolvim::Brain;
+use clap::{App, Arg, ArgMatches};
+use lib_evolvim::brain::{
+    BrainShape, Crossover, ExportBrain, GenerateRandom, NeuralNet, RecombinationConfig,
+    RecombinationInfinite, WeightMutation,
+};
+use lib_evolvim::constants::{
+    DEFAULT_BOARD_SIZE, DEFAULT_CREATURE_MINIMUM, DEFAULT_MAX_TEMP, DEFAULT_MIN_TEMP,
+    DEFAULT_NOISE_STEP_SIZE,
+};
+use lib_evolvim::disaster::DisasterScheduler;
+use lib_evolvim::{Board, BodyGenome};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 
 fn main() {
-    let abort_reader = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+    let abort_reader = Arc::new(AtomicBool::new(false));
     let abort_writer = abort_reader.clone();
 
     ctrlc::set_handler(move || abort_writer.store(true, Ordering::SeqCst))
@@ -58,20 +69,198 @@ fn main() {
                 .takes_value(false)
                 .help("Output a summary of this world"),
         )
+        .arg(
+            Arg::with_name("disasters")
+                .long("disasters")
+                .value_name("FILE")
+                .takes_value(true)
+                .help("Load scheduled and random disasters from this JSON file"),
+        )
+        .arg(
+            Arg::with_name("brain-cost")
+                .long("brain-cost")
+                .value_name("ENERGY")
+                .takes_value(true)
+                .help(
+                    "The energy creatures lose every year for every weight or gene in their brain",
+                ),
+        )
+        .arg(
+            Arg::with_name("learning")
+                .long("learning")
+                .takes_value(false)
+                .help("Let brains change their weights during their life, the way they learn evolves"),
+        )
+        .arg(
+            Arg::with_name("crossover")
+                .long("crossover")
+                .value_name("OPERATOR")
+                .takes_value(true)
+                .possible_values(&["axons", "uniform", "neuron", "single-point", "two-point", "blend"])
+                .help("How feed-forward brains combine the weights of their parents, axons is the default"),
+        )
+        .arg(
+            Arg::with_name("weight-mutation")
+                .long("weight-mutation")
+                .value_name("OPERATOR")
+                .takes_value(true)
+                .possible_values(&["processing", "gaussian"])
+                .help("How feed-forward and recurrent brains mutate their weights, processing is the default"),
+        )
+        .arg(
+            Arg::with_name("hidden-layers")
+                .long("hidden-layers")
+                .value_name("SIZES")
+                .takes_value(true)
+                .help("The hidden layer sizes of new random feed-forward brains, e.g. 16,8, 10 is the default"),
+        )
+        .arg(
+            Arg::with_name("export-brain")
+                .long("export-brain")
+                .value_name("FILE")
+                .takes_value(true)
+                .help("Export the brain of a creature to a .dot (Graphviz) or .json file"),
+        )
+        .arg(
+            Arg::with_name("creature")
+                .long("creature")
+                .value_name("WHICH")
+                .takes_value(true)
+                .requires("export-brain")
+                .help("The creature to export: oldest (the default), biggest or its place in the list of creatures"),
+        )
+        .arg(
+            Arg::with_name("brain")
+                .long("brain")
+                .value_name("TYPE")
+                .takes_value(true)
+                .possible_values(&["feed-forward", "recurrent", "neat", "hyperneat", "mixed"])
+                .default_value("feed-forward")
+                .help("The kind of brain creatures have, has to match the input file"),
+        )
         .get_matches();
 
+    match matches.value_of("brain").unwrap() {
+        "recurrent" => run::<lib_evolvim::RecurrentBrain>(
+            &matches,
+            abort_reader,
+            |_| None,
+            |c| vec![&mut c.mutation],
+        ),
+        "neat" => {
+            run::<lib_evolvim::neat::NeatBrain>(&matches, abort_reader, |_| None, |_| Vec::new())
+        }
+        "hyperneat" => run::<lib_evolvim::neat::HyperNeatBrain>(
+            &matches,
+            abort_reader,
+            |_| None,
+            |_| Vec::new(),
+        ),
+        "mixed" => run::<lib_evolvim::MixedBrain>(
+            &matches,
+            abort_reader,
+            |c| Some(&mut c.feed_forward),
+            |c| vec![&mut c.feed_forward.mutation, &mut c.recurrent.mutation],
+        ),
+        _ => run::<lib_evolvim::Brain>(
+            &matches,
+            abort_reader,
+            |c| Some(c),
+            |c| vec![&mut c.mutation],
+        ),
+    }
+}
+
+/// `recombination` returns the part of the context that decides how feed-forward brains are recombined, if there is one.
+/// `weight_mutations` returns every `WeightMutation` in the context.
+fn run<B>(
+    matches: &ArgMatches,
+    abort_reader: Arc<AtomicBool>,
+    recombination: fn(&mut B::Context) -> Option<&mut RecombinationConfig>,
+    weight_mutations: fn(&mut B::Context) -> Vec<&mut WeightMutation>,
+) where
+    B: NeuralNet
+        + GenerateRandom
+        + RecombinationInfinite
+        + ExportBrain
+        + Serialize
+        + DeserializeOwned,
+{
     let output_file = if matches.is_present("save") {
         matches.value_of("input")
     } else {
         matches.value_of("output")
     };
 
-    let mut board: Board<BrainType> = if let Some(name) = matches.value_of("input") {
-        Board::<BrainType>::load_from(name).unwrap()
+    // Check the export arguments before simulating, so a typo doesn't throw away a long run.
+    let export = match matches.value_of("export-brain") {
+        Some(file) => {
+            let which = matches.value_of("creature").unwrap_or("oldest");
+            match (
+                BrainFormat::from_file(file),
+                CreatureChoice::from_arg(which),
+            ) {
+                (Ok(format), Ok(choice)) => Some((file, format, choice)),
+                (Err(e), _) | (_, Err(e)) => {
+                    eprintln!("{}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        None => None,
+    };
+
+    let shape = match matches.value_of("hidden-layers").map(parse_hidden_layers) {
+        Some(Ok(shape)) => Some(shape),
+        Some(Err(e)) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+        None => None,
+    };
+
+    // The brain settings are applied before a new board is generated, so its first creatures get them too.
+    let mut board: Board<B> = if let Some(name) = matches.value_of("input") {
+        let mut board = Board::<B>::load_from(name).unwrap();
+        configure_brains(
+            matches,
+            &mut board.brain_context,
+            recombination,
+            weight_mutations,
+            shape,
+        );
+        board
     } else {
-        Board::default()
+        let mut context = B::Context::default();
+        configure_brains(
+            matches,
+            &mut context,
+            recombination,
+            weight_mutations,
+            shape,
+        );
+        Board::new_random_with_context(
+            DEFAULT_BOARD_SIZE,
+            DEFAULT_NOISE_STEP_SIZE,
+            DEFAULT_CREATURE_MINIMUM,
+            DEFAULT_MIN_TEMP,
+            DEFAULT_MAX_TEMP,
+            context,
+        )
     };
 
+    if let Some(name) = matches.value_of("disasters") {
+        board.disasters = DisasterScheduler::load_from(name).unwrap();
+    }
+
+    if let Some(cost) = matches.value_of("brain-cost") {
+        board.brain_cost = cost.parse().unwrap();
+    }
+
+    if matches.is_present("learning") {
+        board.lifetime_learning = true;
+    }
+
     if let Some(years) = matches.value_of("iterations") {
         let mut years: usize = years.parse().unwrap();
 
@@ -99,9 +288,253 @@ fn main() {
     if matches.is_present("info") {
         println!("Year: {}", board.get_time() as usize);
         println!("Population: {}", board.creatures.len());
+        println!("Sea level: {:.3}", board.climate.get_sea_level());
+
+        let species = board.get_species_counts();
+        if !species.is_empty() {
+            println!("Species: {}", species.len());
+            for (id, size) in species.iter().take(10) {
+                println!("  species {}: {} creatures", id, size);
+            }
+        }
+
+        // Every kind of brain measures its mutability differently, so they are averaged separately.
+        let architectures = board.get_architecture_counts();
+        println!("Architectures:");
+        for (architecture, size) in &architectures {
+            println!("  {}: {} creatures", architecture, size);
+        }
+        for (architecture, _) in &architectures {
+            let brains: Vec<_> = board
+                .creatures
+                .iter()
+                .map(|c| c.borrow())
+                .filter(|c| c.brain.get_architecture() == *architecture)
+                .collect();
+            let mutabilities: Vec<f64> = brains
+                .iter()
+                .filter_map(|c| c.brain.get_mutability())
+                .collect();
+            let learning_rates: Vec<f64> = brains
+                .iter()
+                .filter_map(|c| c.brain.get_plasticity())
+                .map(|p| p.learning_rate)
+                .collect();
+
+            let name = if architectures.len() > 1 {
+                format!(" of {}", architecture)
+            } else {
+                String::new()
+            };
+            print_average(&format!("mutability{}", name), &mutabilities);
+            print_average(&format!("learning rate{}", name), &learning_rates);
+        }
+
+        if !board.creatures.is_empty() {
+            let creatures: Vec<_> = board.creatures.iter().map(|c| c.borrow()).collect();
+            let bodies: Vec<&BodyGenome> = creatures.iter().map(|c| c.get_body_genome()).collect();
+
+            // Bodies without a top speed would make its average infinite, so they are counted instead.
+            let top_speeds: Vec<f64> = bodies
+                .iter()
+                .map(|b| b.max_speed)
+                .filter(|s| s.is_finite())
+                .collect();
+
+            println!("Average body:");
+            for (name, value) in BodyGenome::average(&bodies).get_traits().iter() {
+                if *name != "max speed" {
+                    println!("  {}: {:.3}", name, value);
+                } else {
+                    let share = top_speeds.len() as f64 / bodies.len() as f64;
+                    println!("  with a top speed: {:.1}%", share * 100.0);
+                    if !top_speeds.is_empty() {
+                        let average = top_speeds.iter().sum::<f64>() / top_speeds.len() as f64;
+                        println!("  {}: {:.3}", name, average);
+                    }
+                }
+            }
+        }
+
+        if !board.event_log.is_empty() {
+            println!("Events:");
+            for event in board.event_log.get_events() {
+                println!("  {}", event);
+            }
+        }
     }
 
+    // `save_to` consumes the board, so the brain is rendered before saving and written to its file after.
+    let export =
+        export.map(|(file, format, choice)| (file, export_brain(&mut board, choice, format)));
+
     if let Some(name) = output_file {
         board.save_to(name).unwrap();
     }
+
+    if let Some((file, contents)) = export {
+        let written = contents.and_then(|c| std::fs::write(file, c).map_err(|e| e.to_string()));
+        if let Err(e) = written {
+            eprintln!("Couldn't export a brain: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Applies `--crossover`, `--weight-mutation` and `--hidden-layers` to the context of the brains.
+fn configure_brains<C>(
+    matches: &ArgMatches,
+    context: &mut C,
+    recombination: fn(&mut C) -> Option<&mut RecombinationConfig>,
+    weight_mutations: fn(&mut C) -> Vec<&mut WeightMutation>,
+    shape: Option<BrainShape>,
+) {
+    if let Some(name) = matches.value_of("weight-mutation") {
+        let mutation = match name {
+            "gaussian" => WeightMutation::Gaussian,
+            _ => WeightMutation::Processing,
+        };
+
+        let targets = weight_mutations(context);
+        if targets.is_empty() {
+            eprintln!("Only feed-forward and recurrent brains use --weight-mutation, ignoring it.");
+        }
+        for target in targets {
+            *target = mutation;
+        }
+    }
+
+    if !matches.is_present("crossover") && shape.is_none() {
+        return;
+    }
+
+    match recombination(context) {
+        Some(config) => {
+            if let Some(name) = matches.value_of("crossover") {
+                config.crossover = match name {
+                    "uniform" => Crossover::Uniform,
+                    "neuron" => Crossover::Neuron,
+                    "single-point" => Crossover::Points(1),
+                    "two-point" => Crossover::Points(2),
+                    "blend" => Crossover::Blend,
+                    _ => Crossover::Axons,
+                };
+            }
+            if let Some(shape) = shape {
+                config.shape = shape;
+            }
+        }
+        None => eprintln!(
+            "Only feed-forward brains use --crossover and --hidden-layers, ignoring them."
+        ),
+    }
+}
+
+/// Parses the value of `--hidden-layers`, a comma separated list of layer sizes. An empty list gives no hidden layers.
+fn parse_hidden_layers(sizes: &str) -> Result<BrainShape, String> {
+    if sizes.trim().is_empty() {
+        return Ok(BrainShape::new(Vec::new()));
+    }
+
+    sizes
+        .split(',')
+        .map(|size| match size.trim().parse::<usize>() {
+            Ok(size) if size > 0 => Ok(size),
+            _ => Err(format!(
+                "--hidden-layers has to be a list of positive numbers like 16,8, not {}",
+                sizes
+            )),
+        })
+        .collect::<Result<Vec<usize>, String>>()
+        .map(BrainShape::new)
+}
+
+/// Prints the average of `values`, unless there are none.
+fn print_average(name: &str, values: &[f64]) {
+    if !values.is_empty() {
+        let average = values.iter().sum::<f64>() / values.len() as f64;
+        println!("Average {}: {}", name, average);
+    }
+}
+
+/// The formats brains can be exported to.
+enum BrainFormat {
+    Dot,
+    Json,
+}
+
+impl BrainFormat {
+    /// Returns the format belonging to the extension of `file`.
+    fn from_file(file: &str) -> Result<Self, String> {
+        if file.ends_with(".json") {
+            Ok(BrainFormat::Json)
+        } else if file.ends_with(".dot") || file.ends_with(".gv") {
+            Ok(BrainFormat::Dot)
+        } else {
+            Err(format!(
+                "Brains can only be exported to .dot, .gv or .json files, not to {}",
+                file
+            ))
+        }
+    }
+}
+
+/// The creature whose brain is exported.
+enum CreatureChoice {
+    Oldest,
+    Biggest,
+    /// The place of the creature in `Board::creatures`.
+    Index(usize),
+}
+
+impl CreatureChoice {
+    /// Parses the value of `--creature`.
+    fn from_arg(which: &str) -> Result<Self, String> {
+        match which {
+            "oldest" => Ok(CreatureChoice::Oldest),
+            "biggest" => Ok(CreatureChoice::Biggest),
+            index => index.parse().map(CreatureChoice::Index).map_err(|_| {
+                format!(
+                    "--creature has to be oldest, biggest or a number, not {}",
+                    which
+                )
+            }),
+        }
+    }
+}
+
+/// Returns the brain of the creature described by `choice` in `format`.
+fn export_brain<B: NeuralNet + ExportBrain>(
+    board: &mut Board<B>,
+    choice: CreatureChoice,
+    format: BrainFormat,
+) -> Result<String, String> {
+    if board.creatures.is_empty() {
+        return Err("there are no creatures left".to_string());
+    }
+
+    match choice {
+        CreatureChoice::Oldest => board.select_oldest(),
+        CreatureChoice::Biggest => board.select_biggest(),
+        CreatureChoice::Index(index) => {
+            let creature = board
+                .creatures
+                .get(index)
+                .ok_or_else(|| {
+                    format!(
+                        "there is no creature {}, there are only {} creatures",
+                        index,
+                        board.creatures.len()
+                    )
+                })?
+                .clone();
+            board.selected_creature.select(creature);
+        }
+    }
+
+    let creature = board.selected_creature.0.as_ref().unwrap().borrow();
+    Ok(match format {
+        BrainFormat::Json => serde_json::to_string_pretty(&creature.brain.to_json()).unwrap(),
+        BrainFormat::Dot => creature.brain.to_dot(),
+    })
 }
@@ -9,8 +9,8 @@ extern crate lib_evolvim;
 
 pub mod ui;
 pub mod view;
-pub use self::ui::{Dragging, MouseCoordinate};
-pub use self::view::View;
+pub use self::ui::{Dragging, MouseCoordinate, Tool};
+pub use self::view::{DisplayMode, View};
 
 use self::graphics::character::CharacterCache;
 use self::graphics::text::Text;
@@ -104,13 +104,14 @@ pub fn draw_lines<G, C>(
     }
 }
 
-pub fn draw_terrain<C, G>(
+pub fn draw_terrain<B, C, G>(
     terrain: &Terrain,
     context: Context,
     graphics: &mut G,
     glyphs: &mut C,
-    view: &View,
+    view: &View<B>,
 ) where
+    B: lib_evolvim::brain::NeuralNet,
     C: CharacterCache,
     C::Error: Debug,
     G: Graphics<Texture = C::Texture>,
@@ -164,7 +165,7 @@ pub fn draw_creature<B: lib_evolvim::brain::NeuralNet, G: Graphics>(
     creature: &Creature<B>,
     context: Context,
     graphics: &mut G,
-    view: &View,
+    view: &View<B>,
 ) {
     let size = view.get_tile_size();
     let transform = context
@@ -172,7 +173,11 @@ pub fn draw_creature<B: lib_evolvim::brain::NeuralNet, G: Graphics>(
         .trans(-view.get_precise_x() * size, -view.get_precise_y() * size);
 
     let radius = creature.get_radius();
-    let color = from_hsba([creature.get_mouth_hue() as f32, 1.0, 1.0, 1.0]);
+    let hue = match (view.get_display_mode(), creature.brain.get_species()) {
+        (DisplayMode::Species, Some(species)) => get_species_hue(species),
+        _ => creature.get_mouth_hue(),
+    };
+    let color = from_hsba([hue as f32, 1.0, 1.0, 1.0]);
 
     let rect = [
         // This gives the upper-left corner of the circle so subtract the radius.
@@ -192,7 +197,7 @@ pub fn draw_details_creature<B, C, G>(
     context: Context,
     graphics: &mut G,
     glyphs: &mut C,
-    view: &View,
+    view: &View<B>,
 ) where
     B: lib_evolvim::brain::NeuralNet + DrawableBrain,
     C: CharacterCache,
@@ -218,6 +223,10 @@ pub fn draw_details_creature<B, C, G>(
         creature.get_py()
     ));
     text_to_draw.push(format!("Speed: {:.3}", creature.get_total_velocity()));
+    text_to_draw.push(format!(
+        "Diet: {:.0}%",
+        creature.get_diet().get_efficiency() * 100.0
+    ));
 
     draw_lines(
         text_to_draw,
@@ -277,7 +286,7 @@ impl DrawableBrain for lib_evolvim::neat::NeatBrain {
     }
 }
 
-impl DrawableBrain for Brain {
+impl DrawableBrain for lib_evolvim::neat::HyperNeatBrain {
     fn draw_brain<C, G>(&self, context: Context, graphics: &mut G, glyphs: &mut C)
     where
         C: CharacterCache,
@@ -285,47 +294,102 @@ impl DrawableBrain for Brain {
         G: Graphics<Texture = C::Texture>,
     {
         let text = Text::new(18);
-        let output = self
-            .get_output()
-            .iter()
-            .map(|value| format!("{:.3}", value))
-            .collect();
-        let hidden = self
-            .get_hidden_layer()
-            .iter()
-            .map(|val| format!("{:.3}", val))
-            .collect();
-        let input = self
-            .get_input_layer()
-            .iter()
-            .map(|val| format!("{:.3}", val))
+        let info = self
+            .get_ordered_key_value_pairs()
+            .into_iter()
+            .map(|(key, value)| format!("{}: {}", key, value))
             .collect();
-        let info = self.intentions();
+        draw_lines(info, 20.0, 100.0, context, text, glyphs, graphics);
+    }
+}
 
-        draw_lines(input, 20.0, 100.0, context, text, glyphs, graphics);
-        draw_lines(
-            hidden,
-            20.0,
-            100.0,
-            context.trans(120.0, 0.0),
-            text,
-            glyphs,
-            graphics,
-        );
+impl DrawableBrain for MixedBrain {
+    fn draw_brain<C, G>(&self, context: Context, graphics: &mut G, glyphs: &mut C)
+    where
+        C: CharacterCache,
+        C::Error: Debug,
+        G: Graphics<Texture = C::Texture>,
+    {
+        match self {
+            MixedBrain::FeedForward(b) => b.draw_brain(context, graphics, glyphs),
+            MixedBrain::Recurrent(b) => b.draw_brain(context, graphics, glyphs),
+            MixedBrain::Neat(b) => b.draw_brain(context, graphics, glyphs),
+            MixedBrain::HyperNeat(b) => b.draw_brain(context, graphics, glyphs),
+        }
+    }
+}
+
+impl DrawableBrain for Brain {
+    fn draw_brain<C, G>(&self, context: Context, graphics: &mut G, glyphs: &mut C)
+    where
+        C: CharacterCache,
+        C::Error: Debug,
+        G: Graphics<Texture = C::Texture>,
+    {
+        let text = Text::new(18);
+        let layers = self.get_layers();
+
+        for (i, layer) in layers.iter().enumerate() {
+            let values = layer.iter().map(|val| format!("{:.3}", val)).collect();
+
+            draw_lines(
+                values,
+                20.0,
+                100.0,
+                context.trans(120.0 * i as f64, 0.0),
+                text,
+                glyphs,
+                graphics,
+            );
+        }
+
+        let info = self.intentions();
         draw_lines(
-            output,
+            info,
             20.0,
             100.0,
-            context.trans(240.0, 0.0),
+            context.trans(120.0 * layers.len() as f64, 0.0),
             text,
             glyphs,
             graphics,
         );
+    }
+}
+
+impl DrawableBrain for RecurrentBrain {
+    fn draw_brain<C, G>(&self, context: Context, graphics: &mut G, glyphs: &mut C)
+    where
+        C: CharacterCache,
+        C::Error: Debug,
+        G: Graphics<Texture = C::Texture>,
+    {
+        let text = Text::new(18);
+        let layers = [
+            self.get_input_layer(),
+            self.get_hidden_state(),
+            self.get_output(),
+        ];
+
+        for (i, layer) in layers.iter().enumerate() {
+            let values = layer.iter().map(|val| format!("{:.3}", val)).collect();
+
+            draw_lines(
+                values,
+                20.0,
+                100.0,
+                context.trans(120.0 * i as f64, 0.0),
+                text,
+                glyphs,
+                graphics,
+            );
+        }
+
+        let info = self.intentions();
         draw_lines(
             info,
             20.0,
             100.0,
-            context.trans(360.0, 0.0),
+            context.trans(120.0 * layers.len() as f64, 0.0),
             text,
             glyphs,
             graphics,
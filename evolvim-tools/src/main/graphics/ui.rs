@@ -4,6 +4,8 @@ use super::*;
 pub enum Dragging {
     /// The user is currently dragging the board around.
     Board,
+    /// The user is currently painting the terrain with a brush.
+    Brush,
     // /// The user is currently dragging the minimum temperature around.
     // MinTemperature,
     // /// The user is currently dragging the maximum temperature around.
@@ -12,6 +14,57 @@ pub enum Dragging {
     None,
 }
 
+/// Determines what clicking on the board does.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Tool {
+    /// Select creatures and drag the board around.
+    Select,
+    /// Paint the terrain, also used for filling regions.
+    Brush(TerrainEdit),
+}
+
+/// All tools in the order they are cycled through.
+const TOOLS: [Tool; 11] = [
+    Tool::Select,
+    Tool::Brush(TerrainEdit::Water),
+    Tool::Brush(TerrainEdit::Land),
+    Tool::Brush(TerrainEdit::Fertility(1.0)),
+    Tool::Brush(TerrainEdit::Fertility(0.0)),
+    Tool::Brush(TerrainEdit::FoodType(0.0)),
+    Tool::Brush(TerrainEdit::FoodType(0.2)),
+    Tool::Brush(TerrainEdit::FoodType(0.4)),
+    Tool::Brush(TerrainEdit::FoodType(0.6)),
+    Tool::Brush(TerrainEdit::FoodType(0.8)),
+    Tool::Brush(TerrainEdit::ClearFood),
+];
+
+impl Default for Tool {
+    fn default() -> Self {
+        Tool::Select
+    }
+}
+
+impl Tool {
+    /// Returns the tool after this one, wraps around to `Tool::Select`.
+    pub fn next(self) -> Tool {
+        let current = TOOLS.iter().position(|t| *t == self).unwrap_or(0);
+
+        TOOLS[(current + 1) % TOOLS.len()]
+    }
+
+    pub fn get_name(&self) -> String {
+        match self {
+            Tool::Select => String::from("select"),
+            Tool::Brush(TerrainEdit::Water) => String::from("water brush"),
+            Tool::Brush(TerrainEdit::Land) => String::from("land brush"),
+            Tool::Brush(TerrainEdit::Fertility(f)) => format!("fertility {:.1} brush", f),
+            Tool::Brush(TerrainEdit::FoodType(f)) => format!("food type {:.1} brush", f),
+            Tool::Brush(TerrainEdit::ClearFood) => String::from("clear food brush"),
+            Tool::Brush(TerrainEdit::GrowthMultiplier(m)) => format!("growth {:.1} brush", m),
+        }
+    }
+}
+
 pub struct MouseCoordinate(f64, f64);
 
 impl MouseCoordinate {
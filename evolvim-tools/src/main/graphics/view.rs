@@ -1,5 +1,4 @@
 use super::*;
-use crate::BrainType;
 use std::ops::Range;
 
 /// The view part of MVC (Model-View-Controller), currently takes on jobs for the controller too.
@@ -7,7 +6,7 @@ use std::ops::Range;
 /// TODO: Provide adequate error handling when the mouse leaves the window.
 ///
 /// TODO 2: Move the "controller" parts over to another struct.
-pub struct View {
+pub struct View<B: NeuralNet> {
     precise_x: f64,
     precise_y: f64,
 
@@ -20,15 +19,21 @@ pub struct View {
     _base_tile_width: f64,
     tile_width: f64,
 
-    pub board: Board<BrainType>,
+    pub board: Board<B>,
 
     pub mouse: MouseCoordinate,
 
     drag: Dragging,
     mode: DisplayMode,
+
+    tool: Tool,
+    brush_radius: f64,
+    edit_history: EditHistory,
+    /// The changes made since the mouse was pressed, these are undone together.
+    stroke: TerrainChange,
 }
 
-impl Default for View {
+impl<B: NeuralNet + GenerateRandom> Default for View<B> {
     fn default() -> Self {
         let board = Board::default();
         let base_tile_width = 100.0;
@@ -52,14 +57,28 @@ impl Default for View {
 
             drag: Dragging::None,
             mode: DisplayMode::default(),
+
+            tool: Tool::default(),
+            brush_radius: 1.0,
+            edit_history: EditHistory::default(),
+            stroke: TerrainChange::default(),
         }
     }
 }
 
-impl View {
+impl<B: NeuralNet> View<B> {
     pub fn on_mouse_release(&mut self) {
         use self::Dragging::*;
 
+        if let Brush = self.drag {
+            // Finish the brush stroke
+            self.drag = None;
+            let stroke = std::mem::replace(&mut self.stroke, TerrainChange::default());
+            self.edit_history.push(stroke);
+
+            return;
+        }
+
         self.drag = None;
 
         if let Some(exact_pos) = self.mouse.into_board_precise_coordinate(
@@ -91,7 +110,13 @@ impl View {
     pub fn on_mouse_press(&mut self) {
         use self::Dragging::*;
 
-        self.drag = Board;
+        match self.tool {
+            Tool::Select => self.drag = Board,
+            Tool::Brush(edit) => {
+                self.drag = Brush;
+                self.paint_at_mouse(edit);
+            }
+        }
     }
 
     pub fn on_mouse_move(&mut self, change_x: f64, change_y: f64) {
@@ -108,24 +133,94 @@ impl View {
 
     pub fn update_mouse(&mut self, x: f64, y: f64) {
         self.mouse = MouseCoordinate::new(x, y);
+
+        if let Dragging::Brush = self.drag {
+            if let Tool::Brush(edit) = self.tool {
+                self.paint_at_mouse(edit);
+            }
+        }
+    }
+
+    /// Switches to the next `Tool`.
+    pub fn next_tool(&mut self) {
+        self.tool = self.tool.next();
+    }
+
+    pub fn change_brush_radius(&mut self, change: f64) {
+        self.brush_radius = (self.brush_radius + change).max(0.0);
+    }
+
+    /// Applies the current brush to the whole water or land region under the mouse.
+    pub fn fill_at_mouse(&mut self) {
+        if let Tool::Brush(edit) = self.tool {
+            if let Some(pos) = self.mouse.into_board_coordinate(
+                self.get_precise_x(),
+                self.get_precise_y(),
+                self.get_tile_size(),
+                self.board.get_board_size(),
+            ) {
+                let time = self.board.get_time();
+                let change = self
+                    .board
+                    .terrain
+                    .fill(pos, edit, time, &self.board.climate);
+
+                self.edit_history.push(change);
+            }
+        }
+    }
+
+    /// Undoes the latest brush stroke or fill.
+    pub fn undo_edit(&mut self) {
+        self.edit_history.undo(&mut self.board.terrain);
+    }
+
+    fn paint_at_mouse(&mut self, edit: TerrainEdit) {
+        if let Some(pos) = self.mouse.into_board_precise_coordinate(
+            self.get_precise_x(),
+            self.get_precise_y(),
+            self.get_tile_size(),
+            self.board.get_board_size(),
+        ) {
+            let time = self.board.get_time();
+            let change =
+                self.board
+                    .terrain
+                    .paint(pos, self.brush_radius, edit, time, &self.board.climate);
+
+            self.stroke.merge(change);
+        }
     }
 
     pub fn switch_display_mode(&mut self) {
         use self::DisplayMode::*;
 
         self.mode = match self.mode {
-            Normal => Tiles,
+            Normal => Species,
+            Species => Tiles,
             Tiles => None,
             None => Normal,
         };
     }
+
+    pub fn get_display_mode(&self) -> &DisplayMode {
+        &self.mode
+    }
 }
 
-impl View {
+impl<B: NeuralNet> View<B> {
     pub fn get_tile_size(&self) -> f64 {
         return self.tile_width;
     }
 
+    /// Returns a description of the current `Tool`, including the brush radius.
+    pub fn get_tool_description(&self) -> String {
+        match self.tool {
+            Tool::Select => self.tool.get_name(),
+            Tool::Brush(_) => format!("{} ({:.0})", self.tool.get_name(), self.brush_radius),
+        }
+    }
+
     pub fn get_x(&self) -> usize {
         return self.precise_x.floor().max(0.0) as usize;
     }
@@ -169,9 +264,9 @@ impl View {
     }
 }
 
-impl View {
+impl<B: NeuralNet + DrawableBrain> View<B> {
     pub fn prepare_for_drawing(&mut self) {
-        if self.mode == DisplayMode::Normal || self.mode == DisplayMode::Tiles {
+        if self.mode != DisplayMode::None {
             let time = self.board.get_time();
             let x_range = self.get_x_range();
             let y_range = self.get_y_range();
@@ -207,7 +302,7 @@ impl View {
         use self::DisplayMode::*;
 
         match self.mode {
-            Normal => {
+            Normal | Species => {
                 draw_terrain(&self.board.terrain, context, graphics, glyphs, &self);
 
                 let y_range = self.get_y_range();
@@ -239,6 +334,8 @@ impl View {
 pub enum DisplayMode {
     /// Normal display mode, like evolv.io had.
     Normal,
+    /// Like `Normal` but creatures are coloured by their species instead of their mouth hue.
+    Species,
     /// Only display tiles.
     Tiles,
     /// Doesn't display anything, lets the simulation go faster because there is no rendering.
@@ -1,16 +1,18 @@
 extern crate clap;
 extern crate lib_evolvim;
 extern crate piston_window;
+extern crate serde;
 
 mod graphics;
 
-use self::graphics::View;
-use clap::{App, Arg};
+use self::graphics::{DrawableBrain, View};
+use clap::{App, Arg, ArgMatches};
+use lib_evolvim::brain::{GenerateRandom, NeuralNet, RecombinationInfinite};
+use lib_evolvim::disaster::DisasterScheduler;
 use lib_evolvim::Board;
 use piston_window::*;
-
-// type BrainType = lib_evolvim::neat::NeatBrain;
-type BrainType = lib_evolvim::brain::Brain;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
 
 fn main() {
     let matches = App::new("Evolvim - GUI launched via CLI")
@@ -41,11 +43,71 @@ fn main() {
                 .requires("input")
                 .help("Saves to the input file when done"),
         )
+        .arg(
+            Arg::with_name("disasters")
+                .long("disasters")
+                .value_name("FILE")
+                .takes_value(true)
+                .help("Load scheduled and random disasters from this JSON file"),
+        )
+        .arg(
+            Arg::with_name("brain-cost")
+                .long("brain-cost")
+                .value_name("ENERGY")
+                .takes_value(true)
+                .help(
+                    "The energy creatures lose every year for every weight or gene in their brain",
+                ),
+        )
+        .arg(
+            Arg::with_name("learning")
+                .long("learning")
+                .takes_value(false)
+                .help(
+                    "Let brains change their weights during their life, the way they learn evolves",
+                ),
+        )
+        .arg(
+            Arg::with_name("brain")
+                .long("brain")
+                .value_name("TYPE")
+                .takes_value(true)
+                .possible_values(&["feed-forward", "recurrent", "neat", "hyperneat", "mixed"])
+                .default_value("feed-forward")
+                .help("The kind of brain creatures have, has to match the input file"),
+        )
         .get_matches();
 
-    let mut view = View::default();
+    match matches.value_of("brain").unwrap() {
+        "recurrent" => run::<lib_evolvim::RecurrentBrain>(&matches),
+        "neat" => run::<lib_evolvim::neat::NeatBrain>(&matches),
+        "hyperneat" => run::<lib_evolvim::neat::HyperNeatBrain>(&matches),
+        "mixed" => run::<lib_evolvim::MixedBrain>(&matches),
+        _ => run::<lib_evolvim::Brain>(&matches),
+    }
+}
+
+fn run<B>(matches: &ArgMatches)
+where
+    B: NeuralNet
+        + GenerateRandom
+        + RecombinationInfinite
+        + DrawableBrain
+        + Serialize
+        + DeserializeOwned,
+{
+    let mut view = View::<B>::default();
     if let Some(filename) = matches.value_of("input") {
-        view.board = Board::<BrainType>::load_from(filename).unwrap();
+        view.board = Board::<B>::load_from(filename).unwrap();
+    }
+    if let Some(filename) = matches.value_of("disasters") {
+        view.board.disasters = DisasterScheduler::load_from(filename).unwrap();
+    }
+    if let Some(cost) = matches.value_of("brain-cost") {
+        view.board.brain_cost = cost.parse().unwrap();
+    }
+    if matches.is_present("learning") {
+        view.board.lifetime_learning = true;
     }
 
     let output_file = if matches.is_present("save") {
@@ -123,6 +185,21 @@ fn main() {
                 Keyboard(Key::Q) => {
                     view.board.selected_creature.deselect();
                 }
+                Keyboard(Key::T) => {
+                    view.next_tool();
+                }
+                Keyboard(Key::F) => {
+                    view.fill_at_mouse();
+                }
+                Keyboard(Key::Z) => {
+                    view.undo_edit();
+                }
+                Keyboard(Key::LeftBracket) => {
+                    view.change_brush_radius(-1.0);
+                }
+                Keyboard(Key::RightBracket) => {
+                    view.change_brush_radius(1.0);
+                }
                 // Keyboard(Key::S) => {
                 //     view.board.save_to("test.bin").unwrap();
                 // }
@@ -152,10 +229,11 @@ fn main() {
         }
 
         window.set_title(format!(
-            "Population size: {}, year: {:.2}, season: {}.",
+            "Population size: {}, year: {:.2}, season: {}, tool: {}.",
             view.board.get_population_size(),
             view.board.get_time(),
-            view.board.get_season()
+            view.board.get_season(),
+            view.get_tool_description()
         ));
     }
 